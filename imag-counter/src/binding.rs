@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Error};
+use std::process::exit;
+use std::result::Result as RResult;
+
+use toml::Value;
+
+use libimagcounter::counter::Counter;
+use libimagcounter::error::CounterError;
+use libimagcounter::group::CounterGroup;
+use libimagstore::store::Store;
+use libimagutil::key_value_split::IntoKeyValue;
+use libimagutil::trace::trace_error;
+
+type Result<T> = RResult<T, CounterError>;
+
+pub enum Binding<'a> {
+    Counter(Counter<'a>),
+    Group(CounterGroup<'a>),
+    Function(String, Box<Fn() -> bool>),
+}
+
+impl<'a> Binding<'a> {
+
+    /// Increment or decrement the counter, or all counters of the group
+    ///
+    /// Does nothing for functions.
+    pub fn change(&mut self, increment: bool) -> Result<()> {
+        match self {
+            &mut Binding::Counter(ref mut ctr) => {
+                debug!("{} counter", if increment { "Incrementing" } else { "Decrementing" });
+                if increment { ctr.inc() } else { ctr.dec() }
+            },
+            &mut Binding::Group(ref group) => {
+                debug!("{} group", if increment { "Incrementing" } else { "Decrementing" });
+                if increment { group.inc_all() } else { group.dec_all() }
+            },
+            &mut Binding::Function(_, _) => Ok(()),
+        }
+    }
+
+}
+
+impl<'a> Display for Binding<'a> {
+
+    fn fmt(&self, fmt: &mut Formatter) -> RResult<(), Error> {
+        match self {
+            &Binding::Counter(ref c) => {
+                match c.name() {
+                    Ok(name) => {
+                        try!(write!(fmt, "{}", name));
+                        if let Ok(value) = c.value() {
+                            try!(write!(fmt, " ({}", value));
+                            if let Ok(Some(unit)) = c.meta().map(|m| m.unit) {
+                                try!(write!(fmt, " {}", unit));
+                            }
+                            try!(write!(fmt, ")"));
+                        }
+                        Ok(())
+                    },
+                    Err(e) => {
+                        trace_error(&e);
+                        Ok(()) // TODO: Find a better way to escalate here.
+                    },
+                }
+            },
+            &Binding::Group(ref g) => {
+                try!(write!(fmt, "{}/*", g.name()));
+                match g.sum() {
+                    Ok(sum) => write!(fmt, " (sum {})", sum),
+                    Err(e) => {
+                        trace_error(&e);
+                        Ok(())
+                    },
+                }
+            },
+            &Binding::Function(ref name, _) => write!(fmt, "{}()", name),
+        }
+    }
+
+}
+
+/// Get the bindings from the `[counter.interactive.bindings]` section of the configuration and
+/// the specifications, which override the configured bindings. Adds a binding for 'q' to quit if
+/// there is no binding to quit. Groups which contain a bound counter are not bound.
+pub fn bindings<'a, 'b, I>(store: &'a Store, config: Option<&Value>, specs: I)
+    -> BTreeMap<char, Binding<'a>>
+    where I: Iterator<Item = &'b str>
+{
+    let mut pairs : BTreeMap<char, Binding> = BTreeMap::new();
+
+    for (key, target) in configured_bindings(config) {
+        match binding_for(store, &target) {
+            Ok(b)  => { pairs.insert(key, b); },
+            Err(e) => { trace_error(&e); },
+        }
+    }
+
+    for spec in specs {
+        match compute_pair(store, spec) {
+            Ok((k, v)) => { pairs.insert(k, v); },
+            Err(e) => { trace_error(&e); },
+        }
+    }
+
+    remove_overlapping_groups(&mut pairs);
+
+    if !has_quit_binding(&pairs) {
+        pairs.insert('q', Binding::Function(String::from("quit"), Box::new(quit)));
+    }
+
+    pairs
+}
+
+/// Read the key bindings from the `[counter.interactive.bindings]` section of the configuration:
+///
+/// ```ignore
+/// [counter.interactive.bindings]
+/// c = "coffee"
+/// w = "water/*"
+/// ```
+fn configured_bindings(config: Option<&Value>) -> Vec<(char, String)> {
+    let section = config
+        .and_then(|c| get_table(c, "counter"))
+        .and_then(|c| get_table(c, "interactive"))
+        .and_then(|c| get_table(c, "bindings"));
+
+    let mut pairs = vec![];
+    if let Some(&Value::Table(ref t)) = section {
+        for (key, target) in t.iter() {
+            match (key.chars().count(), target) {
+                (1, &Value::String(ref target)) => {
+                    pairs.push((key.chars().next().unwrap(), target.clone()));
+                },
+                _ => warn!("Invalid key binding in configuration: '{}' = {:?}", key, target),
+            }
+        }
+    }
+    pairs
+}
+
+fn get_table<'a>(v: &'a Value, key: &str) -> Option<&'a Value> {
+    match v {
+        &Value::Table(ref t) => t.get(key),
+        _ => None,
+    }
+}
+
+fn compute_pair<'a>(store: &'a Store, spec: &str) -> Result<(char, Binding<'a>)> {
+    let kv = String::from(spec).into_kv();
+    if kv.is_none() {
+        debug!("Key-Value parsing failed!");
+        exit(1);
+    }
+    let kv = kv.unwrap();
+
+    let (k, v) = kv.into();
+    if !k.len() == 1 {
+        // We have a key which is not only a single character!
+        exit(1);
+    }
+
+    // TODO uncaught unwrap()
+    binding_for(store, &v).map(|b| (k.chars().next().unwrap(), b))
+}
+
+/// Get the binding for a counter, a group ("name/*") or "quit"
+fn binding_for<'a>(store: &'a Store, target: &str) -> Result<Binding<'a>> {
+    if target.ends_with("/*") {
+        let group = CounterGroup::new(store, String::from(&target[..(target.len() - 2)]));
+        Ok(Binding::Group(group))
+    } else if target == "quit" {
+        Ok(Binding::Function(String::from("quit"), Box::new(quit)))
+    } else {
+        Counter::load(String::from(target), store).map(Binding::Counter)
+    }
+}
+
+/// Remove the bindings of groups which contain a counter which is bound to another key
+///
+/// A bound counter stays borrowed from the store, so changing the group would fail for it.
+fn remove_overlapping_groups(pairs: &mut BTreeMap<char, Binding>) {
+    let counters : Vec<(char, String)> = pairs.iter()
+        .filter_map(|(key, bind)| {
+            match bind {
+                &Binding::Counter(ref c) => c.name().ok().map(|name| (*key, name)),
+                _ => None,
+            }
+        })
+        .collect();
+
+    let overlapping : Vec<char> = pairs.iter()
+        .filter_map(|(key, bind)| {
+            let group = match bind {
+                &Binding::Group(ref g) => g.name(),
+                _ => return None,
+            };
+            let prefix = format!("{}/", group);
+
+            counters.iter()
+                .find(|&&(_, ref name)| name.starts_with(&prefix[..]))
+                .map(|&(counter_key, ref name)| {
+                    warn!("Not binding '{}' to the group '{}/*', its counter '{}' is bound to '{}'",
+                          key, group, name, counter_key);
+                    *key
+                })
+        })
+        .collect();
+
+    for key in overlapping {
+        pairs.remove(&key);
+    }
+}
+
+fn has_quit_binding(pairs: &BTreeMap<char, Binding>) -> bool {
+    pairs.iter()
+        .any(|(_, bind)| {
+            match bind {
+                &Binding::Function(ref name, _) => name == "quit",
+                _ => false,
+            }
+        })
+}
+
+fn quit() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::remove_dir_all;
+
+    use libimagcounter::counter::Counter;
+    use libimagstore::store::Store;
+
+    use super::{Binding, bindings};
+
+    fn store(name: &str) -> Store {
+        let mut path = env::temp_dir();
+        path.push(format!("imag-counter-test-{}", name));
+        let _ = remove_dir_all(&path);
+        Store::new(path, None).unwrap()
+    }
+
+    #[test]
+    fn test_overlapping_group_is_not_bound() {
+        let store = store("overlapping-bindings");
+        for name in vec!["coffee/work", "coffee/home", "water/work"] {
+            let _ = Counter::new(&store, String::from(name), 0).unwrap();
+        }
+
+        let specs = vec!["c=coffee/work", "a=coffee/*", "w=water/*", "o=co/*"];
+        let pairs = bindings(&store, None, specs.into_iter());
+
+        assert!(pairs.get(&'a').is_none());
+        match pairs.get(&'c') {
+            Some(&Binding::Counter(_)) => {},
+            _ => panic!("No counter bound to 'c'"),
+        }
+        match pairs.get(&'w') {
+            Some(&Binding::Group(ref g)) => assert!(g.inc_all().is_ok()),
+            _ => panic!("No group bound to 'w'"),
+        }
+        assert!(pairs.get(&'o').is_some());
+    }
+
+}
@@ -4,6 +4,9 @@ use std::process::exit;
 use libimagrt::runtime::Runtime;
 use libimagutil::trace::trace_error;
 use libimagcounter::counter::Counter;
+use libimagcounter::meta::{CounterMeta, Goal, Period};
+
+use util::int_arg;
 
 pub fn create(rt: &Runtime) {
     rt.cli()
@@ -12,12 +15,47 @@ pub fn create(rt: &Runtime) {
             debug!("Found 'create' subcommand...");
 
             let name = scmd.value_of("name").unwrap(); // safe because clap enforces
+            let name = match scmd.value_of("group") {
+                Some(group) => format!("{}/{}", group, name),
+                None        => String::from(name),
+            };
             let init : i64 = scmd
                 .value_of("initval")
                 .and_then(|i| FromStr::from_str(i).ok())
                 .unwrap_or(0);
 
-            match Counter::new(rt.store(), String::from(name.clone()), init) {
+            let meta = CounterMeta {
+                unit: scmd.value_of("unit").map(String::from),
+                min: int_arg(scmd, "min"),
+                max: int_arg(scmd, "max"),
+                step: int_arg(scmd, "step").unwrap_or(1),
+                goal: int_arg(scmd, "goal").map(|value| {
+                    Goal {
+                        value: value,
+                        period: scmd.value_of("goal-period")
+                            .and_then(Period::from_name)
+                            .unwrap_or(Period::Day),
+                    }
+                }),
+            };
+
+            if meta.step < 1 {
+                warn!("The step size must be positive");
+                exit(1);
+            }
+            if meta.min.and_then(|min| meta.max.map(|max| min > max)).unwrap_or(false) {
+                warn!("The lower bound is greater than the upper bound");
+                exit(1);
+            }
+            if !meta.in_bounds(init) {
+                warn!("The initial value '{}' is out of bounds", init);
+                exit(1);
+            }
+
+            let res = Counter::new(rt.store(), name.clone(), init)
+                .and_then(|mut counter| counter.set_meta(&meta));
+
+            match res {
                 Err(e) => {
                     warn!("Could not create Counter '{}' with initial value '{}'", name, init);
                     trace_error(&e);
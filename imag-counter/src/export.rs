@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+
+use rustc_serialize::json::Json;
+
+use libimagcounter::counter::Counter;
+use libimagcounter::event::CounterEvent;
+use libimagcounter::event::TIMESTAMP_FORMAT;
+use libimagrt::runtime::Runtime;
+use libimagutil::trace::trace_error;
+
+use range::date_range;
+
+pub fn export(rt: &Runtime) {
+    rt.cli()
+        .subcommand_matches("export")
+        .map(|scmd| {
+            debug!("Found 'export' subcommand...");
+
+            let name = String::from(scmd.value_of("name").unwrap()); // safe because clap enforces
+            let (from, to) = date_range(scmd);
+
+            let counter = Counter::load(name, rt.store()).unwrap_or_else(|e| {
+                trace_error(&e);
+                exit(1);
+            });
+
+            let rows = counter.history()
+                .unwrap_or_else(|e| {
+                    trace_error(&e);
+                    exit(1);
+                })
+                .into_iter()
+                .filter(|&(ref ev, _)| from.map(|f| ev.timestamp >= f).unwrap_or(true))
+                .filter(|&(ref ev, _)| to.map(|t| ev.timestamp <= t).unwrap_or(true))
+                .collect::<Vec<_>>();
+
+            match scmd.value_of("format") {
+                Some("json") => print_json(&rows),
+                _            => print_csv(&rows),
+            }
+        });
+}
+
+/// The change of the event. The initial value of the counter is not a change, like in
+/// `Counter::goal_progress()`, so its row has the delta 0.
+fn delta(ev: &CounterEvent) -> i64 {
+    if ev.initial { 0 } else { ev.delta }
+}
+
+fn print_csv(rows: &[(CounterEvent, i64)]) {
+    println!("timestamp,delta,value,note");
+    for &(ref ev, value) in rows {
+        let note = ev.note.as_ref().map(|n| n.replace("\"", "\"\"")).unwrap_or(String::new());
+        println!("{},{},{},\"{}\"", ev.timestamp.format(TIMESTAMP_FORMAT), delta(ev), value, note);
+    }
+}
+
+fn print_json(rows: &[(CounterEvent, i64)]) {
+    let rows = rows.iter()
+        .map(|&(ref ev, value)| {
+            let mut obj = BTreeMap::new();
+            let ts = ev.timestamp.format(TIMESTAMP_FORMAT).to_string();
+            obj.insert(String::from("timestamp"), Json::String(ts));
+            obj.insert(String::from("delta"), Json::I64(delta(ev)));
+            obj.insert(String::from("value"), Json::I64(value));
+            let note = ev.note.clone().map(Json::String).unwrap_or(Json::Null);
+            obj.insert(String::from("note"), note);
+            Json::Object(obj)
+        })
+        .collect();
+
+    println!("{}", Json::Array(rows).pretty());
+}
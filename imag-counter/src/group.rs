@@ -0,0 +1,52 @@
+use std::process::exit;
+
+use libimagrt::runtime::Runtime;
+use libimagutil::trace::trace_error;
+use libimagcounter::group::CounterGroup;
+
+pub fn group(rt: &Runtime) {
+    rt.cli()
+        .subcommand_matches("group")
+        .map(|scmd| {
+            debug!("Found 'group' subcommand...");
+
+            let name  = String::from(scmd.value_of("name").unwrap()); // safe because clap enforces
+            let group = CounterGroup::new(rt.store(), name);
+
+            let res = if scmd.is_present("inc-all") {
+                group.inc_all()
+            } else if scmd.is_present("dec-all") {
+                group.dec_all()
+            } else if scmd.is_present("reset-all") {
+                group.reset_all()
+            } else {
+                Ok(())
+            };
+
+            if let Err(e) = res {
+                trace_error(&e);
+                exit(1);
+            }
+
+            let values = group.values().unwrap_or_else(|e| {
+                trace_error(&e);
+                exit(1);
+            });
+
+            for &(ref name, value) in values.iter() {
+                println!("{} - {}", name, value);
+            }
+
+            let sum_and_average = group.sum().and_then(|s| group.average().map(|a| (s, a)));
+            let (sum, average) = sum_and_average.unwrap_or_else(|e| {
+                trace_error(&e);
+                exit(1);
+            });
+
+            println!("---");
+            match average {
+                Some(average) => println!("sum: {}, average: {:.2}", sum, average),
+                None          => println!("sum: {}, average: -", sum),
+            }
+        });
+}
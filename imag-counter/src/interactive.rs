@@ -1,18 +1,15 @@
 use std::collections::BTreeMap;
-use std::fmt::{Display, Formatter, Error};
 use std::io::Write;
 use std::io::stderr;
 use std::io::stdin;
 use std::process::exit;
-use std::result::Result as RResult;
 
-use libimagcounter::counter::Counter;
-use libimagcounter::error::CounterError;
+use libimagcounter::error::CounterErrorKind;
 use libimagrt::runtime::Runtime;
-use libimagutil::key_value_split::IntoKeyValue;
 use libimagutil::trace::trace_error;
 
-type Result<T> = RResult<T, CounterError>;
+use binding::{Binding, bindings};
+use tui::run_tui;
 
 pub fn interactive(rt: &Runtime) {
     let scmd = rt.cli().subcommand_matches("interactive");
@@ -23,17 +20,16 @@ pub fn interactive(rt: &Runtime) {
     let scmd = scmd.unwrap();
     debug!("Found 'interactive' command");
 
-    let mut pairs : BTreeMap<char, Binding> = BTreeMap::new();
+    let specs : Vec<&str> = scmd.values_of("spec").map(|v| v.collect()).unwrap_or(vec![]);
+    let config = rt.config().map(|c| c.config());
+    let mut pairs = bindings(rt.store(), config, specs.into_iter());
 
-    for spec in scmd.values_of("spec").unwrap() {
-        match compute_pair(rt, &spec) {
-            Ok((k, v)) => { pairs.insert(k, v); },
-            Err(e) => { trace_error(&e); },
+    if scmd.is_present("tui") {
+        if let Err(e) = run_tui(&mut pairs) {
+            trace_error(&e);
+            exit(1);
         }
-    }
-
-    if !has_quit_binding(&pairs) {
-        pairs.insert('q', Binding::Function(String::from("quit"), Box::new(quit)));
+        return;
     }
 
     stderr().flush().ok();
@@ -53,29 +49,7 @@ pub fn interactive(rt: &Runtime) {
 
         let cont = if input.len() > 0 {
             let increment = match input.chars().next() { Some('-') => false, _ => true };
-            input.chars().all(|chr| {
-                match pairs.get_mut(&chr) {
-                    Some(&mut Binding::Counter(ref mut ctr)) => {
-                        if increment {
-                            debug!("Incrementing");
-                            if let Err(e) = ctr.inc() {
-                                trace_error(&e);
-                            }
-                        } else {
-                            debug!("Decrementing");
-                            if let Err(e) = ctr.dec() {
-                                trace_error(&e);
-                            }
-                        }
-                        true
-                    },
-                    Some(&mut Binding::Function(ref name, ref f))   => {
-                        debug!("Calling {}", name);
-                        f()
-                    },
-                    None => true,
-                }
-            })
+            input.chars().all(|chr| apply(&mut pairs, chr, increment))
         } else {
             println!("No input...");
             println!("\tUse a single character to increment the counter which is bound to it");
@@ -91,67 +65,23 @@ pub fn interactive(rt: &Runtime) {
     }
 }
 
-fn has_quit_binding(pairs: &BTreeMap<char, Binding>) -> bool {
-    pairs.iter()
-        .any(|(_, bind)| {
-            match bind {
-                &Binding::Function(ref name, _) => name == "quit",
-                _ => false,
+/// Apply the binding for `chr`, returns false if the program should exit
+fn apply(pairs: &mut BTreeMap<char, Binding>, chr: char, increment: bool) -> bool {
+    match pairs.get_mut(&chr) {
+        Some(&mut Binding::Function(ref name, ref f)) => {
+            debug!("Calling {}", name);
+            f()
+        },
+        Some(binding) => {
+            match binding.change(increment) {
+                Err(ref e) if e.err_type() == CounterErrorKind::ValueOutOfBounds => {
+                    println!("Refusing to change {}, it would be out of bounds", chr);
+                },
+                Err(e) => trace_error(&e),
+                Ok(_)  => { },
             }
-        })
-}
-
-enum Binding<'a> {
-    Counter(Counter<'a>),
-    Function(String, Box<Fn() -> bool>),
-}
-
-impl<'a> Display for Binding<'a> {
-
-    fn fmt(&self, fmt: &mut Formatter) -> RResult<(), Error> {
-        match self {
-            &Binding::Counter(ref c) => {
-                match c.name() {
-                    Ok(name) => {
-                        try!(write!(fmt, "{}", name));
-                        Ok(())
-                    },
-                    Err(e) => {
-                        trace_error(&e);
-                        Ok(()) // TODO: Find a better way to escalate here.
-                    },
-                }
-            },
-            &Binding::Function(ref name, _) => write!(fmt, "{}()", name),
-        }
-    }
-
-}
-
-fn compute_pair<'a>(rt: &'a Runtime, spec: &str) -> Result<(char, Binding<'a>)> {
-    let kv = String::from(spec).into_kv();
-    if kv.is_none() {
-        debug!("Key-Value parsing failed!");
-        exit(1);
-    }
-    let kv = kv.unwrap();
-
-    let (k, v) = kv.into();
-    if !k.len() == 1 {
-        // We have a key which is not only a single character!
-        exit(1);
-    }
-
-    if v == "quit" {
-        // TODO uncaught unwrap()
-        Ok((k.chars().next().unwrap(), Binding::Function(String::from("quit"), Box::new(quit))))
-    } else {
-        // TODO uncaught unwrap()
-        Counter::load(v, rt.store()).and_then(|ctr| Ok((k.chars().next().unwrap(), Binding::Counter(ctr))))
+            true
+        },
+        None => true,
     }
 }
-
-fn quit() -> bool {
-    false
-}
-
@@ -2,13 +2,24 @@ use libimagrt::runtime::Runtime;
 use libimagutil::trace::trace_error;
 use libimagcounter::counter::Counter;
 
+use util::int_arg;
+
 pub fn list(rt: &Runtime) {
     rt.cli()
         .subcommand_matches("list")
-        .map(|_| {
+        .map(|scmd| {
             debug!("Found 'list' subcommand...");
 
-            Counter::all_counters(rt.store()).map(|iterator| {
+            let greater = int_arg(scmd, "greater-than");
+            let lower   = int_arg(scmd, "lower-than");
+            let equals  = int_arg(scmd, "equals");
+
+            let counters = match scmd.value_of("group") {
+                Some(group) => Counter::in_group(rt.store(), group),
+                None        => Counter::all_counters(rt.store()),
+            };
+
+            counters.map(|iterator| {
                 for counter in iterator {
                     counter.map(|c| {
                         let name    = c.name();
@@ -21,7 +32,13 @@ pub fn list(rt: &Runtime) {
                             if value.is_err() {
                                 trace_error(&value.unwrap_err());
                             } else {
-                                println!("{} - {}", name.unwrap(), value.unwrap());
+                                let value = value.unwrap();
+                                if greater.map(|g| value > g).unwrap_or(true) &&
+                                    lower.map(|l| value < l).unwrap_or(true) &&
+                                    equals.map(|e| value == e).unwrap_or(true)
+                                {
+                                    println!("{} - {}{}", name.unwrap(), value, details(&c));
+                                }
                             }
                         }
                     })
@@ -33,3 +50,25 @@ pub fn list(rt: &Runtime) {
 
         });
 }
+
+/// The unit of the counter and the progress toward its goal, for example " cups (3/8 per day)"
+fn details(c: &Counter) -> String {
+    let mut details = String::new();
+
+    match c.meta() {
+        Ok(meta) => if let Some(unit) = meta.unit {
+            details.push_str(&format!(" {}", unit));
+        },
+        Err(e) => trace_error(&e),
+    }
+
+    match c.goal_progress() {
+        Ok(Some((progress, goal))) => {
+            details.push_str(&format!(" ({}/{} per {})", progress, goal.value, goal.period.name()));
+        },
+        Ok(None) => { },
+        Err(e) => trace_error(&e),
+    }
+
+    details
+}
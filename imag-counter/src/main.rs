@@ -15,10 +15,15 @@
 
 #[macro_use] extern crate log;
 #[macro_use] extern crate version;
+extern crate chrono;
 extern crate clap;
+extern crate rustbox;
+extern crate rustc_serialize;
+extern crate toml;
 
 extern crate libimagcounter;
 extern crate libimagrt;
+extern crate libimagstore;
 extern crate libimagutil;
 
 use std::process::exit;
@@ -29,17 +34,27 @@ use libimagcounter::counter::Counter;
 use libimagutil::trace::trace_error;
 use libimagutil::key_value_split::IntoKeyValue;
 
+mod binding;
 mod create;
 mod delete;
+mod export;
+mod group;
 mod interactive;
 mod list;
+mod range;
+mod stats;
+mod tui;
 mod ui;
+mod util;
 
 use ui::build_ui;
 use create::create;
 use delete::delete;
+use export::export;
+use group::group;
 use interactive::interactive;
 use list::list;
+use stats::stats;
 
 enum Action {
     Inc,
@@ -137,8 +152,11 @@ fn main() {
                 match name {
                     "create"      => create(&rt),
                     "delete"      => delete(&rt),
+                    "export"      => export(&rt),
+                    "group"       => group(&rt),
                     "interactive" => interactive(&rt),
                     "list"        => list(&rt),
+                    "stats"       => stats(&rt),
                     _ => {
                         debug!("Unknown command"); // More error handling
                     },
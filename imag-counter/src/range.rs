@@ -0,0 +1,71 @@
+use std::process::exit;
+
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use clap::ArgMatches;
+
+use libimagcounter::event::TIMESTAMP_FORMAT;
+
+/// Parse a date ("2016-07-01") or timestamp ("2016-07-01T12:00:00")
+///
+/// A date is the beginning of the day, or the end of the day if `end_of_day` is set, so a range
+/// "--from 2016-07-01 --to 2016-07-01" contains the whole day.
+pub fn parse_date(s: &str, end_of_day: bool) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, TIMESTAMP_FORMAT)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .map(|d| if end_of_day { d.and_hms(23, 59, 59) } else { d.and_hms(0, 0, 0) })
+        })
+}
+
+/// Get the range passed with "--from" and "--to", exits if one of them is not a date
+pub fn date_range(scmd: &ArgMatches) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+    let get = |name: &str, end_of_day: bool| {
+        scmd.value_of(name)
+            .map(|s| {
+                match parse_date(s, end_of_day) {
+                    Some(d) => d,
+                    None => {
+                        warn!("Not a date: '{}', use YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS", s);
+                        exit(1);
+                    },
+                }
+            })
+    };
+
+    (get("from", false), get("to", true))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::parse_date;
+
+    #[test]
+    fn test_parse_date() {
+        let day = NaiveDate::from_ymd(2016, 7, 1);
+        assert_eq!(Some(day.and_hms(0, 0, 0)), parse_date("2016-07-01", false));
+        assert_eq!(Some(day.and_hms(23, 59, 59)), parse_date("2016-07-01", true));
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let ts = NaiveDate::from_ymd(2016, 7, 1).and_hms(12, 30, 0);
+        assert_eq!(Some(ts), parse_date("2016-07-01T12:30:00", false));
+
+        // An explicit time is not moved to the end of the day
+        assert_eq!(Some(ts), parse_date("2016-07-01T12:30:00", true));
+    }
+
+    #[test]
+    fn test_parse_invalid_date() {
+        assert_eq!(None, parse_date("", false));
+        assert_eq!(None, parse_date("yesterday", false));
+        assert_eq!(None, parse_date("2016-13-01", true));
+        assert_eq!(None, parse_date("2016-07-01 12:30:00", false));
+    }
+
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+
+use chrono::Local;
+use chrono::NaiveDate;
+
+use libimagcounter::counter::Counter;
+use libimagcounter::event::CounterEvent;
+use libimagcounter::meta::Period;
+use libimagrt::runtime::Runtime;
+use libimagutil::trace::trace_error;
+
+use range::date_range;
+
+pub fn stats(rt: &Runtime) {
+    rt.cli()
+        .subcommand_matches("stats")
+        .map(|scmd| {
+            debug!("Found 'stats' subcommand...");
+
+            let name = String::from(scmd.value_of("name").unwrap()); // safe because clap enforces
+            let (from, to) = date_range(scmd);
+            let period = scmd.value_of("period")
+                .and_then(Period::from_name)
+                .unwrap_or(Period::Day);
+
+            let counter = Counter::load(name, rt.store()).unwrap_or_else(|e| {
+                trace_error(&e);
+                exit(1);
+            });
+
+            let events = counter.events_between(from, to).unwrap_or_else(|e| {
+                trace_error(&e);
+                exit(1);
+            });
+
+            let first = from.map(|f| f.date()).or(events.first().map(|ev| ev.timestamp.date()));
+            let last  = to.map(|t| t.date()).unwrap_or(Local::today().naive_local());
+            let first = match first {
+                Some(first) => first,
+                None => {
+                    info!("No changes recorded");
+                    return;
+                },
+            };
+
+            let totals = period_totals(&events, period, first, last);
+            for (key, total) in totals.iter() {
+                println!("{} {: >6}", key, total);
+            }
+
+            let sum : i64 = totals.values().fold(0, |acc, t| acc + t);
+            let average = if totals.is_empty() { 0.0 } else { sum as f64 / totals.len() as f64 };
+            let (longest, current) = streaks(&totals);
+
+            println!("---");
+            println!("periods: {}, total: {}, average: {:.2}", totals.len(), sum, average);
+            println!("longest streak: {}, current streak: {}", longest, current);
+        });
+}
+
+/// Sum up the changes per period, with all periods from `first` to `last`, so periods without
+/// changes are counted as well. The keys are the `Period::key()`s of the periods.
+///
+/// The initial value of the counter is not a change, like in `Counter::goal_progress()`.
+fn period_totals(events: &[CounterEvent], period: Period, first: NaiveDate, last: NaiveDate)
+    -> BTreeMap<String, i64>
+{
+    let mut totals = BTreeMap::new();
+    let mut day = first;
+    while day <= last {
+        totals.insert(period.key(&day), 0);
+        day = day.succ();
+    }
+
+    for ev in events.iter().filter(|ev| !ev.initial) {
+        *totals.entry(period.key(&ev.timestamp.date())).or_insert(0) += ev.delta;
+    }
+
+    totals
+}
+
+/// Get the longest streak and the current streak, which ends with the last period. Streaks are
+/// consecutive periods in which the counter was increased.
+fn streaks(totals: &BTreeMap<String, i64>) -> (usize, usize) {
+    totals.values()
+        .fold((0, 0), |(longest, current), total| {
+            let current = if *total > 0 { current + 1 } else { 0 };
+            (if current > longest { current } else { longest }, current)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use chrono::NaiveDate;
+
+    use libimagcounter::event::CounterEvent;
+    use libimagcounter::meta::Period;
+
+    use super::{period_totals, streaks};
+
+    fn event(y: i32, m: u32, d: u32, delta: i64) -> CounterEvent {
+        CounterEvent {
+            timestamp: NaiveDate::from_ymd(y, m, d).and_hms(12, 0, 0),
+            delta: delta,
+            note: None,
+            initial: false,
+        }
+    }
+
+    fn totals(totals: Vec<(&str, i64)>) -> BTreeMap<String, i64> {
+        totals.into_iter().map(|(k, t)| (String::from(k), t)).collect()
+    }
+
+    #[test]
+    fn test_period_totals_days() {
+        let events = vec![event(2016, 7, 1, 2), event(2016, 7, 1, 3), event(2016, 7, 3, -1)];
+        let first  = NaiveDate::from_ymd(2016, 7, 1);
+        let last   = NaiveDate::from_ymd(2016, 7, 4);
+
+        assert_eq!(totals(vec![("2016-07-01", 5), ("2016-07-02", 0), ("2016-07-03", -1),
+                               ("2016-07-04", 0)]),
+                   period_totals(&events, Period::Day, first, last));
+    }
+
+    #[test]
+    fn test_period_totals_weeks_and_months() {
+        // 2016-07-03 is a sunday, 2016-07-04 a monday
+        let events = vec![event(2016, 7, 3, 1), event(2016, 7, 4, 2), event(2016, 8, 1, 4)];
+        let first  = NaiveDate::from_ymd(2016, 7, 3);
+        let last   = NaiveDate::from_ymd(2016, 8, 1);
+
+        assert_eq!(totals(vec![("2016-W26", 1), ("2016-W27", 2), ("2016-W28", 0),
+                               ("2016-W29", 0), ("2016-W30", 0), ("2016-W31", 4)]),
+                   period_totals(&events, Period::Week, first, last));
+        assert_eq!(totals(vec![("2016-07", 3), ("2016-08", 4)]),
+                   period_totals(&events, Period::Month, first, last));
+    }
+
+    #[test]
+    fn test_period_totals_without_initial_value() {
+        let mut init = event(2016, 7, 1, 100);
+        init.initial = true;
+        let events = vec![init, event(2016, 7, 2, 1)];
+        let first  = NaiveDate::from_ymd(2016, 7, 1);
+        let last   = NaiveDate::from_ymd(2016, 7, 2);
+
+        let totals_ = period_totals(&events, Period::Day, first, last);
+        assert_eq!(totals(vec![("2016-07-01", 0), ("2016-07-02", 1)]), totals_);
+        assert_eq!((1, 1), streaks(&totals_));
+    }
+
+    #[test]
+    fn test_period_totals_empty_range() {
+        let first = NaiveDate::from_ymd(2016, 7, 2);
+        let last  = NaiveDate::from_ymd(2016, 7, 1);
+        assert!(period_totals(&[], Period::Day, first, last).is_empty());
+    }
+
+    #[test]
+    fn test_streaks() {
+        assert_eq!((0, 0), streaks(&totals(vec![])));
+        assert_eq!((0, 0), streaks(&totals(vec![("a", 0), ("b", -1)])));
+        assert_eq!((3, 1),
+                   streaks(&totals(vec![("a", 1), ("b", 2), ("c", 1), ("d", 0), ("e", 4)])));
+        assert_eq!((2, 0), streaks(&totals(vec![("a", 1), ("b", 2), ("c", 0)])));
+        assert_eq!((2, 2), streaks(&totals(vec![("a", 1), ("b", -2), ("c", 1), ("d", 1)])));
+    }
+
+}
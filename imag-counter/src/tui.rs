@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+use std::default::Default;
+use std::iter::repeat;
+
+use rustbox::{Color, Event, InitError, Key, RustBox};
+use rustbox::RB_NORMAL;
+
+use libimagcounter::error::CounterErrorKind;
+
+use binding::Binding;
+
+const HELP : &'static str = "Press a key to increment, '-' and a key to decrement, \
+                             Backspace to undo, Esc to quit";
+
+/// A change done by a key press, which can be undone
+struct Change {
+    key: char,
+    increment: bool,
+}
+
+/// Run the full-screen interface on the terminal, reading the keys without waiting for Enter
+pub fn run_tui(pairs: &mut BTreeMap<char, Binding>) -> Result<(), InitError> {
+    let rb = try!(RustBox::init(Default::default()));
+
+    let keys = repeat(())
+        .map(|_| rb.poll_event(false))
+        .take_while(|ev| {
+            match ev {
+                &Err(ref e) => {
+                    warn!("Could not read from the terminal: {:?}", e);
+                    false
+                },
+                &Ok(_) => true,
+            }
+        })
+        .filter_map(|ev| {
+            match ev {
+                Ok(Event::KeyEvent(key)) => Some(key),
+                _ => None,
+            }
+        });
+
+    run(pairs, keys, |lines| draw(&rb, lines));
+    Ok(())
+}
+
+/// Run the interface with the keys from `keys` until the keys are exhausted or the user quits.
+/// The screen is passed to `draw` as lines of text after each key.
+pub fn run<I, F>(pairs: &mut BTreeMap<char, Binding>, keys: I, mut draw: F)
+    where I: Iterator<Item = Key>,
+          F: FnMut(&[String])
+{
+    let mut undo : Vec<Change> = vec![];
+    let mut decrement = false;
+    let mut status = String::from(HELP);
+
+    draw(&screen(pairs, &status, decrement)[..]);
+    for key in keys {
+        status = String::new();
+
+        match key {
+            Key::Esc | Key::Ctrl('c') => break,
+
+            Key::Char('-') if !pairs.contains_key(&'-') => decrement = !decrement,
+
+            Key::Backspace | Key::Ctrl('z') => {
+                status = match undo.pop() {
+                    Some(change) => {
+                        let res = pairs.get_mut(&change.key)
+                            .map(|b| b.change(!change.increment))
+                            .unwrap_or(Ok(()));
+                        match res {
+                            Ok(_)  => format!("Undone: {}", change.key),
+                            Err(e) => format!("Undoing {} failed: {}", change.key, e),
+                        }
+                    },
+                    None => String::from("Nothing to undo"),
+                };
+            },
+
+            Key::Char(c) => {
+                let quit = match pairs.get_mut(&c) {
+                    Some(&mut Binding::Function(ref name, ref f)) => {
+                        debug!("Calling {}", name);
+                        !f()
+                    },
+                    Some(binding) => {
+                        let increment = !decrement;
+                        match binding.change(increment) {
+                            Ok(_) => undo.push(Change { key: c, increment: increment }),
+                            Err(ref e) if e.err_type() == CounterErrorKind::ValueOutOfBounds => {
+                                status = format!("Refusing to change {}, it would be out of bounds",
+                                                 c);
+                            },
+                            Err(e) => status = format!("Changing {} failed: {}", c, e),
+                        }
+                        false
+                    },
+                    None => {
+                        status = format!("Nothing is bound to '{}'", c);
+                        false
+                    },
+                };
+                decrement = false;
+
+                if quit {
+                    break;
+                }
+            },
+
+            _ => { },
+        }
+
+        draw(&screen(pairs, &status, decrement)[..]);
+    }
+}
+
+/// The table of bindings with their values and the status line
+fn screen(pairs: &BTreeMap<char, Binding>, status: &str, decrement: bool) -> Vec<String> {
+    let mut lines = vec![];
+    lines.push(format!("imag-counter{}", if decrement { " (decrement)" } else { "" }));
+    lines.push(String::new());
+    for (k, v) in pairs.iter() {
+        lines.push(format!("  [{}]  {}", k, v));
+    }
+    lines.push(String::new());
+    lines.push(String::from(status));
+    lines
+}
+
+fn draw(rb: &RustBox, lines: &[String]) {
+    rb.clear();
+    for (y, line) in lines.iter().enumerate() {
+        rb.print(0, y, RB_NORMAL, Color::Default, Color::Default, &line[..]);
+    }
+    rb.present();
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs::remove_dir_all;
+
+    use rustbox::Key;
+
+    use libimagcounter::counter::Counter;
+    use libimagcounter::meta::CounterMeta;
+    use libimagstore::store::Store;
+
+    use binding::Binding;
+    use super::run;
+
+    fn store(name: &str) -> Store {
+        let mut path = env::temp_dir();
+        path.push(format!("imag-counter-test-{}", name));
+        let _ = remove_dir_all(&path);
+        Store::new(path, None).unwrap()
+    }
+
+    fn value(pairs: &BTreeMap<char, Binding>, key: char) -> i64 {
+        match pairs.get(&key) {
+            Some(&Binding::Counter(ref c)) => c.value().unwrap(),
+            _ => panic!("No counter bound to {}", key),
+        }
+    }
+
+    #[test]
+    fn test_change_and_undo() {
+        let store = store("change-and-undo");
+        let mut pairs = BTreeMap::new();
+        pairs.insert('c', Binding::Counter(Counter::new(&store, String::from("c"), 0).unwrap()));
+
+        let keys = vec![Key::Char('c'), Key::Char('c'), Key::Char('-'), Key::Char('c'),
+                        Key::Backspace, Key::Esc, Key::Char('c')];
+        let mut screens = vec![];
+        run(&mut pairs, keys.into_iter(), |lines| screens.push(lines.join("\n")));
+
+        assert_eq!(2, value(&pairs, 'c'));
+        assert_eq!(6, screens.len());
+        assert!(screens[3].starts_with("imag-counter (decrement)"));
+        assert!(screens[5].ends_with("Undone: c"));
+    }
+
+    #[test]
+    fn test_out_of_bounds_is_refused() {
+        let store = store("out-of-bounds");
+        let mut counter = Counter::new(&store, String::from("b"), 0).unwrap();
+        let meta = CounterMeta { max: Some(1), ..CounterMeta::default() };
+        counter.set_meta(&meta).unwrap();
+
+        let mut pairs = BTreeMap::new();
+        pairs.insert('b', Binding::Counter(counter));
+
+        let keys = vec![Key::Char('b'), Key::Char('b'), Key::Backspace];
+        let mut screens = vec![];
+        run(&mut pairs, keys.into_iter(), |lines| screens.push(lines.join("\n")));
+
+        // The refused change is not undone, the first one is
+        assert_eq!(0, value(&pairs, 'b'));
+        assert!(screens[2].ends_with("Refusing to change b, it would be out of bounds"));
+    }
+
+}
@@ -1,4 +1,4 @@
-use clap::{Arg, App, SubCommand};
+use clap::{Arg, ArgGroup, App, SubCommand};
 
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
@@ -37,12 +37,52 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .takes_value(true)
                         .required(true)
                         .help("Create counter with this name"))
+                   .arg(Arg::with_name("group")
+                        .long("group")
+                        .short("G")
+                        .takes_value(true)
+                        .required(false)
+                        .help("Create the counter in this group (same as the name 'GROUP/NAME')"))
                    .arg(Arg::with_name("initval")
                         .long("init")
                         .short("i")
                         .takes_value(true)
                         .required(false)
-                        .help("Initial value")))
+                        .help("Initial value"))
+                   .arg(Arg::with_name("unit")
+                        .long("unit")
+                        .short("u")
+                        .takes_value(true)
+                        .required(false)
+                        .help("What is counted, for example 'cups' or 'km'"))
+                   .arg(Arg::with_name("min")
+                        .long("min")
+                        .takes_value(true)
+                        .required(false)
+                        .help("The counter cannot be changed to a value less than this"))
+                   .arg(Arg::with_name("max")
+                        .long("max")
+                        .takes_value(true)
+                        .required(false)
+                        .help("The counter cannot be changed to a value greater than this"))
+                   .arg(Arg::with_name("step")
+                        .long("step")
+                        .takes_value(true)
+                        .required(false)
+                        .help("Increment and decrement by this value (default: 1)"))
+                   .arg(Arg::with_name("goal")
+                        .long("goal")
+                        .short("g")
+                        .takes_value(true)
+                        .required(false)
+                        .help("Increase the counter by this value each period"))
+                   .arg(Arg::with_name("goal-period")
+                        .long("goal-period")
+                        .takes_value(true)
+                        .required(false)
+                        .requires("goal")
+                        .possible_values(&["day", "week", "month"])
+                        .help("The period of the goal, 'day' (default), 'week' or 'month'")))
 
         .subcommand(SubCommand::with_name("delete")
                    .about("Delete a counter")
@@ -64,6 +104,13 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .required(false)
                         .help("List counters with this name (foo/bar and baz/bar would match 'bar')"))
 
+                   .arg(Arg::with_name("group")
+                        .long("group")
+                        .short("G")
+                        .takes_value(true)
+                        .required(false)
+                        .help("List the counters in this group (and its subgroups)"))
+
                    .arg(Arg::with_name("greater-than")
                         .long("greater")
                         .short("g")
@@ -86,6 +133,72 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .help("List counters which equal VALUE"))
         )
 
+        .subcommand(SubCommand::with_name("group")
+                   .about("Show the counters of a group with their sum and average, or change them")
+                   .version("0.1")
+                   .arg(Arg::with_name("name")
+                        .long("name")
+                        .short("n")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The name of the group, 'coffee' for the counter 'coffee/work'"))
+                   .arg(Arg::with_name("inc-all")
+                        .long("inc-all")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Increment all counters in the group"))
+                   .arg(Arg::with_name("dec-all")
+                        .long("dec-all")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Decrement all counters in the group"))
+                   .arg(Arg::with_name("reset-all")
+                        .long("reset-all")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Reset all counters in the group"))
+                   .group(ArgGroup::with_name("group-action")
+                          .args(&["inc-all", "dec-all", "reset-all"])
+                          .required(false)))
+
+        .subcommand(SubCommand::with_name("export")
+                   .about("Export the changes of a counter")
+                   .version("0.1")
+                   .arg(Arg::with_name("name")
+                        .long("name")
+                        .short("n")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Export the counter with this name"))
+                   .arg(from_arg())
+                   .arg(to_arg())
+                   .arg(Arg::with_name("format")
+                        .long("format")
+                        .short("f")
+                        .takes_value(true)
+                        .required(false)
+                        .possible_values(&["csv", "json"])
+                        .help("Export format, 'csv' (default) or 'json'")))
+
+        .subcommand(SubCommand::with_name("stats")
+                   .about("Show the changes of a counter per day, week or month")
+                   .version("0.1")
+                   .arg(Arg::with_name("name")
+                        .long("name")
+                        .short("n")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Show statistics for the counter with this name"))
+                   .arg(from_arg())
+                   .arg(to_arg())
+                   .arg(Arg::with_name("period")
+                        .long("period")
+                        .short("p")
+                        .takes_value(true)
+                        .required(false)
+                        .possible_values(&["day", "week", "month"])
+                        .help("Sum up the changes per 'day' (default), 'week' or 'month'")))
+
         .subcommand(SubCommand::with_name("interactive")
                    .about("Interactively count things")
                    .version("0.1")
@@ -94,9 +207,37 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .short("s")
                         .takes_value(true)
                         .multiple(true)
-                        .required(true)
+                        .required(false)
                         .help("Specification for key-bindings. Use <KEY>=<VALUE> where KEY is the
                         key to bind (single character) and VALUE is the path to the counter to bind
-                        to.")))
+                        to, or the name of a group followed by '/*' to bind to all counters in
+                        the group. Overrides the bindings in the [counter.interactive.bindings]
+                        section of the configuration."))
+                   .arg(Arg::with_name("tui")
+                        .long("tui")
+                        .short("t")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Full-screen mode: react on key presses without Enter and show the
+                        values of the counters. Use '-' before a key to decrement, Backspace to
+                        undo the last change and Esc to quit.")))
+}
+
+
+fn from_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("from")
+        .long("from")
+        .takes_value(true)
+        .required(false)
+        .value_name("DATE")
+        .help("Only use changes at or after DATE (YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS)")
 }
 
+fn to_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("to")
+        .long("to")
+        .takes_value(true)
+        .required(false)
+        .value_name("DATE")
+        .help("Only use changes at or before DATE (YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS)")
+}
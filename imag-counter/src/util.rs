@@ -0,0 +1,18 @@
+use std::process::exit;
+use std::str::FromStr;
+
+use clap::ArgMatches;
+
+/// Get the integer passed with the argument, exits if it is not an integer
+pub fn int_arg(scmd: &ArgMatches, name: &str) -> Option<i64> {
+    scmd.value_of(name)
+        .map(|v| {
+            match FromStr::from_str(v) {
+                Ok(i) => i,
+                Err(_) => {
+                    warn!("Not an integer: '{}'", v);
+                    exit(1);
+                },
+            }
+        })
+}
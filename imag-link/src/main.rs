@@ -64,6 +64,7 @@ fn main() {
             match name {
                 "internal" => handle_internal_linking(&rt),
                 "external" => handle_external_linking(&rt),
+                "graph"    => handle_graph(&rt),
                 _ => {
                     warn!("No commandline call");
                     exit(1);
@@ -74,23 +75,68 @@ fn main() {
 
 fn handle_internal_linking(rt: &Runtime) {
     use libimagentrylink::internal::InternalLinker;
+    use libimagentrylink::kind::LinkKind;
     use libimagutil::trace::trace_error;
 
     debug!("Handle internal linking call");
     let cmd = rt.cli().subcommand_matches("internal").unwrap();
 
-    if cmd.is_present("list") {
+    if cmd.is_present("migrate") {
+        use libimagentrylink::internal::migrate_all_links;
+
+        debug!("Migrating links...");
+        match migrate_all_links(rt.store()) {
+            Ok(ids) => {
+                for id in ids {
+                    println!("Migrated links of {}", id.display());
+                }
+            },
+            Err(e) => {
+                trace_error(&e);
+                exit(1);
+            },
+        }
+    } else if cmd.is_present("repair") {
+        use libimagentrylink::internal::{Repair, repair_links};
+
+        debug!("Repairing links...");
+        match repair_links(rt.store()) {
+            Ok(repairs) => {
+                for repair in repairs {
+                    match repair {
+                        Repair::AddedBacklink(id, link) => {
+                            println!("Added backlink: {} -> {} ({})",
+                                     id.display(), link.target.display(), link.kind);
+                        },
+                        Repair::RemovedDanglingLink(id, link) => {
+                            println!("Removed dangling link: {} -> {} ({})",
+                                     id.display(), link.target.display(), link.kind);
+                        },
+                    }
+                }
+            },
+            Err(e) => {
+                trace_error(&e);
+                exit(1);
+            },
+        }
+    } else if cmd.is_present("list") {
         debug!("List...");
         for entry in cmd.value_of("list").unwrap().split(",") {
             debug!("Listing for '{}'", entry);
             match get_entry_by_name(rt, entry) {
                 Ok(e) => {
-                    e.get_internal_links()
+                    e.get_typed_links()
                         .map(|links| {
                             let mut i = 0;
-                            for link in links.iter().map(|l| l.to_str()).filter_map(|x| x) {
-                                println!("{: <3}: {}", i, link);
-                                i += 1;
+                            for link in links {
+                                if let Some(target) = link.target.to_str() {
+                                    let label = link.label
+                                        .map(|l| format!(", {}", l))
+                                        .unwrap_or(String::new());
+                                    println!("{: <3}: {} ({}{})", i, target, link.kind, label);
+                                    i += 1;
+                                }
                             }
                         })
                         .map_err(|e| trace_error(&e))
@@ -105,8 +151,18 @@ fn handle_internal_linking(rt: &Runtime) {
         }
         debug!("Listing ready!");
     } else {
+        let scmd_name = match cmd.subcommand_name() {
+            Some(name) => name,
+            None => {
+                warn!("No commandline call");
+                exit(1);
+            },
+        };
+        let scmd = cmd.subcommand_matches(scmd_name).unwrap(); // we know it is there
+        let kind = scmd.value_of("kind").map(LinkKind::from_name);
+
         let mut from = {
-            let from = get_from_entry(&rt);
+            let from = get_from_entry(&rt, scmd);
             if from.is_none() {
                 warn!("No 'from' entry");
                 exit(1);
@@ -116,7 +172,7 @@ fn handle_internal_linking(rt: &Runtime) {
         debug!("Link from = {:?}", from.deref());
 
         let to = {
-            let to = get_to_entries(&rt);
+            let to = get_to_entries(&rt, scmd);
             if to.is_none() {
                 warn!("No 'to' entry");
                 exit(1);
@@ -125,19 +181,25 @@ fn handle_internal_linking(rt: &Runtime) {
         };
         debug!("Link to = {:?}", to.iter().map(|f| f.deref()).collect::<Vec<&Entry>>());
 
-        match cmd.subcommand_name() {
-            Some("add") => {
+        match scmd_name {
+            "add" => {
+                let kind  = kind.unwrap_or(LinkKind::Link);
+                let label = scmd.value_of("label").map(String::from);
                 for mut to_entry in to {
-                    if let Err(e) = to_entry.add_internal_link(&mut from) {
+                    if let Err(e) = from.add_typed_link(&mut to_entry, kind.clone(), label.clone()) {
                         trace_error(&e);
                         exit(1);
                     }
                 }
             },
 
-            Some("remove") => {
+            "remove" => {
                 for mut to_entry in to {
-                    if let Err(e) = to_entry.remove_internal_link(&mut from) {
+                    let res = match kind {
+                        Some(ref kind) => from.remove_typed_link(&mut to_entry, kind),
+                        None           => to_entry.remove_internal_link(&mut from),
+                    };
+                    if let Err(e) = res {
                         trace_error(&e);
                         exit(1);
                     }
@@ -149,13 +211,8 @@ fn handle_internal_linking(rt: &Runtime) {
     }
 }
 
-fn get_from_entry<'a>(rt: &'a Runtime) -> Option<FileLockEntry<'a>> {
-    rt.cli()
-        .subcommand_matches("internal")
-        .unwrap() // safe, we know there is an "internal" subcommand"
-        .subcommand_matches("add")
-        .unwrap() // safe, we know there is an "add" subcommand
-        .value_of("from")
+fn get_from_entry<'a>(rt: &'a Runtime, scmd: &ArgMatches) -> Option<FileLockEntry<'a>> {
+    scmd.value_of("from")
         .and_then(|from_name| {
             match get_entry_by_name(rt, from_name) {
                 Err(e) => {
@@ -168,13 +225,8 @@ fn get_from_entry<'a>(rt: &'a Runtime) -> Option<FileLockEntry<'a>> {
         })
 }
 
-fn get_to_entries<'a>(rt: &'a Runtime) -> Option<Vec<FileLockEntry<'a>>> {
-    rt.cli()
-        .subcommand_matches("internal")
-        .unwrap() // safe, we know there is an "internal" subcommand"
-        .subcommand_matches("add")
-        .unwrap() // safe, we know there is an "add" subcommand
-        .values_of("to")
+fn get_to_entries<'a>(rt: &'a Runtime, scmd: &ArgMatches) -> Option<Vec<FileLockEntry<'a>>> {
+    scmd.values_of("to")
         .map(|values| {
             let mut v = vec![];
             for entry in values.map(|v| get_entry_by_name(rt, v)) {
@@ -197,6 +249,23 @@ fn handle_external_linking(rt: &Runtime) {
     use libimagutil::trace::trace_error;
 
     let scmd       = rt.cli().subcommand_matches("external").unwrap();
+
+    if let Some(check) = scmd.subcommand_matches("check") {
+        handle_external_check(rt, scmd.value_of("id"), check);
+        return;
+    }
+
+    if let Some(gc) = scmd.subcommand_matches("gc") {
+        handle_external_gc(rt, gc);
+        return;
+    }
+
+    if scmd.is_present("list-all") {
+        debug!("Listing all external links!");
+        list_all_links(rt.store());
+        return;
+    }
+
     let entry_name = scmd.value_of("id").unwrap(); // enforced by clap
     let entry      = get_entry_by_name(rt, entry_name);
     if entry.is_err() {
@@ -272,7 +341,7 @@ fn remove_link_from_entry(store: &Store, matches: &ArgMatches, entry: &mut FileL
 
 fn set_links_for_entry(store: &Store, matches: &ArgMatches, entry: &mut FileLockEntry) {
     let links = matches
-        .value_of("links")
+        .value_of("set")
         .map(String::from)
         .unwrap()
         .split(",")
@@ -317,3 +386,209 @@ fn list_links_for_entry(store: &Store, entry: &mut FileLockEntry) {
     }
 }
 
+fn list_all_links(store: &Store) {
+    use libimagentrylink::external::all_external_links;
+
+    match all_external_links(store) {
+        Ok(links) => {
+            for link in links {
+                let url = link.url
+                    .map(|u| u.into_string())
+                    .unwrap_or(format!("{} (no URL)", link.id.display()));
+                println!("{: >3} {}", link.referenced_by.len(), url);
+            }
+        },
+        Err(e) => {
+            trace_error(&e);
+            exit(1);
+        },
+    }
+}
+
+fn handle_external_gc(rt: &Runtime, scmd: &ArgMatches) {
+    use libimagentrylink::external::gc_external_links;
+
+    let dry_run = scmd.is_present("dry-run");
+    match gc_external_links(rt.store(), dry_run) {
+        Ok(deleted) => {
+            for id in deleted {
+                if dry_run {
+                    println!("Would delete: {}", id.display());
+                } else {
+                    println!("Deleted: {}", id.display());
+                }
+            }
+        },
+        Err(e) => {
+            trace_error(&e);
+            exit(1);
+        },
+    }
+}
+
+fn handle_external_check(rt: &Runtime, id: Option<&str>, scmd: &ArgMatches) {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use libimagentrylink::check::{CheckConfig, LinkChecker, check_link_entry, parse_endpoint};
+    use libimagentrylink::internal::InternalLinker;
+    use libimagstore::store::StoreObject;
+    use libimagstore::storeid::StoreId;
+
+    let mut cfg = match CheckConfig::from_config(rt.config().map(|c| c.config())) {
+        Ok(cfg) => cfg,
+        Err(e)  => {
+            trace_error(&e);
+            exit(1);
+        },
+    };
+
+    if let Some(endpoint) = scmd.value_of("endpoint") {
+        match parse_endpoint(endpoint) {
+            Ok(endpoint) => cfg.endpoint = Some(endpoint),
+            Err(e) => {
+                trace_error(&e);
+                exit(1);
+            },
+        }
+    }
+
+    match scmd.value_of("timeout").map(u64::from_str) {
+        Some(Ok(0))    => cfg.timeout = None,
+        Some(Ok(secs)) => cfg.timeout = Some(Duration::from_secs(secs)),
+        Some(Err(e))   => {
+            trace_error(&e);
+            exit(1);
+        },
+        None => { },
+    }
+
+    let ids : Vec<StoreId> = match id {
+        Some(name) => {
+            match get_entry_by_name(rt, name) {
+                Ok(entry) => match entry.get_internal_links() {
+                    Ok(links) => links,
+                    Err(e) => {
+                        trace_error(&e);
+                        exit(1);
+                    },
+                },
+                Err(e) => {
+                    trace_error(&e);
+                    exit(1);
+                },
+            }
+        },
+        None => {
+            rt.store()
+                .walk("links/external")
+                .filter_map(|obj| match obj {
+                    StoreObject::Id(id) => Some(id),
+                    _ => None,
+                })
+                .collect()
+        },
+    };
+
+    let prefix  = rt.store().path().join("links").join("external");
+    let checker = LinkChecker::new(&cfg);
+    for id in ids.into_iter().filter(|id| id.starts_with(&prefix)) {
+        let mut entry = match rt.store().retrieve(id) {
+            Ok(e)  => e,
+            Err(e) => {
+                trace_error(&e);
+                continue;
+            },
+        };
+
+        // The check result is written when the entry is dropped
+        match check_link_entry(&checker, &mut entry) {
+            Ok(Some(result)) => {
+                let status = result.status
+                    .map(|s| format!("{}", s))
+                    .unwrap_or(String::from("---"));
+                let redirect = if result.final_url != result.url {
+                    format!(" -> {}", result.final_url)
+                } else {
+                    String::new()
+                };
+                let info = result.error.clone().or(result.title.clone()).unwrap_or(String::new());
+                println!("{} {}{} {}", status, result.url, redirect, info);
+            },
+            Ok(None) => debug!("Not a link entry: {:?}", entry.get_location()),
+            Err(e)   => trace_error(&e),
+        }
+    }
+}
+
+fn handle_graph(rt: &Runtime) {
+    use std::str::FromStr;
+
+    use libimagentrylink::graph::LinkGraph;
+    use libimagstore::storeid::StoreId;
+
+    let scmd  = rt.cli().subcommand_matches("graph").unwrap();
+    let graph = match LinkGraph::from_store(rt.store()) {
+        Ok(g)  => g,
+        Err(e) => {
+            trace_error(&e);
+            exit(1);
+        },
+    };
+
+    let to_id = |name: &str| -> StoreId {
+        use libimagstore::storeid::build_entry_path;
+
+        match build_entry_path(rt.store(), name) {
+            Ok(path) => StoreId::from(path),
+            Err(e)   => {
+                trace_error(&e);
+                exit(1);
+            },
+        }
+    };
+
+    if let Some(name) = scmd.value_of("neighbors") {
+        let depth = match scmd.value_of("depth").map(usize::from_str) {
+            Some(Ok(d)) => d,
+            Some(Err(e)) => {
+                trace_error(&e);
+                exit(1);
+            },
+            None => 1,
+        };
+
+        for id in graph.neighbors(&to_id(name), depth) {
+            println!("{}", graph.name(&id));
+        }
+    } else if let Some(mut values) = scmd.values_of("path") {
+        let from = to_id(values.next().unwrap()); // enforced by clap
+        let to   = to_id(values.next().unwrap()); // enforced by clap
+
+        match graph.shortest_path(&from, &to) {
+            Some(path) => {
+                for id in path {
+                    println!("{}", graph.name(&id));
+                }
+            },
+            None => {
+                info!("No path between these entries");
+                exit(1);
+            },
+        }
+    } else if scmd.is_present("components") {
+        for (i, component) in graph.connected_components().into_iter().enumerate() {
+            let names : Vec<String> = component.iter().map(|id| graph.name(id)).collect();
+            println!("{: <3}: {}", i, names.join(", "));
+        }
+    } else if scmd.is_present("unlinked") {
+        for id in graph.unlinked() {
+            println!("{}", graph.name(&id));
+        }
+    } else {
+        match scmd.value_of("format") {
+            Some("json") => println!("{}", graph.to_json().pretty()),
+            _            => print!("{}", graph.to_dot()),
+        }
+    }
+}
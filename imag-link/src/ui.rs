@@ -1,4 +1,4 @@
-use clap::{Arg, ArgGroup, App, SubCommand};
+use clap::{Arg, ArgGroup, App, AppSettings, SubCommand};
 
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
@@ -21,6 +21,17 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                                      .required(true)
                                      .multiple(true)
                                      .help("Link to this entries"))
+                                .arg(Arg::with_name("kind")
+                                     .long("kind")
+                                     .short("k")
+                                     .takes_value(true)
+                                     .required(false)
+                                     .help("Kind of the link, for example 'references' or 'subtask-of' (default: 'link')"))
+                                .arg(Arg::with_name("label")
+                                     .long("label")
+                                     .takes_value(true)
+                                     .required(false)
+                                     .help("Label for the link"))
                                 )
 
                     .subcommand(SubCommand::with_name("remove")
@@ -39,6 +50,12 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                                  .required(true)
                                  .multiple(true)
                                  .help("Remove links to these entries"))
+                            .arg(Arg::with_name("kind")
+                                 .long("kind")
+                                 .short("k")
+                                 .takes_value(true)
+                                 .required(false)
+                                 .help("Only remove links of this kind"))
                             )
 
                     .arg(Arg::with_name("list")
@@ -47,16 +64,29 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .takes_value(true)
                          .required(false)
                          .help("List links to this entry"))
+
+                    .arg(Arg::with_name("repair")
+                         .long("repair")
+                         .takes_value(false)
+                         .required(false)
+                         .help("Add missing backlinks and remove links to non-existing entries in the whole store"))
+
+                    .arg(Arg::with_name("migrate")
+                         .long("migrate")
+                         .takes_value(false)
+                         .required(false)
+                         .help("Convert links in the old format to typed links in the whole store"))
                     )
         .subcommand(SubCommand::with_name("external")
                     .about("Add and remove external links")
                     .version("0.1")
+                    .setting(AppSettings::SubcommandsNegateReqs)
 
                     .arg(Arg::with_name("id")
                          .long("id")
                          .short("i")
                          .takes_value(true)
-                         .required(true)
+                         .required_unless("list-all")
                          .help("Modify external link of this entry"))
 
                     .arg(Arg::with_name("add")
@@ -69,9 +99,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                     .arg(Arg::with_name("remove")
                          .long("remove")
                          .short("r")
-                         .takes_value(false)
+                         .takes_value(true)
                          .required(false)
-                         .help("Remove one external link"))
+                         .help("Remove this URI from the external links"))
 
                     .arg(Arg::with_name("set")
                          .long("set")
@@ -87,10 +117,101 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .required(false)
                          .help("List external links"))
 
+                    .arg(Arg::with_name("list-all")
+                         .long("list-all")
+                         .short("L")
+                         .takes_value(false)
+                         .required(false)
+                         .help("List all external links in the store, with the number of entries linking to them"))
+
                     .group(ArgGroup::with_name("external-link-group")
-                           .args(&["add", "remove", "set", "list"])
+                           .args(&["add", "remove", "set", "list", "list-all"])
                            .required(true))
 
+                    .subcommand(SubCommand::with_name("gc")
+                                .about("Delete external links which are not linked from any entry")
+                                .version("0.1")
+                                .arg(Arg::with_name("dry-run")
+                                     .long("dry-run")
+                                     .short("n")
+                                     .takes_value(false)
+                                     .required(false)
+                                     .help("Only print the links which would be deleted"))
+                                )
+
+                    .subcommand(SubCommand::with_name("check")
+                                .about("Request the external links and record status, redirect target and title (of the entry passed with --id, or all)")
+                                .version("0.1")
+                                .arg(Arg::with_name("endpoint")
+                                     .long("endpoint")
+                                     .short("e")
+                                     .takes_value(true)
+                                     .required(false)
+                                     .value_name("URL")
+                                     .help("Send all requests through the HTTP proxy at this URL"))
+                                .arg(Arg::with_name("timeout")
+                                     .long("timeout")
+                                     .short("t")
+                                     .takes_value(true)
+                                     .required(false)
+                                     .value_name("SECONDS")
+                                     .help("Read and write timeout for each request, 0 for none"))
+                                )
+
                     )
-}
 
+        .subcommand(SubCommand::with_name("graph")
+                    .about("Inspect and export the graph of internal links")
+                    .version("0.1")
+
+                    .arg(Arg::with_name("format")
+                         .long("format")
+                         .short("f")
+                         .takes_value(true)
+                         .required(false)
+                         .possible_values(&["dot", "json"])
+                         .help("Export the graph in this format (default: dot)"))
+
+                    .arg(Arg::with_name("neighbors")
+                         .long("neighbors")
+                         .short("n")
+                         .takes_value(true)
+                         .required(false)
+                         .help("List the entries which are linked to this entry"))
+
+                    .arg(Arg::with_name("depth")
+                         .long("depth")
+                         .short("d")
+                         .takes_value(true)
+                         .required(false)
+                         .requires("neighbors")
+                         .help("List the entries up to this number of links away (default: 1)"))
+
+                    .arg(Arg::with_name("path")
+                         .long("path")
+                         .short("p")
+                         .takes_value(true)
+                         .number_of_values(2)
+                         .value_names(&["FROM", "TO"])
+                         .required(false)
+                         .help("Print the shortest path of links between two entries"))
+
+                    .arg(Arg::with_name("components")
+                         .long("components")
+                         .short("c")
+                         .takes_value(false)
+                         .required(false)
+                         .help("List the connected components of the graph"))
+
+                    .arg(Arg::with_name("unlinked")
+                         .long("unlinked")
+                         .short("u")
+                         .takes_value(false)
+                         .required(false)
+                         .help("List the entries without links"))
+
+                    .group(ArgGroup::with_name("graph-group")
+                           .args(&["format", "neighbors", "path", "components", "unlinked"])
+                           .required(false))
+                    )
+}
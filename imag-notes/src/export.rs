@@ -0,0 +1,61 @@
+use std::fs::{File, create_dir_all};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::exit;
+
+use libimagrt::runtime::Runtime;
+use libimagnotes::markdown::MarkdownNote;
+use libimagnotes::note::Note;
+use libimagutil::trace::trace_error;
+
+/// Export all notes as Markdown files "<id>.md" into a directory
+pub fn export(rt: &Runtime) {
+    let dir = rt.cli()
+        .subcommand_matches("export")
+        .unwrap() // safe, we checked in main()
+        .value_of("dir")
+        .map(PathBuf::from)
+        .unwrap(); // safe, clap enforces
+
+    if let Err(e) = create_dir_all(&dir) {
+        trace_error(&e);
+        exit(1);
+    }
+
+    let notes = match Note::all_notes(rt.store()) {
+        Ok(notes) => notes,
+        Err(e) => {
+            trace_error(&e);
+            exit(1);
+        },
+    };
+
+    for note in notes {
+        let note = match note {
+            Ok(note) => note,
+            Err(e) => {
+                trace_error(&e);
+                continue;
+            },
+        };
+
+        let markdown = MarkdownNote::from_note(rt.store(), &note)
+            .and_then(|md| md.to_markdown())
+            .and_then(|markdown| note.id().map(|id| (id, markdown)));
+
+        match markdown {
+            Ok((id, markdown)) => {
+                let path = dir.join(format!("{}.md", id));
+                let res = File::create(&path).and_then(|mut f| f.write_all(markdown.as_bytes()));
+                match res {
+                    Ok(_)  => println!("Exported '{}' to {:?}", id, path),
+                    Err(e) => {
+                        trace_error(&e);
+                        warn!("Could not write {:?}", path);
+                    },
+                }
+            },
+            Err(e) => trace_error(&e),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::fs::{File, read_dir};
+use std::io::Read;
+use std::io::Result as IoResult;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use libimagrt::runtime::Runtime;
+use libimagnotes::markdown::{Imported, MarkdownNote};
+use libimagutil::trace::trace_error;
+
+/// Import the Markdown files of a directory as notes
+///
+/// The notes are created first and linked afterwards, so links can point to notes which are
+/// imported later. Files which have a note with the same title and content already are skipped.
+pub fn import(rt: &Runtime) {
+    let dir = rt.cli()
+        .subcommand_matches("import")
+        .unwrap() // safe, we checked in main()
+        .value_of("dir")
+        .unwrap(); // safe, clap enforces
+
+    let files = match markdown_files(Path::new(dir)) {
+        Ok(files) => files,
+        Err(e) => {
+            trace_error(&e);
+            exit(1);
+        },
+    };
+
+    // The ids used in the Markdown files (the "id" field or the file name) mapped to the ids of
+    // the imported notes
+    let mut ids = BTreeMap::new();
+    let mut imported = vec![];
+
+    for path in files {
+        let name = path.file_stem()
+            .and_then(|s| s.to_str())
+            .map(String::from)
+            .unwrap_or(String::new());
+
+        let mut content = String::new();
+        if let Err(e) = File::open(&path).and_then(|mut f| f.read_to_string(&mut content)) {
+            trace_error(&e);
+            warn!("Could not read {:?}", path);
+            continue;
+        }
+
+        let note = match MarkdownNote::parse(&content) {
+            Ok(note) => note,
+            Err(e) => {
+                trace_error(&e);
+                warn!("Could not parse {:?}", path);
+                continue;
+            },
+        };
+
+        let id = match note.import(rt.store(), &name) {
+            Ok(Imported::Created(id)) => {
+                println!("Imported {:?} as '{}'", path, id);
+                id
+            },
+            Ok(Imported::Duplicate(id)) => {
+                println!("Skipped {:?}, it is a duplicate of '{}'", path, id);
+                id
+            },
+            Err(e) => {
+                trace_error(&e);
+                warn!("Could not import {:?}", path);
+                continue;
+            },
+        };
+
+        ids.insert(name, id.clone());
+        if let Some(ref old_id) = note.id {
+            ids.insert(old_id.clone(), id.clone());
+        }
+        imported.push((note, id));
+    }
+
+    for (note, id) in imported {
+        if let Err(e) = note.add_links(rt.store(), &id, &ids) {
+            trace_error(&e);
+            warn!("Could not add the links of '{}'", id);
+        }
+    }
+}
+
+/// Get the paths of the ".md" and ".markdown" files in `dir`, sorted
+fn markdown_files(dir: &Path) -> IoResult<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in try!(read_dir(dir)) {
+        let path = try!(entry).path();
+        let is_markdown = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext == "md" || ext == "markdown")
+            .unwrap_or(false);
+
+        if path.is_file() && is_markdown {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
@@ -1,21 +1,55 @@
 extern crate clap;
 #[macro_use] extern crate log;
+extern crate regex;
 extern crate semver;
+extern crate toml;
 #[macro_use] extern crate version;
 
 extern crate libimagnotes;
 extern crate libimagrt;
+extern crate libimagstore;
+extern crate libimagentryfilter;
+extern crate libimagentrylist;
 extern crate libimagentrytag;
+extern crate libimaginteraction;
 extern crate libimagutil;
 
+use std::collections::BTreeMap;
+use std::env;
 use std::process::exit;
+use std::str::FromStr;
+
+use clap::ArgMatches;
+use regex::Regex;
+use toml::Value;
 
 use libimagrt::edit::Edit;
 use libimagrt::runtime::Runtime;
 use libimagnotes::note::Note;
+use libimagnotes::template::NoteTemplate;
+use libimagentryfilter::builtin::content::grep::ContentGrep;
+use libimagentryfilter::filter::Filter;
+use libimagentryfilter::tags::{HasAllTags, HasAnyTags};
+use libimagentrylist::cli::{list_entries_with_registry, selected_lister};
+use libimagentrylist::lister::Lister;
+use libimagentrylist::listers::path::PathLister;
+use libimagentrylist::registry::ListerRegistry;
+use libimagentrytag::alias::aliases_from_config;
+use libimagentrytag::stats::TagStatistics;
+use libimagentrytag::tag::Tag;
+use libimagentrytag::tagable::Tagable;
+use libimagentrytag::ui::tag_suggest_argument_name;
+use libimaginteraction::ask::{ask_select_from_list, ask_select_many_from_list};
 use libimagutil::trace::trace_error;
 
+mod export;
+mod import;
+mod matches;
 mod ui;
+
+use export::export;
+use import::import;
+use matches::MatchLister;
 use ui::build_ui;
 
 fn main() {
@@ -43,6 +77,8 @@ fn main() {
                 "delete" => delete(&rt),
                 "edit"   => edit(&rt),
                 "list"   => list(&rt),
+                "import" => import(&rt),
+                "export" => export(&rt),
                 _        => {
                     debug!("Unknown command"); // More error handling
                 },
@@ -54,32 +90,156 @@ fn name_from_cli(rt: &Runtime, subcmd: &str) -> String {
     rt.cli().subcommand_matches(subcmd).unwrap().value_of("name").map(String::from).unwrap()
 }
 
+/// Get the id of the note with the title passed with `--name`
+///
+/// If there are several notes with the title, the user is asked which one to use. If there is
+/// none, the title is used as id.
+fn id_from_cli(rt: &Runtime, subcmd: &str) -> String {
+    let title = name_from_cli(rt, subcmd);
+    let ids = match Note::ids_by_title(rt.store(), &title) {
+        Ok(ids) => ids,
+        Err(e) => {
+            trace_error(&e);
+            exit(1);
+        },
+    };
+
+    match ids.len() {
+        0 => {
+            if !Note::exists(rt.store(), &title) {
+                warn!("No note with the title or id '{}'", title);
+                exit(1);
+            }
+            title
+        },
+        1 => ids.into_iter().next().unwrap(),
+        _ => {
+            println!("There are several notes with the title '{}'", title);
+            let list : Vec<&str> = ids.iter().map(|id| &id[..]).collect();
+            match ask_select_from_list(&list[..]) {
+                Ok(id) => id,
+                Err(e) => {
+                    trace_error(&e);
+                    exit(1);
+                },
+            }
+        },
+    }
+}
+
 fn create(rt: &Runtime) {
-    let name = name_from_cli(rt, "create");
-    Note::new(rt.store(), name.clone(), String::new())
-        .map_err(|e| trace_error(&e))
-        .ok();
+    let title = name_from_cli(rt, "create");
+    let note = match rt.cli().subcommand_matches("create").unwrap().value_of("template") {
+        Some(template) => {
+            NoteTemplate::load(rt.store(), template)
+                .and_then(|t| t.create_note(rt.store(), title, &author(rt)))
+        },
+        None => Note::new(rt.store(), title, String::new()),
+    };
+
+    let id = match note.and_then(|note| note.id()) {
+        Ok(id) => id,
+        Err(e) => {
+            trace_error(&e);
+            exit(1);
+        },
+    };
+    info!("Created note '{}'", id);
 
     if rt.cli().subcommand_matches("create").unwrap().is_present("edit") {
-        if !edit_entry(rt, name) {
+        if !edit_entry(rt, id) {
             exit(1);
         }
     }
 }
 
+/// Get the author for the `{{author}}` placeholder of templates, from the "author" key in the
+/// notes section of the configuration or from the USER environment variable
+fn author(rt: &Runtime) -> String {
+    let configured = match rt.config().map(|c| c.config()) {
+        Some(&Value::Table(ref t)) => match t.get("notes") {
+            Some(&Value::Table(ref notes)) => match notes.get("author") {
+                Some(&Value::String(ref s)) => Some(s.clone()),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    };
+
+    configured.or_else(|| env::var("USER").ok()).unwrap_or(String::new())
+}
+
 fn delete(rt: &Runtime) {
-    Note::delete(rt.store(), String::from(name_from_cli(rt, "delete")))
+    Note::delete(rt.store(), id_from_cli(rt, "delete"))
         .map_err(|e| trace_error(&e))
         .map(|_| println!("Ok"))
         .ok();
 }
 
 fn edit(rt: &Runtime) {
-    edit_entry(rt, name_from_cli(rt, "edit"));
+    let id = id_from_cli(rt, "edit");
+    let suggest = rt.cli()
+        .subcommand_matches("edit")
+        .unwrap() // safe, we checked in main()
+        .is_present(tag_suggest_argument_name());
+
+    if edit_entry(rt, id.clone()) && suggest {
+        suggest_tags(rt, id);
+    }
+}
+
+/// Ask the user which of the tags suggested for the note should be added, and add them
+fn suggest_tags(rt: &Runtime, id: String) {
+    // Built before the note is retrieved, the store does not copy borrowed entries
+    let stats = TagStatistics::from_store(rt.store());
+
+    let mut note = match Note::retrieve(rt.store(), id) {
+        Ok(note) => note,
+        Err(e) => {
+            trace_error(&e);
+            return;
+        },
+    };
+
+    let tags = match note.get_tags() {
+        Ok(tags) => tags,
+        Err(e) => {
+            trace_error(&e);
+            return;
+        },
+    };
+
+    let suggestions = stats.suggest(&tags, note.get_text(), 10);
+    if suggestions.is_empty() {
+        info!("No tags to suggest");
+        return;
+    }
+
+    let aliases = tag_aliases(rt);
+    let list : Vec<&str> = suggestions.iter().map(|t| &t[..]).collect();
+    match ask_select_many_from_list(&list[..], "(done)") {
+        Ok(selected) => for tag in selected {
+            if let Err(e) = note.add_tag_with_aliases(tag, &aliases) {
+                trace_error(&e);
+            }
+        },
+        Err(e) => trace_error(&e),
+    }
 }
 
-fn edit_entry(rt: &Runtime, name: String) -> bool {
-    let note = Note::retrieve(rt.store(), name);
+/// Get the tag aliases from the configuration, or no aliases if they are invalid
+fn tag_aliases(rt: &Runtime) -> BTreeMap<Tag, Tag> {
+    aliases_from_config(rt.config().map(|c| c.config()))
+        .map_err(|e| {
+            warn!("Could not load tag aliases");
+            trace_error(&e);
+        })
+        .unwrap_or(BTreeMap::new())
+}
+
+fn edit_entry(rt: &Runtime, id: String) -> bool {
+    let note = Note::retrieve(rt.store(), id);
     if note.is_err() {
         trace_error(&note.unwrap_err());
         warn!("Cannot edit nonexistent Note");
@@ -98,6 +258,18 @@ fn edit_entry(rt: &Runtime, name: String) -> bool {
 fn list(rt: &Runtime) {
     use std::cmp::Ordering;
 
+    let scmd = rt.cli().subcommand_matches("list").unwrap(); // safe, we checked in main()
+
+    let regex = match scmd.value_of("grep").map(Regex::new) {
+        Some(Ok(regex)) => Some(regex),
+        Some(Err(e)) => {
+            trace_error(&e);
+            exit(1);
+        },
+        None => None,
+    };
+    let filters = list_filters(scmd, regex.clone());
+
     let iter = Note::all_notes(rt.store());
     if iter.is_err() {
         trace_error(&iter.unwrap_err());
@@ -114,6 +286,7 @@ fn list(rt: &Runtime) {
                 Ok(e) => Some(e)
             }
         })
+        .filter(|note| filters.iter().all(|f| f.filter(note)))
         .collect::<Vec<Note>>();
 
     iter.sort_by(|note_a, note_b| {
@@ -124,11 +297,55 @@ fn list(rt: &Runtime) {
         }
     });
 
-    for note in iter {
-        note.get_name()
-            .map(|name| println!("{}", name))
-            .map_err(|e| trace_error(&e))
-            .ok();
+    let config = rt.config().map(|c| c.config());
+    let res = if scmd.is_present("matches") {
+        let context = scmd.value_of("context").and_then(|c| FromStr::from_str(c).ok()).unwrap_or(2);
+        let regex = regex.unwrap(); // safe, clap enforces --grep for --matches
+        MatchLister::new(regex, context).list(iter.into_iter().map(|n| n.into_entry()))
+    } else if scmd.is_present("files-with-matches") {
+        PathLister::new(false).list(iter.into_iter().map(|n| n.into_entry()))
+    } else if selected_lister(rt.cli(), config, "notes").is_some() {
+        let entries = iter.into_iter().map(|n| n.into_entry());
+        list_entries_with_registry(rt.cli(), &ListerRegistry::default(), config, "notes", entries)
+    } else {
+        for note in iter {
+            note.get_name()
+                .map(|name| println!("{}", name))
+                .map_err(|e| trace_error(&e))
+                .ok();
+        }
+        Ok(())
+    };
+
+    if let Err(e) = res {
+        trace_error(&e);
+        exit(1);
     }
 }
 
+/// Get the filters for the notes to list from the `--tags` and `--grep` arguments
+fn list_filters(scmd: &ArgMatches, regex: Option<Regex>) -> Vec<Box<Filter>> {
+    let mut filters : Vec<Box<Filter>> = vec![];
+
+    if let Some(tags) = scmd.value_of("tags") {
+        let tags = tags.split(",")
+            .map(|t| String::from(t.trim()))
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        if scmd.is_present("any-tag") {
+            filters.push(Box::new(HasAnyTags::with_descendants(tags)));
+        } else {
+            filters.push(Box::new(HasAllTags::with_descendants(tags)));
+        }
+    }
+
+    if let Some(regex) = regex {
+        // Creating the filter from a compiled regex does not fail
+        if let Ok(grep) = ContentGrep::new(regex) {
+            filters.push(Box::new(grep));
+        }
+    }
+
+    filters
+}
@@ -0,0 +1,131 @@
+use std::cmp::min;
+use std::io::Result as IoResult;
+use std::io::Write;
+use std::io::stdout;
+
+use regex::Regex;
+use toml::Value;
+
+use libimagentrylist::error::ListError as LE;
+use libimagentrylist::error::ListErrorKind as LEK;
+use libimagentrylist::lister::Lister;
+use libimagentrylist::result::Result;
+use libimagstore::store::FileLockEntry;
+
+/// Lists the title of each note and the lines of its content which match a regex, with `context`
+/// lines before and after each match, like `grep -n -C <context>` does.
+pub struct MatchLister {
+    regex: Regex,
+    context: usize,
+}
+
+impl MatchLister {
+
+    pub fn new(regex: Regex, context: usize) -> MatchLister {
+        MatchLister {
+            regex: regex,
+            context: context,
+        }
+    }
+
+    /// Write the matching lines and their context, prefixed with the line number. Matching lines
+    /// are marked with ':', context lines with '-' and, if there is context, gaps between the
+    /// printed lines with "--".
+    fn write_matches<W: Write>(&self, out: &mut W, content: &str) -> IoResult<()> {
+        let lines   : Vec<&str> = content.lines().collect();
+        let matches : Vec<bool> = lines.iter().map(|l| self.regex.is_match(l)).collect();
+        let mut last = None;
+
+        for (i, line) in lines.iter().enumerate() {
+            let from = i.saturating_sub(self.context);
+            let to   = min(i + self.context + 1, lines.len());
+            if !matches[from..to].iter().any(|m| *m) {
+                continue;
+            }
+
+            if self.context > 0 && last.map(|l| l + 1 < i).unwrap_or(false) {
+                try!(write!(out, "--\n"));
+            }
+            let sep = if matches[i] { ':' } else { '-' };
+            try!(write!(out, "{}{}{}\n", i + 1, sep, line));
+            last = Some(i);
+        }
+        Ok(())
+    }
+
+}
+
+impl Lister for MatchLister {
+
+    fn list<'a, I: Iterator<Item = FileLockEntry<'a>>>(&self, entries: I) -> Result<()> {
+        let mut out = stdout();
+        for entry in entries {
+            let title = match entry.get_header().read("note.name") {
+                Ok(Some(Value::String(s))) => s,
+                _ => format!("{:?}", entry.get_location()),
+            };
+
+            try!(write!(out, "{}\n", title)
+                .and_then(|_| self.write_matches(&mut out, entry.get_content()))
+                .and_then(|_| write!(out, "\n"))
+                .map_err(|e| LE::new(LEK::FormatError, Some(Box::new(e)))));
+        }
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use regex::Regex;
+
+    use super::MatchLister;
+
+    fn matches(pattern: &str, context: usize, content: &str) -> String {
+        let lister = MatchLister::new(Regex::new(pattern).unwrap(), context);
+        let mut out = vec![];
+        lister.write_matches(&mut out, content).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    const CONTENT : &'static str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine";
+
+    #[test]
+    fn test_no_context() {
+        assert_eq!("2:two\n8:eight\n", matches("^(two|eight)$", 0, CONTENT));
+        assert_eq!("", matches("ten", 2, CONTENT));
+    }
+
+    #[test]
+    fn test_gap_between_matches() {
+        assert_eq!("1-one\n2:two\n3-three\n--\n7-seven\n8:eight\n9-nine\n",
+                   matches("^(two|eight)$", 1, CONTENT));
+    }
+
+    #[test]
+    fn test_adjacent_matches() {
+        assert_eq!("2-two\n3:three\n4:four\n5-five\n", matches("^(three|four)$", 1, CONTENT));
+    }
+
+    #[test]
+    fn test_overlapping_context() {
+        // The context of "three" and "six" overlaps in "four" and "five", which are printed once
+        assert_eq!("1-one\n2-two\n3:three\n4-four\n5-five\n6:six\n7-seven\n8-eight\n",
+                   matches("^(three|six)$", 2, CONTENT));
+    }
+
+    #[test]
+    fn test_touching_context() {
+        // No gap if the context of one match ends right before the context of the next one
+        assert_eq!("1-one\n2:two\n3-three\n4-four\n5:five\n6-six\n",
+                   matches("^(two|five)$", 1, CONTENT));
+    }
+
+    #[test]
+    fn test_matches_at_start_and_end() {
+        assert_eq!("1:one\n2-two\n3-three\n--\n7-seven\n8-eight\n9:nine\n",
+                   matches("^(one|nine)$", 2, CONTENT));
+        assert_eq!("1:one\n", matches("one", 0, "one"));
+    }
+
+}
@@ -2,6 +2,8 @@ use clap::{Arg, ArgGroup, App, SubCommand};
 
 use libimagentrytag::ui::tag_argument;
 use libimagentrytag::ui::tag_argument_name;
+use libimagentrytag::ui::tag_suggest_argument;
+use libimagentrylist::cli::build_list_cli_component;
 
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
@@ -13,13 +15,20 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .short("n")
                         .takes_value(true)
                         .required(true)
-                        .help("Create Note with this name"))
+                        .help("Create Note with this title"))
                    .arg(Arg::with_name("edit")
                         .long("edit")
                         .short("e")
                         .takes_value(false)
                         .required(false)
                         .help("Edit after creating"))
+                   .arg(Arg::with_name("template")
+                        .long("template")
+                        .short("T")
+                        .takes_value(true)
+                        .required(false)
+                        .value_name("TEMPLATE")
+                        .help("Create the Note from the template 'notes/templates/<TEMPLATE>'"))
                    )
 
         .subcommand(SubCommand::with_name("delete")
@@ -30,7 +39,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .short("n")
                         .takes_value(true)
                         .required(true)
-                        .help("Delete Note with this name")))
+                        .help("Delete Note with this title (or id)")))
 
         .subcommand(SubCommand::with_name("edit")
                    .about("Edit a Note")
@@ -40,17 +49,80 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .short("n")
                         .takes_value(true)
                         .required(true)
-                        .help("Edit Note with this name"))
+                        .help("Edit Note with this title (or id)"))
 
                    .arg(tag_argument())
+                   .arg(tag_suggest_argument())
                    .group(ArgGroup::with_name("editargs")
                           .args(&[tag_argument_name(), "name"])
                           .required(true))
                    )
 
-        .subcommand(SubCommand::with_name("list")
+        .subcommand(SubCommand::with_name("import")
+                   .about("Import Notes from Markdown files with YAML front matter")
+                   .version("0.1")
+                   .arg(Arg::with_name("dir")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("DIR")
+                        .help("Import the .md and .markdown files of this directory")))
+
+        .subcommand(SubCommand::with_name("export")
+                   .about("Export Notes to Markdown files with YAML front matter")
+                   .version("0.1")
+                   .arg(Arg::with_name("dir")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("DIR")
+                        .help("Write the Notes to this directory, one <id>.md file per Note")))
+
+        .subcommand(build_list_cli_component()
                    .about("List Notes")
-                   .version("0.1"))
+                   .arg(Arg::with_name("tags")
+                        .long("tags")
+                        .takes_value(true)
+                        .required(false)
+                        .value_name("TAGS")
+                        .help("Only list notes with all of these tags (seperate by comma). \
+                               A tag also matches the tags below it, \"work\" matches \"work/x\""))
+                   .arg(Arg::with_name("any-tag")
+                        .long("any-tag")
+                        .takes_value(false)
+                        .required(false)
+                        .requires("tags")
+                        .help("List notes with any of the tags instead of all of them"))
+                   .arg(Arg::with_name("grep")
+                        .long("grep")
+                        .short("g")
+                        .takes_value(true)
+                        .required(false)
+                        .value_name("REGEX")
+                        .help("Only list notes where the content matches this regex"))
+                   .arg(Arg::with_name("matches")
+                        .long("matches")
+                        .short("m")
+                        .takes_value(false)
+                        .required(false)
+                        .requires("grep")
+                        .conflicts_with("files-with-matches")
+                        .help("Print the matching lines of each note"))
+                   .arg(Arg::with_name("context")
+                        .long("context")
+                        .short("C")
+                        .takes_value(true)
+                        .required(false)
+                        .requires("matches")
+                        .value_name("LINES")
+                        .help("Print this many lines around each match (default: 2)"))
+                   .arg(Arg::with_name("files-with-matches")
+                        .long("files-with-matches")
+                        .takes_value(false)
+                        .required(false)
+                        .requires("grep")
+                        .help("Print the paths of the notes where the content matches"))
+                   )
 
 }
 
@@ -15,6 +15,7 @@
 
 extern crate clap;
 #[macro_use] extern crate log;
+extern crate rustc_serialize;
 extern crate semver;
 extern crate toml;
 #[macro_use] extern crate version;
@@ -1,6 +1,7 @@
 use std::process::exit;
 
 use clap::ArgMatches;
+use rustc_serialize::json::ToJson;
 use toml::Value;
 
 use libimagstore::store::FileLockEntry;
@@ -50,9 +51,7 @@ fn print_entry(rt: &Runtime, scmd: &ArgMatches, e: FileLockEntry) {
             debug!("Printing header...");
             if do_print_header_as_json(rt.cli()) {
                 debug!("Printing header as json...");
-                warn!("Printing as JSON currently not supported.");
-                warn!("Will fail now!");
-                unimplemented!()
+                println!("{}", e.get_header().to_json().pretty())
             } else {
                 debug!("Printing header as TOML...");
                 // We have to Value::Table() for Display
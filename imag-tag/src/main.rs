@@ -6,13 +6,29 @@ extern crate toml;
 
 extern crate libimagstore;
 extern crate libimagrt;
+extern crate libimagentryfilter;
 extern crate libimagentrytag;
+extern crate libimaginteraction;
 extern crate libimagutil;
 
+use std::collections::BTreeMap;
 use std::process::exit;
 
+use clap::ArgMatches;
+
 use libimagrt::runtime::Runtime;
+use libimagentryfilter::expr::parse_filter_expression;
+use libimagentryfilter::filter::Filter;
+use libimagentrytag::alias::{aliases_from_config, resolve_alias};
+use libimagentrytag::stats::TagStatistics;
+use libimagentrytag::tag::Tag;
 use libimagentrytag::tagable::Tagable;
+use libimagentrytag::ui::tag_suggest_argument_name;
+use libimagentrytag::util::{is_descendant_of, is_tag, rename_tag};
+use libimaginteraction::ask::ask_select_many_from_list;
+use libimagstore::store::FileLockEntry;
+use libimagstore::store::StoreObject;
+use libimagstore::storeid::StoreId;
 use libimagstore::storeid::build_entry_path;
 
 mod ui;
@@ -37,7 +53,6 @@ fn main() {
         }
     };
 
-    let id = rt.cli().value_of("id").unwrap(); // enforced by clap
     rt.cli()
         .subcommand_name()
         .map_or_else(
@@ -45,13 +60,18 @@ fn main() {
                 let add = rt.cli().value_of("add");
                 let rem = rt.cli().value_of("remove");
                 let set = rt.cli().value_of("set");
+                let suggest = rt.cli().is_present(tag_suggest_argument_name());
 
-                alter(&rt, id, add, rem, set);
+                let aliases = tag_aliases(&rt);
+                alter(&rt, get_id(&rt), add, rem, set, suggest, &aliases);
             },
             |name| {
                 debug!("Call: {}", name);
                 match name {
-                    "list" => list(id, &rt),
+                    "list"     => list(get_id(&rt), &rt),
+                    "list-all" => list_all(&rt),
+                    "rename"   => retag(&rt, rt.cli().subcommand_matches("rename").unwrap(), true),
+                    "merge"    => retag(&rt, rt.cli().subcommand_matches("merge").unwrap(), false),
                     _ => {
                         warn!("Unknown command");
                         // More error handling
@@ -60,7 +80,34 @@ fn main() {
             });
 }
 
-fn alter(rt: &Runtime, id: &str, add: Option<&str>, rem: Option<&str>, set: Option<&str>) {
+/// Get the tag aliases from the configuration, or no aliases if they are invalid
+fn tag_aliases(rt: &Runtime) -> BTreeMap<Tag, Tag> {
+    aliases_from_config(rt.config().map(|c| c.config()))
+        .map_err(|e| {
+            warn!("Could not load tag aliases");
+            trace_error(&e);
+        })
+        .unwrap_or(BTreeMap::new())
+}
+
+fn get_id(rt: &Runtime) -> &str {
+    match rt.cli().value_of("id") {
+        Some(id) => id,
+        None => {
+            warn!("No entry given, pass it with --id");
+            exit(1);
+        },
+    }
+}
+
+fn alter(rt: &Runtime,
+         id: &str,
+         add: Option<&str>,
+         rem: Option<&str>,
+         set: Option<&str>,
+         suggest: bool,
+         aliases: &BTreeMap<Tag, Tag>)
+{
     let path = {
         match build_entry_path(rt.store(), id) {
             Err(e) => {
@@ -72,6 +119,14 @@ fn alter(rt: &Runtime, id: &str, add: Option<&str>, rem: Option<&str>, set: Opti
     };
     debug!("path = {:?}", path);
 
+    // The statistics have to be built before the entry is retrieved, as the store refuses to copy
+    // borrowed entries
+    let stats = if suggest {
+        Some(TagStatistics::from_store(rt.store()))
+    } else {
+        None
+    };
+
     rt.store()
         // "id" must be present, enforced via clap spec
         .retrieve(path)
@@ -80,7 +135,7 @@ fn alter(rt: &Runtime, id: &str, add: Option<&str>, rem: Option<&str>, set: Opti
                 let tags = tags.split(",");
                 for tag in tags {
                     info!("Adding tag '{}'", tag);
-                    if let Err(e) = e.add_tag(String::from(tag)) {
+                    if let Err(e) = e.add_tag_with_aliases(String::from(tag), aliases) {
                         trace_error(&e);
                     }
                 }
@@ -98,11 +153,15 @@ fn alter(rt: &Runtime, id: &str, add: Option<&str>, rem: Option<&str>, set: Opti
 
             set.map(|tags| {
                 info!("Setting tags '{}'", tags);
-                let tags = tags.split(",").map(String::from).collect();
+                let tags = tags.split(",")
+                    .map(|t| resolve_alias(aliases, String::from(t)))
+                    .collect();
                 if let Err(e) = e.set_tags(tags) {
                     trace_error(&e);
                 }
             });
+
+            stats.map(|stats| suggest_tags(&stats, &mut e, aliases));
         })
         .map_err(|e| {
             info!("No entry.");
@@ -111,6 +170,34 @@ fn alter(rt: &Runtime, id: &str, add: Option<&str>, rem: Option<&str>, set: Opti
         .ok();
 }
 
+/// Ask the user which of the tags suggested for the entry should be added, and add them
+fn suggest_tags(stats: &TagStatistics, entry: &mut FileLockEntry, aliases: &BTreeMap<Tag, Tag>) {
+    let tags = match entry.get_tags() {
+        Ok(tags) => tags,
+        Err(e) => {
+            trace_error(&e);
+            return;
+        },
+    };
+
+    let suggestions = stats.suggest(&tags, entry.get_content(), 10);
+    if suggestions.is_empty() {
+        info!("No tags to suggest");
+        return;
+    }
+
+    let list : Vec<&str> = suggestions.iter().map(|t| &t[..]).collect();
+    match ask_select_many_from_list(&list[..], "(done)") {
+        Ok(selected) => for tag in selected {
+            info!("Adding tag '{}'", tag);
+            if let Err(e) = entry.add_tag_with_aliases(tag, aliases) {
+                trace_error(&e);
+            }
+        },
+        Err(e) => trace_error(&e),
+    }
+}
+
 fn list(id: &str, rt: &Runtime) {
     let path = {
         match build_entry_path(rt.store(), id) {
@@ -171,3 +258,134 @@ fn list(id: &str, rt: &Runtime) {
     }
 }
 
+/// Get the ids and tags of all entries in the store which match the filter expression passed with
+/// `--filter`, or of all entries if there is none
+fn filtered_entries(rt: &Runtime, scmd: &ArgMatches) -> Vec<(StoreId, Vec<Tag>)> {
+    let filter = scmd.value_of("filter")
+        .map(|expr| {
+            match parse_filter_expression(expr) {
+                Ok(f)  => f,
+                Err(e) => {
+                    warn!("Could not parse filter expression: '{}'", expr);
+                    trace_error(&e);
+                    exit(1);
+                },
+            }
+        });
+
+    let mut entries = vec![];
+    for obj in rt.store().walk("") {
+        let id = match obj {
+            StoreObject::Id(id) => id,
+            StoreObject::Collection(_) => continue,
+        };
+
+        let entry = match rt.store().retrieve_copy(id.clone()) {
+            Ok(e)  => e,
+            Err(e) => {
+                trace_error(&e);
+                continue;
+            },
+        };
+
+        if !filter.as_ref().map(|f| f.filter(&entry)).unwrap_or(true) {
+            continue;
+        }
+
+        match entry.get_tags() {
+            Ok(tags) => entries.push((id, tags)),
+            Err(e)   => {
+                warn!("Could not read tags of {:?}", id);
+                trace_error(&e);
+            },
+        }
+    }
+    entries
+}
+
+/// Get the id of the entry relative to the store, for printing
+fn entry_name(rt: &Runtime, id: &StoreId) -> String {
+    format!("{}", id.strip_prefix(rt.store().path()).unwrap_or(id).display())
+}
+
+fn list_all(rt: &Runtime) {
+    let scmd = rt.cli().subcommand_matches("list-all").unwrap(); // safe, we checked in main()
+
+    let mut counts : BTreeMap<Tag, usize> = BTreeMap::new();
+    for (_, tags) in filtered_entries(rt, scmd) {
+        for tag in tags {
+            *counts.entry(tag).or_insert(0) += 1;
+        }
+    }
+
+    for (tag, count) in counts {
+        println!("{: >4} {}", count, tag);
+    }
+}
+
+/// Rename (if `rename` is set) or merge the tag passed as first argument into the tag passed as
+/// second argument, in all entries matching the filter
+///
+/// Renaming fails if the new tag is used already, merging does not.
+fn retag(rt: &Runtime, scmd: &ArgMatches, rename: bool) {
+    let (old, new) = if rename {
+        (scmd.value_of("old").unwrap(), scmd.value_of("new").unwrap()) // enforced by clap
+    } else {
+        (scmd.value_of("from").unwrap(), scmd.value_of("into").unwrap()) // enforced by clap
+    };
+    let old = String::from(old);
+    let new = String::from(new);
+    let dry_run = scmd.is_present("dry-run");
+
+    for tag in vec![&old, &new] {
+        if !is_tag(tag) {
+            warn!("Not a tag: '{}'", tag);
+            exit(1);
+        }
+    }
+
+    let entries = filtered_entries(rt, scmd);
+
+    if rename && entries.iter().any(|&(_, ref tags)| tags.iter().any(|t| is_descendant_of(t, &new))) {
+        warn!("The tag '{}' is used already, use 'merge' to merge '{}' into it", new, old);
+        exit(1);
+    }
+
+    let mut changed = 0;
+    for (id, tags) in entries {
+        if !tags.iter().any(|t| is_descendant_of(t, &old)) {
+            continue;
+        }
+
+        let mut new_tags : Vec<Tag> = vec![];
+        for tag in tags.iter() {
+            let tag = rename_tag(tag, &old, &new).unwrap_or(tag.clone());
+            if !new_tags.contains(&tag) {
+                new_tags.push(tag);
+            }
+        }
+
+        println!("{}: {} -> {}", entry_name(rt, &id), tags.join(", "), new_tags.join(", "));
+        changed += 1;
+
+        if dry_run {
+            continue;
+        }
+
+        // The entry is written when it is dropped
+        let res = rt.store()
+            .retrieve(id.clone())
+            .map_err(|e| trace_error(&e))
+            .and_then(|mut entry| entry.set_tags(new_tags).map_err(|e| trace_error(&e)));
+
+        if res.is_err() {
+            warn!("Could not change the tags of {:?}", id);
+        }
+    }
+
+    if dry_run {
+        info!("Would change {} entries", changed);
+    } else {
+        info!("Changed {} entries", changed);
+    }
+}
@@ -1,7 +1,10 @@
-use clap::{Arg, App, ArgGroup, SubCommand};
+use clap::{Arg, App, AppSettings, ArgGroup, SubCommand};
+
+use libimagentrytag::ui::tag_suggest_argument;
 
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
-    app.arg(Arg::with_name("id")
+    app.setting(AppSettings::SubcommandsNegateReqs)
+        .arg(Arg::with_name("id")
                 .long("id")
                 .short("i")
                 .takes_value(true)
@@ -32,6 +35,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .multiple(true)
              .help("Set these tags"))
 
+        .arg(tag_suggest_argument())
+
        .subcommand(SubCommand::with_name("list")
                    .about("List tags (default)")
                    .version("0.1")
@@ -70,6 +75,62 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                           .required(true))
                    )
 
+       .subcommand(SubCommand::with_name("list-all")
+                   .about("List all tags in the store, with the number of entries tagged with them")
+                   .version("0.1")
+                   .arg(filter_arg()))
+
+       .subcommand(SubCommand::with_name("rename")
+                   .about("Rename a tag (and the tags below it) in all entries")
+                   .version("0.1")
+                   .arg(Arg::with_name("old")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .help("The tag to rename"))
+                   .arg(Arg::with_name("new")
+                        .index(2)
+                        .takes_value(true)
+                        .required(true)
+                        .help("The new name of the tag, must not be used yet"))
+                   .arg(filter_arg())
+                   .arg(dry_run_arg()))
+
+       .subcommand(SubCommand::with_name("merge")
+                   .about("Merge a tag (and the tags below it) into another tag in all entries")
+                   .version("0.1")
+                   .arg(Arg::with_name("from")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .help("The tag which is merged and removed"))
+                   .arg(Arg::with_name("into")
+                        .index(2)
+                        .takes_value(true)
+                        .required(true)
+                        .help("The tag which is merged into"))
+                   .arg(filter_arg())
+                   .arg(dry_run_arg()))
+
+}
+
+fn filter_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("filter")
+        .long("filter")
+        .short("f")
+        .takes_value(true)
+        .required(false)
+        .value_name("EXPRESSION")
+        .help("Only use the entries matching this filter expression, for example 'tag:work/* and not tag:done'")
+}
+
+fn dry_run_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("dry-run")
+        .long("dry-run")
+        .short("n")
+        .takes_value(false)
+        .required(false)
+        .help("Only print what would be changed")
 }
 
 
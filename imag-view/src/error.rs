@@ -12,6 +12,14 @@ pub enum ViewErrorKind {
     NoVersion,
     PatternError,
     GlobBuildError,
+    IOError,
+    ViewerError,
+    UIError,
+    NoBrowser,
+    NoEditor,
+    NoCommand,
+    ProcessExitFailure,
+    ExportError,
 }
 
 fn view_error_type_as_str(e: &ViewErrorKind) -> &'static str {
@@ -20,6 +28,14 @@ fn view_error_type_as_str(e: &ViewErrorKind) -> &'static str {
         &ViewErrorKind::NoVersion => "No version specified",
         &ViewErrorKind::PatternError => "Error in Pattern",
         &ViewErrorKind::GlobBuildError => "Could not build glob() Argument",
+        &ViewErrorKind::IOError => "IO Error",
+        &ViewErrorKind::ViewerError => "Error while viewing",
+        &ViewErrorKind::UIError => "Error in the UI",
+        &ViewErrorKind::NoBrowser => "No browser specified and $BROWSER not set",
+        &ViewErrorKind::NoEditor => "No editor specified",
+        &ViewErrorKind::NoCommand => "No command to call",
+        &ViewErrorKind::ProcessExitFailure => "Process did not exit successfully",
+        &ViewErrorKind::ExportError => "Error while exporting",
     }
 }
 
@@ -16,22 +16,31 @@
 extern crate clap;
 extern crate glob;
 #[macro_use] extern crate log;
+extern crate rustbox;
 extern crate semver;
 extern crate toml;
 #[macro_use] extern crate version;
 
+extern crate libimagentrylink;
+extern crate libimagentrytag;
+extern crate libimagentryview;
 extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagutil;
 
+use std::env;
+use std::path::PathBuf;
 use std::result::Result as RResult;
 use std::process::exit;
 
+use libimagentryview::compile::{Markup, Target, compiler};
+use libimagentryview::template::{Template, template_from_config};
 use libimagrt::runtime::Runtime;
 use libimagstore::store::FileLockEntry;
 use libimagutil::trace::trace_error;
 
 mod error;
+mod site;
 mod ui;
 mod viewer;
 
@@ -39,7 +48,14 @@ use error::{ViewError, ViewErrorKind};
 use ui::build_ui;
 use viewer::Viewer;
 use viewer::ViewInformation;
+use viewer::copy::{TempCopy, copy_name};
+use viewer::browser::BrowserViewer;
+use viewer::custom::CustomViewer;
+use viewer::editor::EditorViewer;
+use viewer::json::JsonViewer;
 use viewer::stdout::StdoutViewer;
+use viewer::template::TemplateViewer;
+use viewer::ui::UiViewer;
 
 type Result<T> = RResult<T, ViewError>;
 
@@ -59,7 +75,25 @@ fn main() {
         }
     };
 
-    let entry_id = rt.cli().value_of("id").unwrap(); // enforced by clap
+    if let Some(scmd) = rt.cli().subcommand_matches("export-site") {
+        let dir     = PathBuf::from(scmd.value_of("dir").unwrap()); // enforced by clap
+        let modules = scmd.values_of("module").map(|v| v.map(String::from).collect()).unwrap_or(vec![]);
+        let tags    = scmd.values_of("tag").map(|v| v.map(String::from).collect()).unwrap_or(vec![]);
+
+        if let Err(e) = site::export_site(&rt, &dir, modules, tags) {
+            trace_error(&e);
+            exit(1); // we can afford not-executing destructors here
+        }
+        return;
+    }
+
+    let entry_id = match rt.cli().value_of("id") {
+        Some(id) => id,
+        None     => {
+            warn!("No entry id given, use --id");
+            exit(1); // we can afford not-executing destructors here
+        },
+    };
 
     if rt.cli().is_present("versions") {
         if let Err(e) = view_versions_of(entry_id, &rt) {
@@ -73,6 +107,17 @@ fn main() {
         let view_copy       = rt.cli().is_present("view-copy");
         let keep_copy       = rt.cli().is_present("keep-copy");
 
+        if let Some(scmd) = rt.cli().subcommand_matches("compile") {
+            let from = scmd.value_of("from").unwrap(); // enforced by clap
+            let to   = scmd.value_of("to").unwrap(); // enforced by clap
+
+            if let Err(e) = compile_entry(&rt, entry_id, entry_version, from, to, view_copy) {
+                trace_error(&e);
+                exit(1); // we can afford not-executing destructors here
+            }
+            return;
+        }
+
         let scmd = rt.cli().subcommand_matches("view-in");
         if scmd.is_none() {
             debug!("No commandline call");
@@ -80,21 +125,36 @@ fn main() {
         }
         let scmd = scmd.unwrap();
 
-        let viewer = {
+        let viewer : Box<Viewer> = {
             if scmd.is_present("view-in-stdout") {
                 Box::new(StdoutViewer::new())
+            } else if scmd.is_present("view-in-json") {
+                Box::new(JsonViewer::new(true))
+            } else if let Some(template) = scmd.value_of("view-in-template") {
+                Box::new(build_template_viewer(template))
             } else if scmd.is_present("view-in-ui") {
-                warn!("Viewing in UI is currently not supported, switch to stdout");
-                Box::new(StdoutViewer::new())
+                Box::new(UiViewer::new())
             } else if scmd.is_present("view-in-browser") {
-                warn!("Viewing in browser is currently not supported, switch to stdout");
-                Box::new(StdoutViewer::new())
+                match scmd.value_of("view-in-browser").map(String::from).or(env::var("BROWSER").ok()) {
+                    Some(browser) => Box::new(BrowserViewer::new(browser, rt.store().path().clone())),
+                    None => {
+                        trace_error(&ViewError::new(ViewErrorKind::NoBrowser, None));
+                        exit(1); // we can afford not-executing destructors here
+                    },
+                }
             } else if scmd.is_present("view-in-texteditor") {
-                warn!("Viewing in texteditor is currently not supported, switch to stdout");
-                Box::new(StdoutViewer::new())
-            } else if scmd.is_present("view-in-custom") {
-                warn!("Viewing in custom is currently not supported, switch to stdout");
-                Box::new(StdoutViewer::new())
+                match scmd.value_of("view-in-texteditor").map(String::from).or(editor(&rt)) {
+                    Some(editor) => Box::new(EditorViewer::new(editor)),
+                    None => {
+                        trace_error(&ViewError::new(ViewErrorKind::NoEditor, None));
+                        exit(1); // we can afford not-executing destructors here
+                    },
+                }
+            } else if let Some(call) = scmd.value_of("view-in-custom") {
+                Box::new(CustomViewer::new(String::from(call)))
+            } else if let Some(template) = configured_view_template(&rt, entry_id) {
+                debug!("Using view template from configuration: '{}'", template);
+                Box::new(build_template_viewer(&template[..]))
             } else {
                 Box::new(StdoutViewer::new())
             }
@@ -115,10 +175,77 @@ fn main() {
             keep_copy:      keep_copy,
         };
 
-        viewer.view(view_info);
+        if let Err(e) = viewer.view(view_info) {
+            trace_error(&e);
+            exit(1); // we can afford not-executing destructors here
+        }
+    }
+}
+
+fn build_template_viewer(template: &str) -> TemplateViewer {
+    match Template::parse(template) {
+        Ok(t)  => TemplateViewer::new(t),
+        Err(e) => {
+            warn!("Could not parse template: '{}'", template);
+            trace_error(&e);
+            exit(1); // we can afford not-executing destructors here
+        },
     }
 }
 
+/// Get the "view-template" from the configuration section of the module the entry belongs to
+fn configured_view_template(rt: &Runtime, id: &str) -> Option<String> {
+    let module = id.trim_left_matches('/').split('/').next().unwrap_or("");
+    rt.config()
+        .and_then(|cfg| template_from_config(cfg.config(), module, "view-template"))
+}
+
+/// Compile the entry from the markup `from` to `to` and print the result
+///
+/// If `copy` is true, the result is written to a file in the temporary directory, which is kept,
+/// and the path of the file is printed.
+fn compile_entry(rt: &Runtime,
+                 id: &str,
+                 version: Option<&str>,
+                 from: &str,
+                 to: &str,
+                 copy: bool)
+    -> Result<()>
+{
+    let markup = try!(Markup::from_name(from)
+                      .map_err(|e| ViewError::new(ViewErrorKind::ViewerError, Some(Box::new(e)))));
+    let target = try!(Target::from_name(to)
+                      .map_err(|e| ViewError::new(ViewErrorKind::ViewerError, Some(Box::new(e)))));
+
+    let entry  = try!(load_entry(id, version, rt));
+    let output = try!(compiler(markup, target, rt.store().path().clone())
+                      .compile(&entry)
+                      .map_err(|e| ViewError::new(ViewErrorKind::ViewerError, Some(Box::new(e)))));
+
+    if copy {
+        let suffix = match target {
+            Target::Html     => ".html",
+            Target::Terminal => ".txt",
+        };
+        let name = copy_name(entry.get_location());
+        let copy = try!(TempCopy::new(&name[..], suffix, &output[..], true));
+        println!("{}", copy.path().display());
+    } else {
+        println!("{}", output);
+    }
+
+    Ok(())
+}
+
+/// Get the editor from the commandline, the configuration or $EDITOR, in this order
+fn editor(rt: &Runtime) -> Option<String> {
+    rt.cli()
+        .value_of("editor")
+        .map(String::from)
+        .or(rt.config().and_then(|c| c.editor().map(|s| s.clone())))
+        .or(env::var("EDITOR").ok())
+}
+
 // TODO: This is a shameless adaption of imag-store/src/util.rs
 fn load_entry<'a>(id: &str,
                   version: Option<&str>,
@@ -0,0 +1,319 @@
+//! Export entries as a static HTML site
+//!
+//! The site consists of
+//!
+//! * one page per entry at `<module>/<path>~<version>.html`, with the front-matter, the content,
+//!   the tags, the internal links, the backlinks and the external links of the entry,
+//! * one index page per module at `<module>/index.html`,
+//! * one page per tag at `tags/<tag>.html`, hierarchical tags in subdirectories ("work/x" at
+//!   `tags/work/x.html`),
+//! * an `index.html`, linking to the module indexes and tag pages.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use libimagentrylink::external::ExternalLinker;
+use libimagentrytag::tagable::Tagable;
+use libimagentryview::compile::{internal_links, page_path};
+use libimagentryview::compile::html::{HtmlCompiler, escape_html, page};
+use libimagrt::runtime::Runtime;
+use libimagstore::store::{Entry, StoreObject};
+use libimagstore::storeid::StoreId;
+
+use error::{ViewError, ViewErrorKind};
+use viewer::Result;
+
+/// An entry which is exported, with the data which is needed for its page
+struct Page {
+    entry: Entry,
+    module: String,
+    tags: Vec<String>,
+}
+
+/// Export the entries of `modules` (all modules if empty) to `dir`
+///
+/// If `tags` is not empty, only entries which have at least one of these tags are exported. The
+/// "links" module, which holds the external links, is not exported unless explicitely requested.
+pub fn export_site(rt: &Runtime, dir: &Path, modules: Vec<String>, tags: Vec<String>) -> Result<()> {
+    let store_path = rt.store().path().clone();
+    let modules = if modules.is_empty() {
+        try!(all_modules(&store_path))
+    } else {
+        modules
+    };
+
+    let mut pages = vec![];
+    for module in modules.iter() {
+        for obj in rt.store().walk(&module[..]) {
+            let id = match obj {
+                StoreObject::Id(id)        => id,
+                StoreObject::Collection(_) => continue,
+            };
+
+            debug!("Loading entry for export: {:?}", id);
+            let entry = try!(rt.store()
+                             .retrieve_copy(id)
+                             .map_err(|e| ViewError::new(ViewErrorKind::StoreError, Some(Box::new(e)))));
+
+            let entry_tags = try!(entry.get_tags()
+                .map_err(|e| ViewError::new(ViewErrorKind::ExportError, Some(Box::new(e)))));
+
+            if !tags.is_empty() && !entry_tags.iter().any(|t| tags.contains(t)) {
+                debug!("Skipping entry, tags do not match: {:?}", entry.get_location());
+                continue;
+            }
+
+            pages.push(Page {
+                entry: entry,
+                module: module.clone(),
+                tags: entry_tags,
+            });
+        }
+    }
+
+    info!("Exporting {} entries to {}", pages.len(), dir.display());
+
+    let exported : Vec<StoreId> = pages.iter().map(|p| p.entry.get_location().clone()).collect();
+
+    let backlinks = try!(collect_backlinks(&pages[..]));
+
+    let compiler = HtmlCompiler::new(store_path.clone(), true);
+    let mut module_index : BTreeMap<String, Vec<StoreId>> = BTreeMap::new();
+    let mut tag_index : BTreeMap<String, Vec<StoreId>> = BTreeMap::new();
+
+    for p in pages.iter() {
+        let id = p.entry.get_location();
+        let path = page_path(&store_path, id);
+        let root = root_prefix(&path);
+
+        let links = try!(internal_links(&p.entry)
+            .map_err(|e| ViewError::new(ViewErrorKind::ExportError, Some(Box::new(e)))))
+            .into_iter()
+            .filter(|l| exported.contains(l))
+            .collect::<Vec<StoreId>>();
+
+        let back = backlinks.get(id)
+            .map(|b| b.iter().filter(|l| !links.contains(l)).cloned().collect())
+            .unwrap_or(vec![]);
+
+        let external = try!(p.entry.get_external_links(rt.store())
+            .map_err(|e| ViewError::new(ViewErrorKind::ExportError, Some(Box::new(e)))));
+
+        let mut body = format!("<p><a href=\"{}{}/index.html\">{}</a></p>\n",
+                               root, escape_html(&p.module[..]), escape_html(&p.module[..]));
+
+        body.push_str(&compiler.front_matter_html(&p.entry)[..]);
+
+        if !p.tags.is_empty() {
+            body.push_str("<ul class=\"tags\">\n");
+            for tag in p.tags.iter() {
+                body.push_str(&format!("<li><a href=\"{}{}\">{}</a></li>\n",
+                                       root, escape_html(&tag_page(tag)[..]),
+                                       escape_html(&tag[..]))[..]);
+            }
+            body.push_str("</ul>\n");
+        }
+
+        body.push_str(&try!(compiler.content_html(&p.entry)
+            .map_err(|e| ViewError::new(ViewErrorKind::ExportError, Some(Box::new(e)))))[..]);
+
+        if !links.is_empty() {
+            body.push_str("<h2>Links</h2>\n");
+            body.push_str(&compiler.link_list_html("links", id, &links[..])[..]);
+        }
+
+        if !back.is_empty() {
+            body.push_str("<h2>Backlinks</h2>\n");
+            body.push_str(&compiler.link_list_html("backlinks", id, &back[..])[..]);
+        }
+
+        if !external.is_empty() {
+            body.push_str("<h2>External links</h2>\n<ul class=\"external-links\">\n");
+            for url in external {
+                let url = escape_html(url.as_str());
+                body.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n", url, url)[..]);
+            }
+            body.push_str("</ul>\n");
+        }
+
+        try!(write_page(dir, &path, &page(&compiler.entry_name(id)[..], &body[..])[..]));
+
+        module_index.entry(p.module.clone()).or_insert(vec![]).push(id.clone());
+        for tag in p.tags.iter() {
+            tag_index.entry(tag.clone()).or_insert(vec![]).push(id.clone());
+        }
+    }
+
+    for (module, ids) in module_index.iter() {
+        let body = entry_list(&compiler, &store_path, "../", &ids[..]);
+        let path = PathBuf::from(module).join("index.html");
+        try!(write_page(dir, &path, &page(&module[..], &body[..])[..]));
+    }
+
+    for (tag, ids) in tag_index.iter() {
+        let path = PathBuf::from(tag_page(tag));
+        let body = entry_list(&compiler, &store_path, &root_prefix(&path)[..], &ids[..]);
+        try!(write_page(dir, &path, &page(&format!("Tag: {}", tag)[..], &body[..])[..]));
+    }
+
+    let mut body = String::from("<h2>Modules</h2>\n<ul class=\"modules\">\n");
+    for module in module_index.keys() {
+        let module = escape_html(&module[..]);
+        body.push_str(&format!("<li><a href=\"{}/index.html\">{}</a></li>\n", module, module)[..]);
+    }
+    body.push_str("</ul>\n");
+
+    if !tag_index.is_empty() {
+        body.push_str("<h2>Tags</h2>\n<ul class=\"tags\">\n");
+        for (tag, ids) in tag_index.iter() {
+            body.push_str(&format!("<li><a href=\"{}\">{}</a> ({})</li>\n",
+                                   escape_html(&tag_page(tag)[..]),
+                                   escape_html(&tag[..]),
+                                   ids.len())[..]);
+        }
+        body.push_str("</ul>\n");
+    }
+
+    write_page(dir, Path::new("index.html"), &page("imag", &body[..])[..])
+}
+
+/// Get the names of all modules in the store, except the "links" module
+fn all_modules(store_path: &Path) -> Result<Vec<String>> {
+    let entries = try!(fs::read_dir(store_path)
+        .map_err(|e| ViewError::new(ViewErrorKind::IOError, Some(Box::new(e)))));
+
+    let mut modules = vec![];
+    for entry in entries {
+        let entry = try!(entry.map_err(|e| ViewError::new(ViewErrorKind::IOError, Some(Box::new(e)))));
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        match entry.file_name().to_str() {
+            Some(name) if is_dir && name != "links" && !name.starts_with(".") => {
+                modules.push(String::from(name))
+            },
+            _ => { },
+        }
+    }
+    modules.sort();
+    Ok(modules)
+}
+
+/// Render a list of links to the entry pages, for a page which is `root` away from the root
+fn entry_list(compiler: &HtmlCompiler, store_path: &Path, root: &str, ids: &[StoreId]) -> String {
+    let mut out = String::from("<ul class=\"entries\">\n");
+    for id in ids {
+        let path = page_path(store_path, id);
+        let href = path.components()
+            .filter_map(|c| c.as_os_str().to_str())
+            .collect::<Vec<&str>>()
+            .join("/");
+        out.push_str(&format!("<li><a href=\"{}{}\">{}</a></li>\n",
+                              root, escape_html(&href[..]),
+                              escape_html(&compiler.entry_name(id)[..]))[..]);
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+/// Get the prefix to get from the page at `path` to the root of the site
+fn root_prefix(path: &Path) -> String {
+    let mut prefix = String::new();
+    for _ in 1..path.components().count() {
+        prefix.push_str("../");
+    }
+    prefix
+}
+
+/// Get the path of the page for `tag`, relative to the root of the site
+///
+/// The levels of hierarchical tags are directories, so "work/x" and "work-x" get different pages.
+fn tag_page(tag: &str) -> String {
+    format!("tags/{}.html", tag)
+}
+
+/// Build the backlinks from the links of all exported entries: the ids of the linking entries for
+/// each linked id
+fn collect_backlinks(pages: &[Page]) -> Result<BTreeMap<StoreId, Vec<StoreId>>> {
+    let mut backlinks : BTreeMap<StoreId, Vec<StoreId>> = BTreeMap::new();
+    for p in pages.iter() {
+        let links = try!(internal_links(&p.entry)
+            .map_err(|e| ViewError::new(ViewErrorKind::ExportError, Some(Box::new(e)))));
+
+        for link in links {
+            backlinks.entry(link).or_insert(vec![]).push(p.entry.get_location().clone());
+        }
+    }
+    Ok(backlinks)
+}
+
+fn write_page(dir: &Path, path: &Path, html: &str) -> Result<()> {
+    let path = dir.join(path);
+    debug!("Writing page: {:?}", path);
+
+    if let Some(parent) = path.parent() {
+        try!(fs::create_dir_all(parent)
+             .map_err(|e| ViewError::new(ViewErrorKind::IOError, Some(Box::new(e)))));
+    }
+
+    File::create(&path)
+        .and_then(|mut f| f.write_all(html.as_bytes()))
+        .map_err(|e| ViewError::new(ViewErrorKind::IOError, Some(Box::new(e))))
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use toml::Value;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+
+    use super::{Page, collect_backlinks, root_prefix, tag_page};
+
+    fn id(name: &str) -> StoreId {
+        StoreId::from(PathBuf::from(format!("/store/notes/{}~0.1.0", name)))
+    }
+
+    fn page(name: &str, links: &[&str]) -> Page {
+        let mut entry = Entry::new(id(name));
+        let links = links.iter()
+            .map(|l| Value::String(String::from(id(l).to_str().unwrap())))
+            .collect();
+        entry.get_header_mut().set("imag.links", Value::Array(links)).unwrap();
+
+        Page {
+            entry: entry,
+            module: String::from("notes"),
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_tag_pages_do_not_collide() {
+        assert_eq!("tags/work.html", tag_page("work"));
+        assert_eq!("tags/work/x.html", tag_page("work/x"));
+        assert_eq!("tags/work-x.html", tag_page("work-x"));
+    }
+
+    #[test]
+    fn test_root_prefix_of_tag_pages() {
+        assert_eq!("../", root_prefix(Path::new(&tag_page("work"))));
+        assert_eq!("../../", root_prefix(Path::new(&tag_page("work/x"))));
+        assert_eq!("", root_prefix(Path::new("index.html")));
+    }
+
+    #[test]
+    fn test_collect_backlinks() {
+        let pages = vec![page("a", &["b", "c"]), page("b", &["c"]), page("c", &[])];
+        let backlinks = collect_backlinks(&pages[..]).unwrap();
+
+        assert_eq!(None, backlinks.get(&id("a")));
+        assert_eq!(Some(&vec![id("a")]), backlinks.get(&id("b")));
+        assert_eq!(Some(&vec![id("a"), id("b")]), backlinks.get(&id("c")));
+    }
+
+}
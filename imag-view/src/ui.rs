@@ -6,8 +6,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
             .long("id")
             .short("i")
             .takes_value(true)
-            .required(true)
-            .help("View this entry at this store path"))
+            .required(false)
+            .help("View this entry at this store path (required, except for export-site)"))
 
         .arg(Arg::with_name("version")
             .long("version")
@@ -46,7 +46,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
             .short("k")
             .takes_value(false)
             .required(false)
-            .help("If --copy was passed, keep the copy after viewing."))
+            .help("If --copy was passed, keep the copy after viewing. \
+                   Copies for --browser are always kept."))
 
         .subcommand(SubCommand::with_name("view-in")
                    .about("View the entry in ...")
@@ -59,26 +60,44 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .required(false)
                         .help("View by printing to stdout"))
 
+                   .arg(Arg::with_name("view-in-json")
+                        .long("json")
+                        .short("j")
+                        .takes_value(false)
+                        .required(false)
+                        .help("View by printing the entry as JSON to stdout"))
+
+                   .arg(Arg::with_name("view-in-template")
+                        .long("template")
+                        .short("t")
+                        .takes_value(true)
+                        .required(false)
+                        .help("View by printing the entry formatted by this template, for example '{{note.name}}: {{content|lines(1)}}'"))
+
                    .arg(Arg::with_name("view-in-ui")
                         .long("ui")
                         .short("u")
                         .takes_value(false)
                         .required(false)
-                        .help("View by opening own curses-like UI (default)"))
+                        .help("View by opening own curses-like UI"))
 
                    .arg(Arg::with_name("view-in-browser")
                         .long("browser")
                         .short("b")
                         .takes_value(true) // optional, which browser
+                        .min_values(0)
+                        .max_values(1)
                         .required(false)
-                        .help("View content in $BROWSER (fails if no env variable $BROWSER)"))
+                        .help("View content in this browser, or in $BROWSER if none is given"))
 
                    .arg(Arg::with_name("view-in-texteditor")
                         .long("editor")
                         .short("e")
                         .takes_value(true) // optional, which editor
+                        .min_values(0)
+                        .max_values(1)
                         .required(false)
-                        .help("View content in $EDITOR"))
+                        .help("View content in this editor, or in $EDITOR if none is given"))
 
                    .arg(Arg::with_name("view-in-custom")
                         .long("custom")
@@ -89,6 +108,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
 
                    .group(ArgGroup::with_name("viewer")
                           .args(&["view-in-stdout",
+                                  "view-in-json",
+                                  "view-in-template",
                                   "view-in-ui",
                                   "view-in-browser",
                                   "view-in-texteditor",
@@ -98,22 +119,46 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    )
 
         .subcommand(SubCommand::with_name("compile")
-                   .about("Compile content to other format for viewing, implies that the entry gets copied to /tmp")
+                   .about("Compile content to other format for viewing and print it. With --copy, the result is written to /tmp and its path is printed")
                    .version("0.1")
                    .arg(Arg::with_name("from")
                         .long("from")
                         .short("f")
                         .takes_value(true) // "markdown" or "textile" or "restructuredtex"
                         .required(true)
-                        .help("Compile from"))
+                        .help("Compile from (currently only 'markdown')"))
 
                    .arg(Arg::with_name("to")
                         .long("to")
                         .short("t")
                         .takes_value(true) // "html" or "HTML" or ... json maybe?
                         .required(true)
-                        .help("Compile to"))
+                        .help("Compile to ('html' or 'terminal')"))
                    )
-}
 
+        .subcommand(SubCommand::with_name("export-site")
+                   .about("Export entries as static HTML site into a directory")
+                   .version("0.1")
+                   .arg(Arg::with_name("dir")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .help("Directory to export to"))
+
+                   .arg(Arg::with_name("module")
+                        .long("module")
+                        .short("m")
+                        .takes_value(true)
+                        .multiple(true)
+                        .required(false)
+                        .help("Export only entries of this module (all modules except 'links' if not specified)"))
 
+                   .arg(Arg::with_name("tag")
+                        .long("tag")
+                        .short("t")
+                        .takes_value(true)
+                        .multiple(true)
+                        .required(false)
+                        .help("Export only entries with this tag"))
+                   )
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use libimagentryview::compile::Compiler;
+use libimagentryview::compile::html::HtmlCompiler;
+
+use error::{ViewError, ViewErrorKind};
+use viewer::{Result, ViewInformation, Viewer};
+use viewer::copy::{TempCopy, copy_name};
+use viewer::custom::call;
+
+/// Viewer which compiles the entry to a temporary HTML file and opens it in a browser
+///
+/// The header is shown as front-matter if `--header` was passed or `--content` was not passed.
+/// The HTML file is always kept: browsers like xdg-open or an already running firefox return
+/// before they read the file, so it cannot be removed when the browser process exited.
+pub struct BrowserViewer {
+    browser: String,
+    store_path: PathBuf,
+}
+
+impl BrowserViewer {
+
+    pub fn new(browser: String, store_path: PathBuf) -> BrowserViewer {
+        BrowserViewer {
+            browser: browser,
+            store_path: store_path,
+        }
+    }
+
+}
+
+impl Viewer for BrowserViewer {
+
+    fn view(&self, vi: ViewInformation) -> Result<()> {
+        let show_front_matter = vi.view_header || !vi.view_content;
+        let html = try!(HtmlCompiler::new(self.store_path.clone(), show_front_matter)
+            .compile(&vi.entry)
+            .map_err(|e| ViewError::new(ViewErrorKind::ViewerError, Some(Box::new(e)))));
+
+        let name = copy_name(vi.entry.get_location());
+        let copy = try!(TempCopy::new(&name[..], ".html", &html[..], true));
+        call(&self.browser[..], copy.path())
+    }
+
+}
+
@@ -0,0 +1,97 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use error::{ViewError, ViewErrorKind};
+use viewer::Result;
+
+/// A read-only copy of an entry in the temporary directory
+///
+/// The copy is removed when this object is dropped, unless it should be kept.
+pub struct TempCopy {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempCopy {
+
+    /// Write `text` to a new file `<tmpdir>/imag-view-<time>-<n>-<name><suffix>` and make the
+    /// file read-only
+    ///
+    /// The file is created exclusively, with the first number `n` which is not taken, so the
+    /// copies of different users and processes in a shared temporary directory never replace each
+    /// other.
+    pub fn new(name: &str, suffix: &str, text: &str, keep: bool) -> Result<TempCopy> {
+        let (path, mut file) = try!(create_unique(name, suffix));
+
+        debug!("Copying entry to {:?}", path);
+        try!(file.write_all(text.as_bytes())
+             .and_then(|_| file.sync_data())
+             .map_err(mk_io_error));
+
+        let mut permissions = try!(fs::metadata(&path).map_err(mk_io_error)).permissions();
+        permissions.set_readonly(true);
+        try!(fs::set_permissions(&path, permissions).map_err(mk_io_error));
+
+        Ok(TempCopy {
+            path: path,
+            keep: keep,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+}
+
+impl Drop for TempCopy {
+
+    fn drop(&mut self) {
+        if self.keep {
+            info!("Keeping copy: {}", self.path.display());
+        } else {
+            debug!("Removing copy: {:?}", self.path);
+            if let Err(e) = fs::remove_file(&self.path) {
+                warn!("Could not remove {}: {}", self.path.display(), e);
+            }
+        }
+    }
+
+}
+
+/// Create a new file in the temporary directory, which did not exist before
+fn create_unique(name: &str, suffix: &str) -> Result<(PathBuf, File)> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| format!("{}{:09}", d.as_secs(), d.subsec_nanos()))
+        .unwrap_or(String::from("0"));
+
+    for n in 0u64.. {
+        let path = env::temp_dir().join(format!("imag-view-{}-{}-{}{}", time, n, name, suffix));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(ref e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(mk_io_error(e)),
+        }
+    }
+    unreachable!()
+}
+
+/// Build the file name for a copy of the entry at `location`
+pub fn copy_name(location: &Path) -> String {
+    location.file_name()
+        .and_then(|s| s.to_str())
+        .map(String::from)
+        .unwrap_or(String::from("entry"))
+}
+
+fn mk_io_error<E: Error + 'static>(e: E) -> ViewError {
+    ViewError::new(ViewErrorKind::IOError, Some(Box::new(e)))
+}
+
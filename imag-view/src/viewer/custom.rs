@@ -0,0 +1,104 @@
+use std::path::Path;
+use std::process::Command;
+
+use error::{ViewError, ViewErrorKind};
+use viewer::{Result, ViewInformation, Viewer};
+use viewer::copy::{TempCopy, copy_name};
+
+/// Viewer which calls a custom program on the entry file
+///
+/// Each `%e` in the call string is replaced with the path of the entry file. If `--copy` was
+/// passed, this is the path of a read-only copy in the temporary directory, otherwise the path of
+/// the entry in the store.
+pub struct CustomViewer {
+    call: String,
+}
+
+impl CustomViewer {
+
+    pub fn new(call: String) -> CustomViewer {
+        CustomViewer { call: call }
+    }
+
+}
+
+impl Viewer for CustomViewer {
+
+    fn view(&self, vi: ViewInformation) -> Result<()> {
+        if vi.view_copy {
+            let name = copy_name(vi.entry.get_location());
+            let copy = try!(TempCopy::new(&name[..], "", &vi.text()[..], vi.keep_copy));
+            call(&self.call[..], copy.path())
+        } else {
+            call(&self.call[..], vi.entry.get_location())
+        }
+    }
+
+}
+
+/// Build a command from the `call` string, where `%e` is replaced with `path`
+///
+/// If the call string does not contain `%e`, the path is appended as last argument.
+pub fn build_command(call: &str, path: &Path) -> Result<Command> {
+    let path = try!(path.to_str().ok_or(ViewError::new(ViewErrorKind::IOError, None)));
+    let mut parts = call.split_whitespace();
+    let program = try!(parts.next().ok_or(ViewError::new(ViewErrorKind::NoCommand, None)));
+
+    let mut cmd = Command::new(program.replace("%e", path));
+    let mut substituted = program.contains("%e");
+    for part in parts {
+        substituted = substituted || part.contains("%e");
+        cmd.arg(part.replace("%e", path));
+    }
+
+    if !substituted {
+        cmd.arg(path);
+    }
+
+    Ok(cmd)
+}
+
+/// Call the program from the `call` string on `path` and wait for it to exit
+pub fn call(call: &str, path: &Path) -> Result<()> {
+    let mut cmd = try!(build_command(call, path));
+    debug!("Calling: {:?}", cmd);
+
+    match cmd.status().map(|s| s.success()) {
+        Ok(true)  => Ok(()),
+        Ok(false) => Err(ViewError::new(ViewErrorKind::ProcessExitFailure, None)),
+        Err(e)    => Err(ViewError::new(ViewErrorKind::IOError, Some(Box::new(e)))),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use error::ViewErrorKind;
+    use super::build_command;
+
+    fn command(call: &str) -> String {
+        format!("{:?}", build_command(call, Path::new("/tmp/entry")).unwrap())
+    }
+
+    #[test]
+    fn test_path_is_appended_without_placeholder() {
+        assert_eq!("\"less\" \"/tmp/entry\"", command("less"));
+        assert_eq!("\"less\" \"-R\" \"/tmp/entry\"", command("less -R"));
+    }
+
+    #[test]
+    fn test_placeholder_is_replaced() {
+        assert_eq!("\"vim\" \"/tmp/entry\" \"-R\"", command("vim %e -R"));
+        assert_eq!("\"viewer\" \"--file=/tmp/entry\"", command("viewer --file=%e"));
+        assert_eq!("\"diff\" \"/tmp/entry\" \"/tmp/entry\"", command("diff %e %e"));
+    }
+
+    #[test]
+    fn test_empty_call() {
+        let res = build_command("  ", Path::new("/tmp/entry"));
+        assert_eq!(ViewErrorKind::NoCommand, res.unwrap_err().err_type());
+    }
+
+}
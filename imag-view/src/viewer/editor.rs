@@ -0,0 +1,29 @@
+use viewer::{Result, ViewInformation, Viewer};
+use viewer::copy::{TempCopy, copy_name};
+use viewer::custom::call;
+
+/// Viewer which opens a read-only copy of the entry in an editor
+///
+/// The copy is removed after the editor exited, unless `--keep-copy` was passed.
+pub struct EditorViewer {
+    editor: String,
+}
+
+impl EditorViewer {
+
+    pub fn new(editor: String) -> EditorViewer {
+        EditorViewer { editor: editor }
+    }
+
+}
+
+impl Viewer for EditorViewer {
+
+    fn view(&self, vi: ViewInformation) -> Result<()> {
+        let name = copy_name(vi.entry.get_location());
+        let copy = try!(TempCopy::new(&name[..], "", &vi.text()[..], vi.keep_copy));
+        call(&self.editor[..], copy.path())
+    }
+
+}
+
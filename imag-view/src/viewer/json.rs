@@ -0,0 +1,28 @@
+use libimagentryview::builtin::json::JsonViewer as EntryJsonViewer;
+use libimagentryview::viewer::Viewer as EntryViewer;
+
+use error::{ViewError, ViewErrorKind};
+use viewer::{Result, ViewInformation, Viewer};
+
+/// Viewer which prints the entry as JSON object to stdout
+pub struct JsonViewer {
+    pretty: bool,
+}
+
+impl JsonViewer {
+
+    pub fn new(pretty: bool) -> JsonViewer {
+        JsonViewer { pretty: pretty }
+    }
+
+}
+
+impl Viewer for JsonViewer {
+
+    fn view(&self, vi: ViewInformation) -> Result<()> {
+        let viewer = EntryJsonViewer::new(vi.view_header, vi.view_content, self.pretty);
+        viewer.view_entry(&vi.entry)
+            .map_err(|e| ViewError::new(ViewErrorKind::ViewerError, Some(Box::new(e))))
+    }
+
+}
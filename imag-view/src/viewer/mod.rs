@@ -1,7 +1,22 @@
+pub mod browser;
+pub mod copy;
+pub mod custom;
+pub mod editor;
+pub mod json;
 pub mod stdout;
+pub mod template;
+pub mod ui;
+
+use std::result::Result as RResult;
+
+use toml::encode_str;
 
 use libimagstore::store::FileLockEntry;
 
+use error::ViewError;
+
+pub type Result<T> = RResult<T, ViewError>;
+
 pub struct ViewInformation<'a> {
     pub entry: FileLockEntry<'a>,
     pub view_header: bool,
@@ -10,7 +25,26 @@ pub struct ViewInformation<'a> {
     pub keep_copy: bool,
 }
 
+impl<'a> ViewInformation<'a> {
+
+    /// The text to view, depending on whether the header and/or the content should be viewed.
+    ///
+    /// If neither is requested, the whole entry is returned as it is stored on disk.
+    pub fn text(&self) -> String {
+        match (self.view_header, self.view_content) {
+            (true, true)   => {
+                format!("{}\n{}", encode_str(self.entry.get_header().header()),
+                                  self.entry.get_content())
+            },
+            (true, false)  => encode_str(self.entry.get_header().header()),
+            (false, true)  => self.entry.get_content().clone(),
+            (false, false) => self.entry.to_str(),
+        }
+    }
+
+}
+
 pub trait Viewer {
-    fn view(&self, vi: ViewInformation);
+    fn view(&self, vi: ViewInformation) -> Result<()>;
 }
 
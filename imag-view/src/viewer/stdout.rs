@@ -1,39 +1,38 @@
-use std::io::{Stdout, stdout};
+use std::fs::File;
+use std::io::Read;
 
-use toml::encode_str;
+use error::{ViewError, ViewErrorKind};
+use viewer::{Result, ViewInformation, Viewer};
+use viewer::copy::{TempCopy, copy_name};
 
-use viewer::{ViewInformation, Viewer};
-
-pub struct StdoutViewer {
-    out: Stdout,
-}
+pub struct StdoutViewer;
 
 impl StdoutViewer {
 
     pub fn new() -> StdoutViewer {
-        StdoutViewer { out: stdout() }
+        StdoutViewer
     }
 
 }
 
 impl Viewer for StdoutViewer {
 
-    fn view(&self, vi: ViewInformation) {
-        if vi.view_copy {
-            unimplemented!();
-        }
-
-        if vi.view_header {
-            println!("{}", encode_str(vi.entry.get_header().header()));
-        }
+    fn view(&self, vi: ViewInformation) -> Result<()> {
+        let text = vi.text();
 
-        if vi.view_content {
-            println!("{}", vi.entry.get_content());
+        if vi.view_copy {
+            let copy = try!(TempCopy::new(&copy_name(vi.entry.get_location())[..], "",
+                                          &text[..], vi.keep_copy));
+            let mut s = String::new();
+            try!(File::open(copy.path())
+                 .and_then(|mut f| f.read_to_string(&mut s))
+                 .map_err(|e| ViewError::new(ViewErrorKind::IOError, Some(Box::new(e)))));
+            println!("{}", s);
+        } else {
+            println!("{}", text);
         }
 
-        if vi.view_copy && !vi.keep_copy {
-            unimplemented!()
-        }
+        Ok(())
     }
 
 }
@@ -0,0 +1,28 @@
+use libimagentryview::builtin::template::TemplateViewer as EntryTemplateViewer;
+use libimagentryview::template::Template;
+use libimagentryview::viewer::Viewer as EntryViewer;
+
+use error::{ViewError, ViewErrorKind};
+use viewer::{Result, ViewInformation, Viewer};
+
+/// Viewer which prints the entry formatted by a template to stdout
+pub struct TemplateViewer {
+    viewer: EntryTemplateViewer,
+}
+
+impl TemplateViewer {
+
+    pub fn new(template: Template) -> TemplateViewer {
+        TemplateViewer { viewer: EntryTemplateViewer::new(template) }
+    }
+
+}
+
+impl Viewer for TemplateViewer {
+
+    fn view(&self, vi: ViewInformation) -> Result<()> {
+        self.viewer.view_entry(&vi.entry)
+            .map_err(|e| ViewError::new(ViewErrorKind::ViewerError, Some(Box::new(e))))
+    }
+
+}
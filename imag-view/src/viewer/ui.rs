@@ -0,0 +1,84 @@
+use std::default::Default;
+
+use rustbox::{Color, Event, Key, RustBox};
+use rustbox::RB_NORMAL;
+use rustbox::RB_REVERSE;
+
+use error::{ViewError, ViewErrorKind};
+use viewer::{Result, ViewInformation, Viewer};
+use viewer::copy::{TempCopy, copy_name};
+
+/// Viewer which shows the entry in a curses-like pager
+///
+/// Keys: `j`/Down and `k`/Up scroll by one line, Space/PageDown and `b`/PageUp by one page,
+/// `g`/Home and `G`/End jump to the top and the bottom, `q`/Esc quits.
+pub struct UiViewer;
+
+impl UiViewer {
+
+    pub fn new() -> UiViewer {
+        UiViewer
+    }
+
+}
+
+impl Viewer for UiViewer {
+
+    fn view(&self, vi: ViewInformation) -> Result<()> {
+        let text = vi.text();
+        let title = format!("{}", vi.entry.get_location().display());
+
+        // Keep the copy alive while the UI is running
+        let _copy = if vi.view_copy {
+            let name = copy_name(vi.entry.get_location());
+            Some(try!(TempCopy::new(&name[..], "", &text[..], vi.keep_copy)))
+        } else {
+            None
+        };
+
+        let lines : Vec<&str> = text.lines().collect();
+        let rb = try!(RustBox::init(Default::default())
+            .map_err(|e| ViewError::new(ViewErrorKind::UIError, Some(Box::new(e)))));
+
+        let mut offset = 0;
+        loop {
+            let page = if rb.height() > 1 { rb.height() - 1 } else { 1 };
+            let max_offset = if lines.len() > page { lines.len() - page } else { 0 };
+            if offset > max_offset {
+                offset = max_offset;
+            }
+
+            draw(&rb, &title[..], &lines[..], offset, page);
+
+            match rb.poll_event(false) {
+                Ok(Event::KeyEvent(key)) => match key {
+                    Key::Char('q') | Key::Esc => break,
+                    Key::Char('j') | Key::Down => offset += 1,
+                    Key::Char('k') | Key::Up => offset = offset.saturating_sub(1),
+                    Key::Char(' ') | Key::PageDown => offset += page,
+                    Key::Char('b') | Key::PageUp => offset = offset.saturating_sub(page),
+                    Key::Char('g') | Key::Home => offset = 0,
+                    Key::Char('G') | Key::End => offset = max_offset,
+                    _ => { },
+                },
+                Ok(_) => { },
+                Err(e) => return Err(ViewError::new(ViewErrorKind::UIError, Some(Box::new(e)))),
+            }
+        }
+
+        Ok(())
+    }
+
+}
+
+fn draw(rb: &RustBox, title: &str, lines: &[&str], offset: usize, page: usize) {
+    rb.clear();
+    for (y, line) in lines.iter().skip(offset).take(page).enumerate() {
+        rb.print(0, y, RB_NORMAL, Color::Default, Color::Default, line);
+    }
+
+    let status = format!("{} ({}/{}) -- q to quit", title, offset + 1, lines.len());
+    rb.print(0, page, RB_REVERSE, Color::Default, Color::Default, &status[..]);
+    rb.present();
+}
+
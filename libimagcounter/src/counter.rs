@@ -1,16 +1,21 @@
 use std::ops::DerefMut;
 
 use toml::Value;
+use chrono::Local;
+use chrono::NaiveDateTime;
 
 use std::collections::BTreeMap;
 
 use libimagstore::store::Store;
 use libimagstore::storeid::StoreIdIterator;
 use libimagstore::store::FileLockEntry;
+use libimagstore::store::StoreObject;
 use libimagstore::storeid::StoreId;
 use libimagstore::storeid::IntoStoreId;
 
 use module_path::ModuleEntryPath;
+use event::CounterEvent;
+use meta::{CounterMeta, Goal};
 use result::Result;
 use error::CounterError as CE;
 use error::CounterErrorKind as CEK;
@@ -51,6 +56,12 @@ impl<'a> Counter<'a> {
                 if setres.is_err() {
                     return Err(CE::new(CEK::StoreWriteError, Some(Box::new(setres.unwrap_err()))));
                 }
+
+                let event  = CounterEvent::initial(init).to_value();
+                let setres = header.set("counter.events", Value::Array(vec![event]));
+                if setres.is_err() {
+                    return Err(CE::new(CEK::StoreWriteError, Some(Box::new(setres.unwrap_err()))));
+                }
             }
 
             lockentry
@@ -59,46 +70,159 @@ impl<'a> Counter<'a> {
         Ok(Counter { fle: fle })
     }
 
+    /// Increase the value by the step size of the counter
     pub fn inc(&mut self) -> Result<()> {
-        let mut header = self.fle.deref_mut().get_header_mut();
-        match header.read("counter.value") {
-            Ok(Some(Value::Integer(i))) => {
-                header.set("counter.value", Value::Integer(i + 1))
-                    .map_err(|e| CE::new(CEK::StoreWriteError, Some(Box::new(e))))
-                    .map(|_| ())
-            },
-            Err(e) => Err(CE::new(CEK::StoreReadError, Some(Box::new(e)))),
-            _ => Err(CE::new(CEK::StoreReadError, None)),
-        }
+        let step = try!(self.meta()).step;
+        self.change_by(step, None)
     }
 
+    /// Decrease the value by the step size of the counter
     pub fn dec(&mut self) -> Result<()> {
-        let mut header = self.fle.deref_mut().get_header_mut();
-        match header.read("counter.value") {
-            Ok(Some(Value::Integer(i))) => {
-                header.set("counter.value", Value::Integer(i - 1))
-                    .map_err(|e| CE::new(CEK::StoreWriteError, Some(Box::new(e))))
-                    .map(|_| ())
-            },
-            Err(e) => Err(CE::new(CEK::StoreReadError, Some(Box::new(e)))),
-            _ => Err(CE::new(CEK::StoreReadError, None)),
-        }
+        let step = try!(self.meta()).step;
+        self.change_by(-step, None)
     }
 
     pub fn reset(&mut self) -> Result<()> {
-        let mut header = self.fle.deref_mut().get_header_mut();
-        header.set("counter.value", Value::Integer(0))
-            .map_err(|e| CE::new(CEK::StoreWriteError, Some(Box::new(e))))
-            .map(|_| ())
+        self.set(0)
     }
 
     pub fn set(&mut self, v: i64) -> Result<()> {
+        self.set_with_note(v, None)
+    }
+
+    /// Change the value by `delta` and record the change as event, with an optional note
+    pub fn change_by(&mut self, delta: i64, note: Option<String>) -> Result<()> {
+        let value = try!(self.value());
+        self.write_value(value + delta, CounterEvent::now(delta, note))
+    }
+
+    /// Set the value and record the change as event, with an optional note
+    pub fn set_with_note(&mut self, v: i64, note: Option<String>) -> Result<()> {
+        let value = try!(self.value());
+        self.write_value(v, CounterEvent::now(v - value, note))
+    }
+
+    fn write_value(&mut self, v: i64, event: CounterEvent) -> Result<()> {
+        if !try!(self.meta()).in_bounds(v) {
+            debug!("Value {} is out of the bounds of the counter", v);
+            return Err(CE::new(CEK::ValueOutOfBounds, None));
+        }
+
+        let mut events = match self.fle.get_header().read("counter.events") {
+            Ok(Some(Value::Array(a))) => a,
+            Ok(None)    => vec![],
+            Ok(Some(_)) => return Err(CE::new(CEK::HeaderTypeError, None)),
+            Err(e)      => return Err(CE::new(CEK::StoreReadError, Some(Box::new(e)))),
+        };
+        events.push(event.to_value());
+
         let mut header = self.fle.deref_mut().get_header_mut();
+        try!(header.set("counter.events", Value::Array(events))
+            .map_err(|e| CE::new(CEK::StoreWriteError, Some(Box::new(e)))));
         header.set("counter.value", Value::Integer(v))
             .map_err(|e| CE::new(CEK::StoreWriteError, Some(Box::new(e))))
             .map(|_| ())
     }
 
+    /// Get all changes of the counter, in the order they happened
+    ///
+    /// Counters which were created before events were recorded have no events for the changes
+    /// until then.
+    pub fn events(&self) -> Result<Vec<CounterEvent>> {
+        match self.fle.get_header().read("counter.events") {
+            Ok(Some(Value::Array(a))) => a.iter().map(CounterEvent::from_value).collect(),
+            Ok(None)    => Ok(vec![]),
+            Ok(Some(_)) => Err(CE::new(CEK::HeaderTypeError, None)),
+            Err(e)      => Err(CE::new(CEK::StoreReadError, Some(Box::new(e)))),
+        }
+    }
+
+    /// Get the changes of the counter which happened between `from` and `to`, both inclusive. A
+    /// missing bound does not restrict the range.
+    pub fn events_between(&self, from: Option<NaiveDateTime>, to: Option<NaiveDateTime>)
+        -> Result<Vec<CounterEvent>>
+    {
+        self.events()
+            .map(|events| {
+                events.into_iter()
+                    .filter(|ev| from.map(|f| ev.timestamp >= f).unwrap_or(true))
+                    .filter(|ev| to.map(|t| ev.timestamp <= t).unwrap_or(true))
+                    .collect()
+            })
+    }
+
+    /// Get all changes of the counter together with the value of the counter after the change
+    pub fn history(&self) -> Result<Vec<(CounterEvent, i64)>> {
+        let value  = try!(self.value());
+        let events = try!(self.events());
+
+        let mut current = events.iter().fold(value, |acc, ev| acc - ev.delta);
+        Ok(events.into_iter()
+           .map(|ev| {
+               current += ev.delta;
+               (ev, current)
+           })
+           .collect())
+    }
+
+    /// Replay the events to get the value the counter had at `time`
+    ///
+    /// The changes which were not recorded as events are assumed to have happened before the first
+    /// event.
+    pub fn value_at(&self, time: NaiveDateTime) -> Result<i64> {
+        let value  = try!(self.value());
+        let events = try!(self.events());
+
+        let initial = events.iter().fold(value, |acc, ev| acc - ev.delta);
+        Ok(events.iter()
+           .filter(|ev| ev.timestamp <= time)
+           .fold(initial, |acc, ev| acc + ev.delta))
+    }
+
+    /// Get the unit, bounds, step size and goal of the counter
+    pub fn meta(&self) -> Result<CounterMeta> {
+        match self.fle.get_header().read("counter") {
+            Ok(Some(Value::Table(t))) => CounterMeta::from_table(&t),
+            Ok(_)  => Err(CE::new(CEK::HeaderTypeError, None)),
+            Err(e) => Err(CE::new(CEK::StoreReadError, Some(Box::new(e)))),
+        }
+    }
+
+    /// Set the unit, bounds, step size and goal of the counter
+    ///
+    /// The bounds are enforced for the following changes, the current value is not checked.
+    pub fn set_meta(&mut self, meta: &CounterMeta) -> Result<()> {
+        let mut table = match self.fle.get_header().read("counter") {
+            Ok(Some(Value::Table(t))) => t,
+            Ok(_)  => return Err(CE::new(CEK::HeaderTypeError, None)),
+            Err(e) => return Err(CE::new(CEK::StoreReadError, Some(Box::new(e)))),
+        };
+        meta.to_table(&mut table);
+
+        self.fle.deref_mut().get_header_mut().set("counter", Value::Table(table))
+            .map_err(|e| CE::new(CEK::StoreWriteError, Some(Box::new(e))))
+            .map(|_| ())
+    }
+
+    /// Get how much the counter was increased in the current period of its goal, and the goal
+    ///
+    /// Only increases count, so resetting the counter does not undo the progress. The initial value
+    /// of the counter does not count either. Returns None if the counter has no goal.
+    pub fn goal_progress(&self) -> Result<Option<(i64, Goal)>> {
+        let goal = match try!(self.meta()).goal {
+            Some(goal) => goal,
+            None => return Ok(None),
+        };
+
+        let start  = goal.period.start_of(&Local::today().naive_local()).and_hms(0, 0, 0);
+        let events = try!(self.events_between(Some(start), None));
+        let progress = events.iter()
+            .filter(|ev| !ev.initial && ev.delta > 0)
+            .fold(0, |acc, ev| acc + ev.delta);
+
+        Ok(Some((progress, goal)))
+    }
+
     pub fn name(&self) -> Result<CounterName> {
         self.fle.get_header().read("counter.name")
             .map_err(|e| CE::new(CEK::StoreWriteError, Some(Box::new(e))))
@@ -135,10 +259,26 @@ impl<'a> Counter<'a> {
             .map_err(|e| CE::new(CEK::StoreWriteError, Some(Box::new(e))))
     }
 
+    /// Get all counters, including the counters in groups
     pub fn all_counters(store: &Store) -> Result<CounterIterator> {
-        store.retrieve_for_module("counter")
-            .map(|iter| CounterIterator::new(store, iter))
-            .map_err(|e| CE::new(CEK::StoreReadError, Some(Box::new(e))))
+        Ok(Counter::walk(store, String::from("counter")))
+    }
+
+    /// Get the counters in the group, including the counters in its subgroups, see
+    /// `group::CounterGroup`
+    pub fn in_group<'b>(store: &'b Store, group: &str) -> Result<CounterIterator<'b>> {
+        Ok(Counter::walk(store, format!("counter/{}", group)))
+    }
+
+    fn walk(store: &Store, path: String) -> CounterIterator {
+        let ids = store.walk(&path[..])
+            .filter_map(|obj| {
+                match obj {
+                    StoreObject::Id(id) => Some(id),
+                    StoreObject::Collection(_) => None,
+                }
+            });
+        CounterIterator::new(store, StoreIdIterator::new(Box::new(ids)))
     }
 
 }
@@ -186,3 +326,138 @@ impl<'a> Iterator for CounterIterator<'a> {
 
 }
 
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::remove_dir_all;
+    use std::ops::DerefMut;
+
+    use chrono::NaiveDateTime;
+    use toml::Value;
+
+    use libimagstore::store::Store;
+
+    use event::{CounterEvent, TIMESTAMP_FORMAT};
+    use meta::{CounterMeta, Goal, Period};
+    use super::Counter;
+
+    fn store(name: &str) -> Store {
+        let mut path = env::temp_dir();
+        path.push(format!("imag-counter-test-{}", name));
+        let _ = remove_dir_all(&path);
+        Store::new(path, None).unwrap()
+    }
+
+    fn time(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, TIMESTAMP_FORMAT).unwrap()
+    }
+
+    /// Create the counter "test" with the value `value` and the `events`
+    fn counter<'a>(store: &'a Store, value: i64, events: Vec<(&str, i64)>) -> Counter<'a> {
+        let events = events.into_iter()
+            .map(|(ts, delta)| {
+                CounterEvent { timestamp: time(ts), delta: delta, note: None, initial: false }
+            })
+            .map(|ev| ev.to_value())
+            .collect();
+
+        let mut counter = Counter::new(store, String::from("test"), 0).unwrap();
+        {
+            let header = counter.fle.deref_mut().get_header_mut();
+            header.set("counter.events", Value::Array(events)).unwrap();
+            header.set("counter.value", Value::Integer(value)).unwrap();
+        }
+        counter
+    }
+
+    /// Changed by 3, -1 and 5 to 10, so it was 3 before the first recorded event
+    fn counter_with_history(store: &Store) -> Counter {
+        counter(store, 10, vec![("2016-01-01T10:00:00", 3),
+                                ("2016-01-02T10:00:00", -1),
+                                ("2016-01-03T10:00:00", 5)])
+    }
+
+    #[test]
+    fn test_value_at() {
+        let store = store("value-at");
+        let c = counter_with_history(&store);
+
+        assert_eq!(3, c.value_at(time("2015-12-31T00:00:00")).unwrap());
+        assert_eq!(3, c.value_at(time("2016-01-01T09:59:59")).unwrap());
+        assert_eq!(6, c.value_at(time("2016-01-01T10:00:00")).unwrap());
+        assert_eq!(5, c.value_at(time("2016-01-02T12:00:00")).unwrap());
+        assert_eq!(10, c.value_at(time("2016-01-03T10:00:00")).unwrap());
+        assert_eq!(10, c.value_at(time("2017-01-01T00:00:00")).unwrap());
+    }
+
+    #[test]
+    fn test_value_at_without_events() {
+        let store = store("value-at-without-events");
+        let c = counter(&store, 7, vec![]);
+        assert_eq!(7, c.value_at(time("2016-01-01T00:00:00")).unwrap());
+    }
+
+    #[test]
+    fn test_events_between() {
+        let store = store("events-between");
+        let c = counter_with_history(&store);
+        let deltas = |from: Option<&str>, to: Option<&str>| -> Vec<i64> {
+            c.events_between(from.map(time), to.map(time))
+                .unwrap()
+                .into_iter()
+                .map(|ev| ev.delta)
+                .collect()
+        };
+
+        assert_eq!(vec![3, -1, 5], deltas(None, None));
+        assert_eq!(vec![-1, 5], deltas(Some("2016-01-02T10:00:00"), None));
+        assert_eq!(vec![3, -1], deltas(None, Some("2016-01-02T10:00:00")));
+        assert_eq!(vec![-1, 5], deltas(Some("2016-01-02T10:00:00"), Some("2016-01-03T10:00:00")));
+        assert_eq!(vec![-1], deltas(Some("2016-01-01T10:00:01"), Some("2016-01-03T09:59:59")));
+        assert!(deltas(Some("2016-01-04T00:00:00"), None).is_empty());
+    }
+
+    #[test]
+    fn test_history() {
+        let store = store("history");
+        let c = counter_with_history(&store);
+        let history : Vec<(i64, i64)> = c.history()
+            .unwrap()
+            .into_iter()
+            .map(|(ev, value)| (ev.delta, value))
+            .collect();
+
+        assert_eq!(vec![(3, 6), (-1, 5), (5, 10)], history);
+    }
+
+    #[test]
+    fn test_history_of_new_counter() {
+        let store = store("history-new");
+        let mut c = Counter::new(&store, String::from("new"), 4).unwrap();
+        c.change_by(2, Some(String::from("note"))).unwrap();
+        c.set(1).unwrap();
+
+        let history = c.history().unwrap();
+        assert_eq!(vec![(4, 4), (2, 6), (-5, 1)],
+                   history.iter().map(|&(ref ev, v)| (ev.delta, v)).collect::<Vec<_>>());
+        assert_eq!(Some(String::from("note")), history[1].0.note);
+    }
+
+    #[test]
+    fn test_goal_progress_excludes_initial_value() {
+        let store = store("goal-progress");
+        let mut c = Counter::new(&store, String::from("goal"), 5).unwrap();
+        assert_eq!(None, c.goal_progress().unwrap());
+
+        let goal = Goal { value: 8, period: Period::Day };
+        c.set_meta(&CounterMeta { goal: Some(goal.clone()), ..CounterMeta::default() }).unwrap();
+        assert_eq!(Some((0, goal.clone())), c.goal_progress().unwrap());
+
+        c.change_by(3, None).unwrap();
+        c.change_by(-1, None).unwrap();
+        c.inc().unwrap();
+        assert_eq!(Some((4, goal)), c.goal_progress().unwrap());
+    }
+
+}
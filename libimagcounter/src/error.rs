@@ -12,6 +12,7 @@ pub enum CounterErrorKind {
     StoreWriteError,
     HeaderTypeError,
     HeaderFieldMissingError,
+    ValueOutOfBounds,
 }
 
 fn counter_error_type_as_str(e: &CounterErrorKind) -> &'static str {
@@ -20,6 +21,7 @@ fn counter_error_type_as_str(e: &CounterErrorKind) -> &'static str {
         &CounterErrorKind::StoreWriteError => "Store write error",
         &CounterErrorKind::HeaderTypeError => "Header type error",
         &CounterErrorKind::HeaderFieldMissingError => "Header field missing error",
+        &CounterErrorKind::ValueOutOfBounds => "Value is out of the bounds of the counter",
     }
 }
 
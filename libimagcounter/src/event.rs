@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use chrono::Local;
+use chrono::NaiveDateTime;
+use toml::Value;
+
+use result::Result;
+use error::CounterError as CE;
+use error::CounterErrorKind as CEK;
+
+/// The format the timestamps of the events are stored in
+pub const TIMESTAMP_FORMAT : &'static str = "%Y-%m-%dT%H:%M:%S";
+
+/// A change of a counter, stored in the "counter.events" array of the counter header
+#[derive(Debug, Clone, PartialEq)]
+pub struct CounterEvent {
+    pub timestamp: NaiveDateTime,
+    pub delta: i64,
+    pub note: Option<String>,
+
+    /// Whether this is the event which records the initial value of the counter when it is
+    /// created, stored as `initial = true`
+    pub initial: bool,
+}
+
+impl CounterEvent {
+
+    /// An event which happens now
+    pub fn now(delta: i64, note: Option<String>) -> CounterEvent {
+        CounterEvent {
+            timestamp: Local::now().naive_local(),
+            delta: delta,
+            note: note,
+            initial: false,
+        }
+    }
+
+    /// The event which records the initial value of a counter which is created now
+    pub fn initial(value: i64) -> CounterEvent {
+        CounterEvent {
+            initial: true,
+            ..CounterEvent::now(value, None)
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut table = BTreeMap::new();
+        let ts = self.timestamp.format(TIMESTAMP_FORMAT).to_string();
+        table.insert(String::from("timestamp"), Value::String(ts));
+        table.insert(String::from("delta"), Value::Integer(self.delta));
+        if let Some(ref note) = self.note {
+            table.insert(String::from("note"), Value::String(note.clone()));
+        }
+        if self.initial {
+            table.insert(String::from("initial"), Value::Boolean(true));
+        }
+        Value::Table(table)
+    }
+
+    pub fn from_value(v: &Value) -> Result<CounterEvent> {
+        let table = match v {
+            &Value::Table(ref t) => t,
+            _ => return Err(CE::new(CEK::HeaderTypeError, None)),
+        };
+
+        let timestamp = match table.get("timestamp") {
+            Some(&Value::String(ref s)) => {
+                try!(NaiveDateTime::parse_from_str(&s[..], TIMESTAMP_FORMAT)
+                     .map_err(|e| CE::new(CEK::HeaderTypeError, Some(Box::new(e)))))
+            },
+            Some(_) => return Err(CE::new(CEK::HeaderTypeError, None)),
+            None    => return Err(CE::new(CEK::HeaderFieldMissingError, None)),
+        };
+
+        let delta = match table.get("delta") {
+            Some(&Value::Integer(i)) => i,
+            Some(_) => return Err(CE::new(CEK::HeaderTypeError, None)),
+            None    => return Err(CE::new(CEK::HeaderFieldMissingError, None)),
+        };
+
+        let note = match table.get("note") {
+            Some(&Value::String(ref s)) => Some(s.clone()),
+            Some(_) => return Err(CE::new(CEK::HeaderTypeError, None)),
+            None    => None,
+        };
+
+        let initial = match table.get("initial") {
+            Some(&Value::Boolean(b)) => b,
+            Some(_) => return Err(CE::new(CEK::HeaderTypeError, None)),
+            None    => false,
+        };
+
+        Ok(CounterEvent {
+            timestamp: timestamp,
+            delta: delta,
+            note: note,
+            initial: initial,
+        })
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use chrono::NaiveDateTime;
+    use toml::Value;
+
+    use error::CounterErrorKind as CEK;
+    use super::{CounterEvent, TIMESTAMP_FORMAT};
+
+    fn event(note: Option<&str>) -> CounterEvent {
+        CounterEvent {
+            timestamp: NaiveDateTime::parse_from_str("2016-05-01T12:30:00", TIMESTAMP_FORMAT)
+                .unwrap(),
+            delta: -3,
+            note: note.map(String::from),
+            initial: false,
+        }
+    }
+
+    fn table(fields: Vec<(&str, Value)>) -> Value {
+        Value::Table(fields.into_iter().map(|(k, v)| (String::from(k), v)).collect())
+    }
+
+    fn error(v: Value) -> CEK {
+        match CounterEvent::from_value(&v) {
+            Ok(ev) => panic!("Unexpected event: {:?}", ev),
+            Err(e) => e.err_type(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let initial = CounterEvent { initial: true, ..event(None) };
+        for ev in vec![event(None), event(Some("ran 3km less")), initial] {
+            assert_eq!(ev, CounterEvent::from_value(&ev.to_value()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_to_value() {
+        let v = event(Some("note")).to_value();
+        assert_eq!(table(vec![("timestamp", Value::String(String::from("2016-05-01T12:30:00"))),
+                              ("delta", Value::Integer(-3)),
+                              ("note", Value::String(String::from("note")))]),
+                   v);
+
+        match event(None).to_value() {
+            Value::Table(t) => {
+                assert!(!t.contains_key("note"));
+                assert!(!t.contains_key("initial"));
+            },
+            v => panic!("Unexpected value: {:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_from_value_errors() {
+        let ts = Value::String(String::from("2016-05-01T12:30:00"));
+
+        assert_eq!(CEK::HeaderTypeError, error(Value::Integer(1)));
+        assert_eq!(CEK::HeaderFieldMissingError, error(table(vec![("delta", Value::Integer(1))])));
+        assert_eq!(CEK::HeaderFieldMissingError, error(table(vec![("timestamp", ts.clone())])));
+        assert_eq!(CEK::HeaderTypeError,
+                   error(table(vec![("timestamp", Value::String(String::from("yesterday"))),
+                                    ("delta", Value::Integer(1))])));
+        assert_eq!(CEK::HeaderTypeError,
+                   error(table(vec![("timestamp", ts.clone()), ("delta", Value::Float(1.0))])));
+        assert_eq!(CEK::HeaderTypeError,
+                   error(table(vec![("timestamp", ts.clone()),
+                                    ("delta", Value::Integer(1)),
+                                    ("note", Value::Integer(1))])));
+        assert_eq!(CEK::HeaderTypeError,
+                   error(table(vec![("timestamp", ts.clone()),
+                                    ("delta", Value::Integer(1)),
+                                    ("initial", Value::String(String::from("yes")))])));
+        assert!(CounterEvent::from_value(&Value::Table(BTreeMap::new())).is_err());
+    }
+
+}
@@ -0,0 +1,100 @@
+use libimagstore::store::Store;
+
+use counter::{Counter, CounterIterator, CounterName};
+use result::Result;
+
+/// A group of counters
+///
+/// Counters with a slash in their name are in a group: "coffee/work" and "coffee/home" are in the
+/// group "coffee". Groups can be nested, "coffee/work" is in the group "coffee" as well as the
+/// counter "coffee/work/morning".
+pub struct CounterGroup<'a> {
+    store: &'a Store,
+    name: String,
+}
+
+impl<'a> CounterGroup<'a> {
+
+    pub fn new(store: &'a Store, name: String) -> CounterGroup<'a> {
+        CounterGroup {
+            store: store,
+            name: name,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name[..]
+    }
+
+    /// Create a counter in the group, `name` is the name of the counter in the group
+    pub fn create_counter(&self, name: CounterName, init: i64) -> Result<Counter<'a>> {
+        Counter::new(self.store, format!("{}/{}", self.name, name), init)
+    }
+
+    pub fn counters(&self) -> Result<CounterIterator<'a>> {
+        Counter::in_group(self.store, &self.name[..])
+    }
+
+    /// Get the names and values of the counters in the group
+    pub fn values(&self) -> Result<Vec<(CounterName, i64)>> {
+        let mut values = vec![];
+        for counter in try!(self.counters()) {
+            let counter = try!(counter);
+            values.push((try!(counter.name()), try!(counter.value())));
+        }
+        Ok(values)
+    }
+
+    /// Get the sum of the values of the counters in the group
+    pub fn sum(&self) -> Result<i64> {
+        self.values().map(|vs| vs.iter().fold(0, |acc, &(_, v)| acc + v))
+    }
+
+    /// Get the average of the values of the counters in the group, None if the group is empty
+    pub fn average(&self) -> Result<Option<f64>> {
+        self.values()
+            .map(|vs| {
+                if vs.is_empty() {
+                    None
+                } else {
+                    Some(vs.iter().fold(0, |acc, &(_, v)| acc + v) as f64 / vs.len() as f64)
+                }
+            })
+    }
+
+    pub fn inc_all(&self) -> Result<()> {
+        self.for_all(|c| c.inc())
+    }
+
+    pub fn dec_all(&self) -> Result<()> {
+        self.for_all(|c| c.dec())
+    }
+
+    pub fn reset_all(&self) -> Result<()> {
+        self.for_all(|c| c.reset())
+    }
+
+    /// Call `f` for all counters in the group, even if it fails for one of them. Returns the first
+    /// error.
+    fn for_all<F>(&self, f: F) -> Result<()>
+        where F: Fn(&mut Counter<'a>) -> Result<()>
+    {
+        let mut result = Ok(());
+        for counter in try!(self.counters()) {
+            let res = counter.and_then(|mut c| f(&mut c));
+            if let Err(e) = res {
+                debug!("Changing counter in group '{}' failed: {:?}", self.name, e);
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+}
+
+/// Get the name of the group the counter is in, "coffee" for "coffee/work"
+pub fn group_of(name: &str) -> Option<&str> {
+    name.rfind('/').map(|i| &name[..i])
+}
@@ -12,6 +12,7 @@
     while_true,
 )]
 
+extern crate chrono;
 extern crate toml;
 #[macro_use] extern crate log;
 #[macro_use] extern crate semver;
@@ -22,5 +23,8 @@ module_entry_path_mod!("counter", "0.1.0");
 
 pub mod counter;
 pub mod error;
+pub mod event;
+pub mod group;
+pub mod meta;
 pub mod result;
 
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDate;
+use toml::Value;
+
+use result::Result;
+use error::CounterError as CE;
+use error::CounterErrorKind as CEK;
+
+/// A period of time, for goals and statistics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+
+    pub fn from_name(s: &str) -> Option<Period> {
+        match s {
+            "day"   => Some(Period::Day),
+            "week"  => Some(Period::Week),
+            "month" => Some(Period::Month),
+            _       => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            &Period::Day   => "day",
+            &Period::Week  => "week",
+            &Period::Month => "month",
+        }
+    }
+
+    /// The first day of the period `date` is in. Weeks start on monday.
+    pub fn start_of(&self, date: &NaiveDate) -> NaiveDate {
+        match self {
+            &Period::Day   => *date,
+            &Period::Week  => *date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            &Period::Month => NaiveDate::from_ymd(date.year(), date.month(), 1),
+        }
+    }
+
+    /// A string which is the same for all days of the period `date` is in, for example "2016-W27"
+    /// for weeks
+    pub fn key(&self, date: &NaiveDate) -> String {
+        match self {
+            &Period::Day   => date.format("%Y-%m-%d").to_string(),
+            &Period::Week  => {
+                let (year, week, _) = date.isoweekdate();
+                format!("{}-W{:02}", year, week)
+            },
+            &Period::Month => date.format("%Y-%m").to_string(),
+        }
+    }
+
+}
+
+/// The value a counter should be increased by in each period, e.g. 8 per day
+#[derive(Debug, Clone, PartialEq)]
+pub struct Goal {
+    pub value: i64,
+    pub period: Period,
+}
+
+/// Optional settings of a counter, stored in the counter header
+#[derive(Debug, Clone, PartialEq)]
+pub struct CounterMeta {
+    /// What is counted, "cups" or "km"
+    pub unit: Option<String>,
+
+    /// The value of the counter cannot be changed to less than this
+    pub min: Option<i64>,
+
+    /// The value of the counter cannot be changed to more than this
+    pub max: Option<i64>,
+
+    /// The value a counter is increased or decreased by
+    pub step: i64,
+
+    pub goal: Option<Goal>,
+}
+
+impl Default for CounterMeta {
+
+    fn default() -> CounterMeta {
+        CounterMeta {
+            unit: None,
+            min: None,
+            max: None,
+            step: 1,
+            goal: None,
+        }
+    }
+
+}
+
+impl CounterMeta {
+
+    /// Check whether `v` is within the bounds
+    pub fn in_bounds(&self, v: i64) -> bool {
+        self.min.map(|min| v >= min).unwrap_or(true) && self.max.map(|max| v <= max).unwrap_or(true)
+    }
+
+    /// Read the settings from the "counter" table of the header
+    pub fn from_table(t: &BTreeMap<String, Value>) -> Result<CounterMeta> {
+        let unit = match t.get("unit") {
+            Some(&Value::String(ref s)) => Some(s.clone()),
+            Some(_) => return Err(CE::new(CEK::HeaderTypeError, None)),
+            None    => None,
+        };
+        let min  = try!(get_int(t, "min"));
+        let max  = try!(get_int(t, "max"));
+        let step = try!(get_int(t, "step")).unwrap_or(1);
+        if step < 1 {
+            debug!("The step size must be positive, but is {}", step);
+            return Err(CE::new(CEK::HeaderTypeError, None));
+        }
+
+        let goal = match try!(get_int(t, "goal")) {
+            Some(value) => {
+                let period = match t.get("goal-period") {
+                    Some(&Value::String(ref s)) => {
+                        try!(Period::from_name(s).ok_or(CE::new(CEK::HeaderTypeError, None)))
+                    },
+                    Some(_) => return Err(CE::new(CEK::HeaderTypeError, None)),
+                    None    => Period::Day,
+                };
+                Some(Goal { value: value, period: period })
+            },
+            None => None,
+        };
+
+        Ok(CounterMeta {
+            unit: unit,
+            min: min,
+            max: max,
+            step: step,
+            goal: goal,
+        })
+    }
+
+    /// Write the settings to the "counter" table of the header, removing the unset ones
+    pub fn to_table(&self, t: &mut BTreeMap<String, Value>) {
+        for key in &["unit", "min", "max", "step", "goal", "goal-period"] {
+            t.remove(*key);
+        }
+
+        if let Some(ref unit) = self.unit {
+            t.insert(String::from("unit"), Value::String(unit.clone()));
+        }
+        if let Some(min) = self.min {
+            t.insert(String::from("min"), Value::Integer(min));
+        }
+        if let Some(max) = self.max {
+            t.insert(String::from("max"), Value::Integer(max));
+        }
+        if self.step != 1 {
+            t.insert(String::from("step"), Value::Integer(self.step));
+        }
+        if let Some(ref goal) = self.goal {
+            t.insert(String::from("goal"), Value::Integer(goal.value));
+            t.insert(String::from("goal-period"), Value::String(String::from(goal.period.name())));
+        }
+    }
+
+}
+
+fn get_int(t: &BTreeMap<String, Value>, key: &str) -> Result<Option<i64>> {
+    match t.get(key) {
+        Some(&Value::Integer(i)) => Ok(Some(i)),
+        Some(_) => Err(CE::new(CEK::HeaderTypeError, None)),
+        None    => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use chrono::NaiveDate;
+    use toml::Value;
+
+    use super::{CounterMeta, Goal, Period};
+
+    #[test]
+    fn test_table_round_trip() {
+        let metas = vec![
+            CounterMeta::default(),
+            CounterMeta {
+                unit: Some(String::from("cups")),
+                min: Some(0),
+                max: Some(10),
+                step: 2,
+                goal: Some(Goal { value: 8, period: Period::Week }),
+            },
+            CounterMeta { min: Some(-5), ..CounterMeta::default() },
+        ];
+
+        for meta in metas {
+            let mut table = BTreeMap::new();
+            meta.to_table(&mut table);
+            assert_eq!(meta, CounterMeta::from_table(&table).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_to_table_removes_unset_fields() {
+        let mut table = BTreeMap::new();
+        table.insert(String::from("name"), Value::String(String::from("coffee")));
+        table.insert(String::from("unit"), Value::String(String::from("cups")));
+        table.insert(String::from("step"), Value::Integer(3));
+        table.insert(String::from("goal"), Value::Integer(8));
+        table.insert(String::from("goal-period"), Value::String(String::from("day")));
+
+        CounterMeta::default().to_table(&mut table);
+        assert_eq!(vec!["name"], table.keys().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_from_table_errors() {
+        let invalid = vec![
+            ("unit", Value::Integer(1)),
+            ("min", Value::String(String::from("0"))),
+            ("step", Value::Integer(0)),
+            ("step", Value::Integer(-1)),
+            ("goal-period", Value::String(String::from("year"))),
+        ];
+
+        for (key, value) in invalid {
+            let mut table = BTreeMap::new();
+            table.insert(String::from("goal"), Value::Integer(8));
+            table.insert(String::from(key), value);
+            assert!(CounterMeta::from_table(&table).is_err(), "{} should be invalid", key);
+        }
+    }
+
+    #[test]
+    fn test_in_bounds() {
+        let meta = CounterMeta { min: Some(0), max: Some(10), ..CounterMeta::default() };
+        assert!(meta.in_bounds(0));
+        assert!(meta.in_bounds(10));
+        assert!(!meta.in_bounds(-1));
+        assert!(!meta.in_bounds(11));
+
+        let meta = CounterMeta { max: Some(10), ..CounterMeta::default() };
+        assert!(meta.in_bounds(i64::min_value()));
+        assert!(!meta.in_bounds(11));
+        assert!(CounterMeta::default().in_bounds(i64::max_value()));
+    }
+
+    #[test]
+    fn test_period_start_of() {
+        // 2016-07-06 is a wednesday
+        let date = NaiveDate::from_ymd(2016, 7, 6);
+        assert_eq!(date, Period::Day.start_of(&date));
+        assert_eq!(NaiveDate::from_ymd(2016, 7, 4), Period::Week.start_of(&date));
+        assert_eq!(NaiveDate::from_ymd(2016, 7, 1), Period::Month.start_of(&date));
+
+        // Weeks start on monday, across the end of a month
+        let date = NaiveDate::from_ymd(2016, 8, 7);
+        assert_eq!(NaiveDate::from_ymd(2016, 8, 1), Period::Week.start_of(&date));
+        let date = NaiveDate::from_ymd(2016, 9, 2);
+        assert_eq!(NaiveDate::from_ymd(2016, 8, 29), Period::Week.start_of(&date));
+    }
+
+    #[test]
+    fn test_period_key() {
+        let date = NaiveDate::from_ymd(2016, 7, 6);
+        assert_eq!("2016-07-06", Period::Day.key(&date));
+        assert_eq!("2016-W27", Period::Week.key(&date));
+        assert_eq!("2016-07", Period::Month.key(&date));
+
+        // The first days of 2016 belong to the last week of 2015
+        assert_eq!("2015-W53", Period::Week.key(&NaiveDate::from_ymd(2016, 1, 3)));
+        assert_eq!("2016-W01", Period::Week.key(&NaiveDate::from_ymd(2016, 1, 4)));
+    }
+
+    #[test]
+    fn test_period_names() {
+        for period in vec![Period::Day, Period::Week, Period::Month] {
+            assert_eq!(Some(period), Period::from_name(period.name()));
+        }
+        assert_eq!(None, Period::from_name("year"));
+    }
+
+}
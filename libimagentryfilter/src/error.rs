@@ -0,0 +1,71 @@
+use std::error::Error;
+use std::fmt::Error as FmtError;
+use std::clone::Clone;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterErrorKind {
+    ExpressionSyntaxError,
+    UnknownPredicate,
+    InvalidRegex,
+}
+
+fn filter_error_type_as_str(e: &FilterErrorKind) -> &'static str {
+    match e {
+        &FilterErrorKind::ExpressionSyntaxError => "Syntax error in filter expression",
+        &FilterErrorKind::UnknownPredicate      => "Unknown predicate in filter expression",
+        &FilterErrorKind::InvalidRegex          => "Invalid regular expression in filter expression",
+    }
+}
+
+impl Display for FilterErrorKind {
+
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FmtError> {
+        try!(write!(fmt, "{}", filter_error_type_as_str(self)));
+        Ok(())
+    }
+
+}
+
+#[derive(Debug)]
+pub struct FilterError {
+    kind: FilterErrorKind,
+    cause: Option<Box<Error>>,
+}
+
+impl FilterError {
+
+    pub fn new(errtype: FilterErrorKind, cause: Option<Box<Error>>) -> FilterError {
+        FilterError {
+            kind: errtype,
+            cause: cause,
+        }
+    }
+
+    pub fn err_type(&self) -> FilterErrorKind {
+        self.kind
+    }
+
+}
+
+impl Display for FilterError {
+
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FmtError> {
+        try!(write!(fmt, "[{}]", filter_error_type_as_str(&self.kind.clone())));
+        Ok(())
+    }
+
+}
+
+impl Error for FilterError {
+
+    fn description(&self) -> &str {
+        filter_error_type_as_str(&self.kind.clone())
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        self.cause.as_ref().map(|e| &**e)
+    }
+
+}
+
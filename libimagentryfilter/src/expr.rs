@@ -0,0 +1,272 @@
+//! Filter expressions
+//!
+//! A filter expression is a string which is parsed into a filter, so filters can be passed on the
+//! commandline. An expression is built from these predicates:
+//!
+//! * `tag:NAME` - the entry has the tag `NAME`
+//! * `tag:NAME/*` - the entry has the tag `NAME` or a tag below it in the hierarchy
+//! * `content:REGEX` - the content of the entry matches the regular expression
+//! * `header:PATH=VALUE` - the header field at `PATH` is the string `VALUE`
+//!
+//! which can be combined with `not`, `and`, `or` and parentheses, for example:
+//!
+//! ```ignore
+//! tag:work/* and not (tag:done or content:^draft)
+//! ```
+//!
+//! `not` binds stronger than `and`, which binds stronger than `or`. Predicates cannot contain
+//! whitespace or parentheses.
+
+use toml::Value;
+
+use builtin::content::grep::ContentGrep;
+use builtin::header::field_eq::FieldEq;
+use error::FilterError as FE;
+use error::FilterErrorKind as FEK;
+use filter::Filter;
+use ops::and::And;
+use ops::not::Not;
+use ops::or::Or;
+use result::Result;
+use tags::HasTag;
+
+/// Parse a filter expression into a filter
+pub fn parse_filter_expression(s: &str) -> Result<Box<Filter>> {
+    let tokens = tokenize(s);
+    let mut parser = Parser { tokens: tokens, pos: 0 };
+
+    let filter = try!(parser.parse_or());
+    if parser.pos != parser.tokens.len() {
+        debug!("Unexpected token in filter expression: {:?}", parser.tokens.get(parser.pos));
+        return Err(FE::new(FEK::ExpressionSyntaxError, None));
+    }
+    Ok(filter)
+}
+
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+
+    for c in s.chars() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if !current.is_empty() {
+                tokens.push(current.clone());
+                current.clear();
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            current.push(c);
+        }
+    }
+
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|t| &t[..])
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn parse_or(&mut self) -> Result<Box<Filter>> {
+        let mut filter = try!(self.parse_and());
+        while self.peek() == Some("or") {
+            self.pos += 1;
+            let right = try!(self.parse_and());
+            filter = Box::new(Or::new(filter, right));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Box<Filter>> {
+        let mut filter = try!(self.parse_unary());
+        while self.peek() == Some("and") {
+            self.pos += 1;
+            let right = try!(self.parse_unary());
+            filter = Box::new(And::new(filter, right));
+        }
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> Result<Box<Filter>> {
+        match self.next() {
+            Some(ref t) if t == "not" => {
+                self.parse_unary().map(|f| Box::new(Not::new(f)) as Box<Filter>)
+            },
+            Some(ref t) if t == "(" => {
+                let filter = try!(self.parse_or());
+                match self.next() {
+                    Some(ref t) if t == ")" => Ok(filter),
+                    _ => Err(FE::new(FEK::ExpressionSyntaxError, None)),
+                }
+            },
+            Some(t) => parse_predicate(&t[..]),
+            None => Err(FE::new(FEK::ExpressionSyntaxError, None)),
+        }
+    }
+
+}
+
+fn parse_predicate(s: &str) -> Result<Box<Filter>> {
+    let (kind, arg) = match s.find(':') {
+        Some(i) => (&s[..i], &s[(i + 1)..]),
+        None    => {
+            debug!("Predicate without ':': '{}'", s);
+            return Err(FE::new(FEK::ExpressionSyntaxError, None));
+        },
+    };
+
+    if arg.is_empty() {
+        return Err(FE::new(FEK::ExpressionSyntaxError, None));
+    }
+
+    match kind {
+        "tag" => {
+            if arg.ends_with("/*") {
+                let tag = String::from(&arg[..(arg.len() - 2)]);
+                Ok(Box::new(HasTag::with_descendants(tag)))
+            } else {
+                Ok(Box::new(HasTag::new(String::from(arg))))
+            }
+        },
+
+        "content" => {
+            ContentGrep::new(arg)
+                .map(|f| Box::new(f) as Box<Filter>)
+                .map_err(|e| FE::new(FEK::InvalidRegex, Some(Box::new(e))))
+        },
+
+        "header" => {
+            match arg.find('=') {
+                Some(i) => {
+                    let path  = String::from(&arg[..i]);
+                    let value = Value::String(String::from(&arg[(i + 1)..]));
+                    Ok(Box::new(FieldEq::new(path, value)))
+                },
+                None => Err(FE::new(FEK::ExpressionSyntaxError, None)),
+            }
+        },
+
+        _ => {
+            debug!("Unknown predicate: '{}'", kind);
+            Err(FE::new(FEK::UnknownPredicate, None))
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use toml::Value;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+    use libimagentrytag::tagable::Tagable;
+
+    use error::FilterErrorKind as FEK;
+    use super::parse_filter_expression;
+
+    fn entry(tags: Vec<&str>, content: &str) -> Entry {
+        let mut e = Entry::new(StoreId::from(PathBuf::from("/test~0.1.0")));
+        e.set_tags(tags.into_iter().map(String::from).collect()).unwrap();
+        *e.get_content_mut() = String::from(content);
+        e
+    }
+
+    fn matches(expr: &str, e: &Entry) -> bool {
+        parse_filter_expression(expr).unwrap().filter(e)
+    }
+
+    fn error(expr: &str) -> FEK {
+        match parse_filter_expression(expr) {
+            Ok(_)  => panic!("Expression should not parse: '{}'", expr),
+            Err(e) => e.err_type(),
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let a = entry(vec!["a"], "");
+
+        // a or (b and c)
+        assert!(matches("tag:a or tag:b and tag:c", &a));
+        // (not a) and b
+        assert!(!matches("not tag:a and tag:b", &a));
+        // (not b) or (a and c)
+        assert!(matches("not tag:b or tag:a and tag:c", &a));
+        assert!(matches("not not tag:a", &a));
+    }
+
+    #[test]
+    fn test_parentheses() {
+        let a = entry(vec!["a"], "");
+        assert!(!matches("(tag:a or tag:b) and tag:c", &a));
+        assert!(matches("not (tag:b and tag:a)", &a));
+        assert!(matches("((tag:a))", &a));
+        assert!(matches("(tag:b)or(tag:a)", &a));
+    }
+
+    #[test]
+    fn test_tag() {
+        let e = entry(vec!["work/projectx"], "");
+        assert!(matches("tag:work/projectx", &e));
+        assert!(!matches("tag:work", &e));
+        assert!(matches("tag:work/*", &e));
+        assert!(!matches("tag:workshop/*", &e));
+    }
+
+    #[test]
+    fn test_content() {
+        let e = entry(vec![], "draft\nof a note");
+        assert!(matches("content:^draft", &e));
+        assert!(matches("content:note$", &e));
+        assert!(!matches("content:^note", &e));
+    }
+
+    #[test]
+    fn test_header() {
+        let mut e = entry(vec![], "");
+        let mut a = BTreeMap::new();
+        a.insert(String::from("b"), Value::String(String::from("c")));
+        e.get_header_mut().set("a", Value::Table(a)).unwrap();
+
+        assert!(matches("header:a.b=c", &e));
+        assert!(!matches("header:a.b=d", &e));
+        assert!(!matches("header:a.x=c", &e));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(FEK::ExpressionSyntaxError, error(""));
+        assert_eq!(FEK::ExpressionSyntaxError, error("(tag:a"));
+        assert_eq!(FEK::ExpressionSyntaxError, error("tag:a)"));
+        assert_eq!(FEK::ExpressionSyntaxError, error("(tag:a or tag:b"));
+        assert_eq!(FEK::ExpressionSyntaxError, error("tag"));
+        assert_eq!(FEK::ExpressionSyntaxError, error("tag:"));
+        assert_eq!(FEK::ExpressionSyntaxError, error("header:a.b"));
+        assert_eq!(FEK::ExpressionSyntaxError, error("tag:a and"));
+        assert_eq!(FEK::ExpressionSyntaxError, error("tag:a tag:b"));
+        assert_eq!(FEK::UnknownPredicate, error("foo:bar"));
+        assert_eq!(FEK::InvalidRegex, error("content:[a"));
+    }
+
+}
@@ -28,11 +28,14 @@ extern crate libimagentrytag;
 
 pub mod cli;
 pub mod builtin;
+pub mod error;
 pub mod filter;
 pub mod ops;
+pub mod result;
 
 // extended functionality of the crate
 // these depend on other internal libraries than libimagstore and use the upper core modules for
 // their functionality
 
+pub mod expr;
 pub mod tags;
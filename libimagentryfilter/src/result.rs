@@ -0,0 +1,5 @@
+use std::result::Result as RResult;
+
+use error::FilterError;
+
+pub type Result<T> = RResult<T, FilterError>;
@@ -1,12 +1,14 @@
 use libimagstore::store::Entry;
 use libimagentrytag::tagable::Tagable;
 use libimagentrytag::tag::Tag;
+use libimagentrytag::util::is_descendant_of;
 
 use filter::Filter;
 
 /// Check whether an Entry has a certain tag
 pub struct HasTag {
     tag: Tag,
+    descendants: bool,
 }
 
 impl HasTag {
@@ -14,6 +16,16 @@ impl HasTag {
     pub fn new(tag: Tag) -> HasTag {
         HasTag {
             tag: tag,
+            descendants: false,
+        }
+    }
+
+    /// Also match entries which have a tag below `tag` in the hierarchy, so "work" matches
+    /// entries tagged with "work/projectx"
+    pub fn with_descendants(tag: Tag) -> HasTag {
+        HasTag {
+            tag: tag,
+            descendants: true,
         }
     }
 
@@ -22,7 +34,7 @@ impl HasTag {
 impl Filter for HasTag {
 
     fn filter(&self, e: &Entry) -> bool {
-        e.has_tag(&self.tag).ok().unwrap_or(false)
+        entry_has_tag(e, &self.tag, self.descendants)
     }
 
 }
@@ -31,6 +43,7 @@ impl Filter for HasTag {
 /// Check whether an Entry has all of these tags
 pub struct HasAllTags {
     tags: Vec<Tag>,
+    descendants: bool,
 }
 
 impl HasAllTags {
@@ -38,6 +51,16 @@ impl HasAllTags {
     pub fn new(tags: Vec<Tag>) -> HasAllTags {
         HasAllTags {
             tags: tags,
+            descendants: false,
+        }
+    }
+
+    /// Like `HasAllTags::new()`, but each tag is also matched by the tags below it in the
+    /// hierarchy
+    pub fn with_descendants(tags: Vec<Tag>) -> HasAllTags {
+        HasAllTags {
+            tags: tags,
+            descendants: true,
         }
     }
 
@@ -46,7 +69,11 @@ impl HasAllTags {
 impl Filter for HasAllTags {
 
     fn filter(&self, e: &Entry) -> bool {
-        e.has_tags(&self.tags).ok().unwrap_or(false)
+        if self.descendants {
+            self.tags.iter().all(|tag| entry_has_tag(e, tag, true))
+        } else {
+            e.has_tags(&self.tags).ok().unwrap_or(false)
+        }
     }
 
 }
@@ -55,6 +82,7 @@ impl Filter for HasAllTags {
 /// Check whether an Entry has any of these tags
 pub struct HasAnyTags {
     tags: Vec<Tag>,
+    descendants: bool,
 }
 
 impl HasAnyTags {
@@ -62,6 +90,16 @@ impl HasAnyTags {
     pub fn new(tags: Vec<Tag>) -> HasAnyTags {
         HasAnyTags {
             tags: tags,
+            descendants: false,
+        }
+    }
+
+    /// Like `HasAnyTags::new()`, but each tag is also matched by the tags below it in the
+    /// hierarchy
+    pub fn with_descendants(tags: Vec<Tag>) -> HasAnyTags {
+        HasAnyTags {
+            tags: tags,
+            descendants: true,
         }
     }
 
@@ -70,8 +108,105 @@ impl HasAnyTags {
 impl Filter for HasAnyTags {
 
     fn filter(&self, e: &Entry) -> bool {
-        self.tags.iter().any(|tag| e.has_tag(tag).ok().unwrap_or(false))
+        self.tags.iter().any(|tag| entry_has_tag(e, tag, self.descendants))
+    }
+
+}
+
+
+/// Check whether an Entry has a tag which is in the hierarchy below a certain tag, without the tag
+/// itself: "work" matches entries tagged with "work/projectx", but not entries only tagged with
+/// "work"
+pub struct HasTagBelow {
+    tag: Tag,
+}
+
+impl HasTagBelow {
+
+    pub fn new(tag: Tag) -> HasTagBelow {
+        HasTagBelow {
+            tag: tag,
+        }
+    }
+
+}
+
+impl Filter for HasTagBelow {
+
+    fn filter(&self, e: &Entry) -> bool {
+        e.get_tags()
+            .map(|tags| tags.iter().any(|t| *t != self.tag && is_descendant_of(t, &self.tag)))
+            .unwrap_or(false)
     }
 
 }
 
+
+fn entry_has_tag(e: &Entry, tag: &Tag, descendants: bool) -> bool {
+    if descendants {
+        e.get_tags()
+            .map(|tags| tags.iter().any(|t| is_descendant_of(t, tag)))
+            .unwrap_or(false)
+    } else {
+        e.has_tag(tag).ok().unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+    use libimagentrytag::tagable::Tagable;
+
+    use filter::Filter;
+    use super::{HasAllTags, HasAnyTags, HasTag, HasTagBelow};
+
+    fn entry(tags: Vec<&str>) -> Entry {
+        let mut e = Entry::new(StoreId::from(PathBuf::from("/test~0.1.0")));
+        e.set_tags(tags.into_iter().map(String::from).collect()).unwrap();
+        e
+    }
+
+    fn tags(tags: Vec<&str>) -> Vec<String> {
+        tags.into_iter().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_has_tag() {
+        let e = entry(vec!["work/projectx", "home"]);
+        assert!(HasTag::new(String::from("home")).filter(&e));
+        assert!(!HasTag::new(String::from("work")).filter(&e));
+        assert!(HasTag::with_descendants(String::from("work")).filter(&e));
+        assert!(HasTag::with_descendants(String::from("work/projectx")).filter(&e));
+
+        let e = entry(vec!["workshop"]);
+        assert!(!HasTag::with_descendants(String::from("work")).filter(&e));
+    }
+
+    #[test]
+    fn test_has_all_tags() {
+        let e = entry(vec!["work/projectx", "home"]);
+        assert!(HasAllTags::new(tags(vec!["work/projectx", "home"])).filter(&e));
+        assert!(!HasAllTags::new(tags(vec!["work", "home"])).filter(&e));
+        assert!(HasAllTags::with_descendants(tags(vec!["work", "home"])).filter(&e));
+        assert!(!HasAllTags::with_descendants(tags(vec!["work", "garden"])).filter(&e));
+    }
+
+    #[test]
+    fn test_has_any_tags() {
+        let e = entry(vec!["work/projectx"]);
+        assert!(!HasAnyTags::new(tags(vec!["work", "home"])).filter(&e));
+        assert!(HasAnyTags::with_descendants(tags(vec!["work", "home"])).filter(&e));
+        assert!(!HasAnyTags::with_descendants(tags(vec!["home", "garden"])).filter(&e));
+    }
+
+    #[test]
+    fn test_has_tag_below() {
+        assert!(HasTagBelow::new(String::from("work")).filter(&entry(vec!["work/projectx"])));
+        assert!(!HasTagBelow::new(String::from("work")).filter(&entry(vec!["work"])));
+        assert!(!HasTagBelow::new(String::from("work")).filter(&entry(vec!["workshop"])));
+    }
+
+}
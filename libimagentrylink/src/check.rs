@@ -0,0 +1,462 @@
+//! Checking external links
+//!
+//! Each URL is requested and the result of the request is recorded in the header of the link
+//! entry:
+//!
+//! ```ignore
+//! [link]
+//! status = 200
+//! final-url = "https://imag-pim.org/"
+//! title = "imag"
+//! checked-at = "2016-07-20T12:00:00"
+//! ```
+//!
+//! If the request fails, `link.error` holds the reason instead of the status.
+//!
+//! The HTTP client is configured in the `[link.check]` section of the configuration:
+//!
+//! ```ignore
+//! [link.check]
+//! endpoint = "http://localhost:3128" # send all requests through this HTTP proxy
+//! timeout = 10                       # read and write timeout in seconds
+//! max-redirects = 10
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::time::Duration;
+
+use chrono::Local;
+use hyper::Client;
+use hyper::client::RedirectPolicy;
+use hyper::header::Location;
+use toml::Value;
+use url::Url;
+
+use libimagstore::store::Entry;
+use libimagstore::store::EntryHeader;
+
+use error::LinkError as LE;
+use error::LinkErrorKind as LEK;
+use result::Result;
+
+/// Only this many bytes of the body are read to find the title
+const MAX_BODY_SIZE: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckConfig {
+    /// Host and port of the HTTP proxy all requests are sent through
+    pub endpoint: Option<(String, u16)>,
+
+    /// Read and write timeout of the requests
+    pub timeout: Option<Duration>,
+
+    /// Number of redirects which are followed
+    pub max_redirects: usize,
+}
+
+impl Default for CheckConfig {
+
+    fn default() -> CheckConfig {
+        CheckConfig {
+            endpoint: None,
+            timeout: Some(Duration::from_secs(10)),
+            max_redirects: 10,
+        }
+    }
+
+}
+
+impl CheckConfig {
+
+    /// Read the configuration from the `[link.check]` section of the imag configuration, if there
+    /// is one
+    pub fn from_config(config: Option<&Value>) -> Result<CheckConfig> {
+        let mut cfg = CheckConfig::default();
+
+        let section = match config {
+            Some(&Value::Table(ref t)) => {
+                match t.get("link") {
+                    Some(&Value::Table(ref l)) => l.get("check"),
+                    _ => None,
+                }
+            },
+            _ => None,
+        };
+
+        let section = match section {
+            Some(&Value::Table(ref t)) => t,
+            Some(_) => return Err(LE::new(LEK::InvalidCheckConfig, None)),
+            None    => return Ok(cfg),
+        };
+
+        match section.get("endpoint") {
+            Some(&Value::String(ref s)) => cfg.endpoint = Some(try!(parse_endpoint(s))),
+            Some(_) => return Err(LE::new(LEK::InvalidCheckConfig, None)),
+            None    => { },
+        }
+
+        match section.get("timeout") {
+            Some(&Value::Integer(0)) => cfg.timeout = None,
+            Some(&Value::Integer(i)) if i > 0 => cfg.timeout = Some(Duration::from_secs(i as u64)),
+            Some(_) => return Err(LE::new(LEK::InvalidCheckConfig, None)),
+            None    => { },
+        }
+
+        match section.get("max-redirects") {
+            Some(&Value::Integer(i)) if i >= 0 => cfg.max_redirects = i as usize,
+            Some(_) => return Err(LE::new(LEK::InvalidCheckConfig, None)),
+            None    => { },
+        }
+
+        Ok(cfg)
+    }
+
+}
+
+/// Parse an endpoint of the form `http://host:port` into host and port
+pub fn parse_endpoint(s: &str) -> Result<(String, u16)> {
+    let url = try!(Url::parse(s).map_err(|e| LE::new(LEK::InvalidCheckConfig, Some(Box::new(e)))));
+
+    match (url.host_str(), url.port_or_known_default()) {
+        (Some(host), Some(port)) => Ok((String::from(host), port)),
+        _ => Err(LE::new(LEK::InvalidCheckConfig, None)),
+    }
+}
+
+/// The result of checking one URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub url: Url,
+
+    /// Status code of the last response, if there was one
+    pub status: Option<u16>,
+
+    /// The URL after following all redirects
+    pub final_url: Url,
+
+    pub title: Option<String>,
+    pub checked_at: String,
+
+    /// Why the request failed, if it failed
+    pub error: Option<String>,
+}
+
+impl CheckResult {
+
+    /// Whether the URL could be fetched without an error
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.status.map(|s| s >= 200 && s < 300).unwrap_or(false)
+    }
+
+    /// Write the result into the `link` section of the header
+    pub fn write_to(&self, header: &mut EntryHeader) -> Result<()> {
+        let mut table = match header.read("link") {
+            Ok(Some(Value::Table(t))) => t,
+            Ok(Some(_)) => {
+                warn!("There is a value at 'link' which is not a table.");
+                warn!("Going to override this value");
+                BTreeMap::new()
+            },
+            Ok(None) => BTreeMap::new(),
+            Err(e)   => return Err(LE::new(LEK::EntryHeaderReadError, Some(Box::new(e)))),
+        };
+
+        for key in &["status", "final-url", "title", "error"] {
+            table.remove(*key);
+        }
+
+        if let Some(status) = self.status {
+            table.insert(String::from("status"), Value::Integer(status as i64));
+        }
+        table.insert(String::from("final-url"), Value::String(String::from(self.final_url.as_str())));
+        if let Some(ref title) = self.title {
+            table.insert(String::from("title"), Value::String(title.clone()));
+        }
+        table.insert(String::from("checked-at"), Value::String(self.checked_at.clone()));
+        if let Some(ref error) = self.error {
+            table.insert(String::from("error"), Value::String(error.clone()));
+        }
+
+        header.set("link", Value::Table(table))
+            .map(|_| ())
+            .map_err(|e| LE::new(LEK::EntryHeaderWriteError, Some(Box::new(e))))
+    }
+
+}
+
+pub struct LinkChecker {
+    client: Client,
+    max_redirects: usize,
+}
+
+impl LinkChecker {
+
+    pub fn new(cfg: &CheckConfig) -> LinkChecker {
+        let mut client = match cfg.endpoint {
+            Some((ref host, port)) => Client::with_http_proxy(host.clone(), port),
+            None => Client::new(),
+        };
+
+        // We follow the redirects ourselves, to know where we end up
+        client.set_redirect_policy(RedirectPolicy::FollowNone);
+        client.set_read_timeout(cfg.timeout);
+        client.set_write_timeout(cfg.timeout);
+
+        LinkChecker {
+            client: client,
+            max_redirects: cfg.max_redirects,
+        }
+    }
+
+    /// Request the URL, following redirects
+    ///
+    /// Failing requests are not an error, but recorded in the result.
+    pub fn check(&self, url: &Url) -> CheckResult {
+        let mut result = CheckResult {
+            url: url.clone(),
+            status: None,
+            final_url: url.clone(),
+            title: None,
+            checked_at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+            error: None,
+        };
+
+        for _ in 0..(self.max_redirects + 1) {
+            debug!("Requesting {}", result.final_url);
+            let mut response = match self.client.get(result.final_url.as_str()).send() {
+                Ok(r)  => r,
+                Err(e) => {
+                    debug!("Request failed: {:?}", e);
+                    result.error = Some(format!("{}", e));
+                    return result;
+                },
+            };
+
+            result.status = Some(response.status.to_u16());
+
+            if response.status.is_redirection() {
+                let location = response.headers.get::<Location>().map(|l| l.0.clone());
+                match location.map(|l| result.final_url.join(&l[..])) {
+                    Some(Ok(next)) => {
+                        debug!("Redirected to {}", next);
+                        result.final_url = next;
+                        continue;
+                    },
+                    Some(Err(e)) => {
+                        result.error = Some(format!("Invalid redirect: {}", e));
+                        return result;
+                    },
+                    None => return result, // a redirect without target, nothing more to do
+                }
+            }
+
+            if response.status.is_success() {
+                let mut body = vec![];
+                match response.by_ref().take(MAX_BODY_SIZE).read_to_end(&mut body) {
+                    Ok(_)  => result.title = find_title(&String::from_utf8_lossy(&body[..])),
+                    Err(e) => debug!("Could not read body: {:?}", e),
+                }
+            }
+
+            return result;
+        }
+
+        result.error = Some(format!("More than {} redirects", self.max_redirects));
+        result
+    }
+
+}
+
+/// Get the URL of a link entry, or None if the entry is not a link entry
+pub fn get_link_url(entry: &Entry) -> Result<Option<Url>> {
+    match entry.get_header().read("imag.content.url") {
+        Ok(Some(Value::String(s))) => {
+            Url::parse(&s[..])
+                .map(Some)
+                .map_err(|e| LE::new(LEK::InvalidUri, Some(Box::new(e))))
+        },
+        Ok(Some(_)) => Err(LE::new(LEK::EntryHeaderReadError, None)),
+        Ok(None)    => Ok(None),
+        Err(e)      => Err(LE::new(LEK::EntryHeaderReadError, Some(Box::new(e)))),
+    }
+}
+
+/// Check the URL of a link entry and record the result in its header
+///
+/// Returns None if the entry is not a link entry.
+pub fn check_link_entry(checker: &LinkChecker, entry: &mut Entry) -> Result<Option<CheckResult>> {
+    let url = match try!(get_link_url(entry)) {
+        Some(url) => url,
+        None      => return Ok(None),
+    };
+
+    let result = checker.check(&url);
+    result.write_to(entry.get_header_mut()).map(|_| Some(result))
+}
+
+/// Find the content of the `<title>` element in a HTML document
+fn find_title(html: &str) -> Option<String> {
+    find_ignore_case(html, "<title", 0)
+        .and_then(|start| html[start..].find('>').map(|i| start + i + 1))
+        .and_then(|start| {
+            find_ignore_case(html, "</title", start).map(|end| &html[start..end])
+        })
+        .map(|title| {
+            let title = title.split_whitespace().collect::<Vec<&str>>().join(" ");
+            decode_entities(&title[..])
+        })
+        .and_then(|title| if title.is_empty() { None } else { Some(title) })
+}
+
+fn find_ignore_case(haystack: &str, needle: &str, from: usize) -> Option<usize> {
+    let lower = |b: &u8| if *b >= b'A' && *b <= b'Z' { *b + 32 } else { *b };
+    let needle : Vec<u8> = needle.as_bytes().iter().map(&lower).collect();
+    let haystack = haystack.as_bytes();
+
+    if haystack.len() < needle.len() {
+        return None;
+    }
+
+    (from..(haystack.len() - needle.len() + 1))
+        .find(|&i| haystack[i..(i + needle.len())].iter().map(&lower).eq(needle.iter().cloned()))
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::thread;
+
+    use toml::Value;
+    use url::Url;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+
+    use super::{CheckConfig, LinkChecker, check_link_entry, find_title};
+
+    /// Start a HTTP server on a free local port, which answers
+    ///
+    /// * `/page` with a page with a title,
+    /// * `/moved` with a redirect to `/page`,
+    /// * everything else with 404.
+    ///
+    /// Requests in the absolute form, as sent to a proxy, are answered the same way.
+    fn mock_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(s)  => s,
+                    Err(_) => continue,
+                };
+
+                let target = {
+                    let mut reader = BufReader::new(&stream);
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).map(|n| n > 2).unwrap_or(false) {
+                        line.clear();
+                    }
+
+                    request_line.split_whitespace().nth(1).map(String::from).unwrap_or(String::new())
+                };
+
+                let path = Url::parse(&target[..])
+                    .map(|u| String::from(u.path()))
+                    .unwrap_or(target);
+
+                let response = match &path[..] {
+                    "/page"  => {
+                        let body = "<html><head><TITLE>\n  Fish &amp; Chips </title></head></html>";
+                        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                                body.len(), body)
+                    },
+                    "/moved" => String::from("HTTP/1.1 301 Moved Permanently\r\nLocation: /page\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+                    _        => String::from("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+                };
+
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        port
+    }
+
+    #[test]
+    fn test_find_title() {
+        assert_eq!(Some(String::from("a <b>")), find_title("<title lang=\"en\">a &lt;b&gt;</TITLE>"));
+        assert_eq!(None, find_title("<html><title></title></html>"));
+        assert_eq!(None, find_title("no html at all"));
+    }
+
+    #[test]
+    fn test_check_follows_redirects() {
+        let port    = mock_server();
+        let checker = LinkChecker::new(&CheckConfig::default());
+        let url     = Url::parse(&format!("http://127.0.0.1:{}/moved", port)[..]).unwrap();
+
+        let result = checker.check(&url);
+        assert!(result.is_ok());
+        assert_eq!(Some(200), result.status);
+        assert_eq!(format!("http://127.0.0.1:{}/page", port), result.final_url.as_str());
+        assert_eq!(Some(String::from("Fish & Chips")), result.title);
+    }
+
+    #[test]
+    fn test_check_through_endpoint() {
+        let port = mock_server();
+        let mut cfg = CheckConfig::default();
+        cfg.endpoint = Some((String::from("127.0.0.1"), port));
+        let checker = LinkChecker::new(&cfg);
+
+        let mut entry = Entry::new(StoreId::from(PathBuf::from("/links/external/abc~0.1.0")));
+        entry.get_header_mut()
+            .set("imag.content", Value::Table(BTreeMap::new()))
+            .unwrap();
+        entry.get_header_mut()
+            .set("imag.content.url", Value::String(String::from("http://example.com/gone")))
+            .unwrap();
+
+        let result = check_link_entry(&checker, &mut entry).unwrap().unwrap();
+        assert!(!result.is_ok());
+        assert_eq!(Some(404), result.status);
+
+        let hdr = entry.get_header();
+        assert_eq!(Some(Value::Integer(404)), hdr.read("link.status").unwrap());
+        assert_eq!(Some(Value::String(String::from("http://example.com/gone"))),
+                   hdr.read("link.final-url").unwrap());
+        assert!(hdr.read("link.checked-at").unwrap().is_some());
+        assert_eq!(None, hdr.read("link.title").unwrap());
+    }
+
+    #[test]
+    fn test_check_unreachable() {
+        let mut cfg = CheckConfig::default();
+        cfg.max_redirects = 0;
+        let checker = LinkChecker::new(&cfg);
+
+        // Get a free port, which is closed again when the listener is dropped
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let result = checker.check(&Url::parse(&format!("http://{}/", addr)).unwrap());
+        assert!(!result.is_ok());
+        assert_eq!(None, result.status);
+        assert!(result.error.is_some());
+    }
+
+}
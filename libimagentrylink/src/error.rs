@@ -10,6 +10,7 @@ pub enum LinkErrorKind {
     LinkTargetDoesNotExist,
     InternalConversionError,
     InvalidUri,
+    InvalidCheckConfig,
     StoreReadError,
     StoreWriteError,
 }
@@ -34,6 +35,9 @@ fn link_error_type_as_str(e: &LinkErrorKind) -> &'static str {
         &LinkErrorKind::InvalidUri
             => "URI is not valid",
 
+        &LinkErrorKind::InvalidCheckConfig
+            => "Configuration for checking links is not valid",
+
         &LinkErrorKind::StoreReadError
             => "Store read error",
 
@@ -13,13 +13,16 @@
 
 use std::ops::DerefMut;
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 use libimagstore::store::Entry;
 use libimagstore::store::FileLockEntry;
 use libimagstore::store::Store;
+use libimagstore::store::StoreObject;
 use libimagstore::storeid::StoreId;
 use libimagstore::storeid::IntoStoreId;
 
+use check::get_link_url;
 use error::LinkError as LE;
 use error::LinkErrorKind as LEK;
 use result::Result;
@@ -60,7 +63,7 @@ impl<'a> Link<'a> {
     /// Get a link Url object from a FileLockEntry, ignore errors.
     fn get_link_uri_from_filelockentry(file: &FileLockEntry<'a>) -> Option<Url> {
         file.get_header()
-            .read("imag.content.uri")
+            .read("imag.content.url")
             .ok()
             .and_then(|opt| {
                 match opt {
@@ -73,7 +76,7 @@ impl<'a> Link<'a> {
     pub fn get_url(&self) -> Result<Option<Url>> {
         let opt = self.link
             .get_header()
-            .read("imag.content.uri");
+            .read("imag.content.url");
 
         match opt {
             Ok(Some(Value::String(s))) => {
@@ -104,15 +107,141 @@ pub trait ExternalLinker : InternalLinker {
 
 }
 
-/// Check whether the StoreId starts with `/link/external/`
+/// Check whether the StoreId points to an entry in `links/external/`
 pub fn is_external_link_storeid(id: &StoreId) -> bool {
-    debug!("Checking whether this is a /link/external/*: '{:?}'", id);
-    id.starts_with("/link/external/")
+    debug!("Checking whether this is a links/external/*: '{:?}'", id);
+    id.parent().map(|p| p.ends_with("links/external")).unwrap_or(false)
 }
 
-fn get_external_link_from_file(entry: &FileLockEntry) -> Result<Url> {
-    Link::get_link_uri_from_filelockentry(entry) // TODO: Do not hide error by using this function
-        .ok_or(LE::new(LEK::StoreReadError, None))
+/// An entry which holds an external link, with the entries which link to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalLinkEntry {
+    pub id: StoreId,
+    pub url: Option<Url>,
+    pub referenced_by: Vec<StoreId>,
+}
+
+/// Get the directory in the store where the external link entries live
+fn external_link_dir(store: &Store) -> PathBuf {
+    store.path().join("links").join("external")
+}
+
+/// Get all external link entries in the store, each with the entries which link to it
+///
+/// The referencing entries are found by scanning the links of all other entries, so backlinks in
+/// the link entries which are not valid anymore are not counted.
+pub fn all_external_links(store: &Store) -> Result<Vec<ExternalLinkEntry>> {
+    let dir = external_link_dir(store);
+    let mut links : BTreeMap<StoreId, ExternalLinkEntry> = BTreeMap::new();
+
+    if !dir.is_dir() {
+        debug!("No external links in the store");
+        return Ok(vec![]);
+    }
+
+    for obj in store.walk("links/external") {
+        if let StoreObject::Id(id) = obj {
+            let entry = try!(store.retrieve_copy(id.clone())
+                .map_err(|e| LE::new(LEK::StoreReadError, Some(Box::new(e)))));
+
+            links.insert(id.clone(), ExternalLinkEntry {
+                id: id,
+                url: try!(get_link_url(&entry)),
+                referenced_by: vec![],
+            });
+        }
+    }
+
+    for obj in store.walk("") {
+        let id = match obj {
+            StoreObject::Id(ref id) if !id.starts_with(&dir) => id.clone(),
+            _ => continue,
+        };
+
+        let entry = try!(store.retrieve_copy(id.clone())
+            .map_err(|e| LE::new(LEK::StoreReadError, Some(Box::new(e)))));
+
+        for link in try!(entry.get_internal_links()) {
+            if let Some(l) = links.get_mut(&link) {
+                l.referenced_by.push(id.clone());
+            }
+        }
+    }
+
+    Ok(links.into_iter().map(|(_, l)| l).collect())
+}
+
+/// Delete all external link entries which are not linked from any other entry
+///
+/// Returns the ids of the deleted entries. If `dry_run` is set, nothing is deleted.
+pub fn gc_external_links(store: &Store, dry_run: bool) -> Result<Vec<StoreId>> {
+    let mut deleted = vec![];
+    for link in try!(all_external_links(store)) {
+        if !link.referenced_by.is_empty() {
+            continue;
+        }
+
+        if dry_run {
+            debug!("Would delete orphaned link entry: {:?}", link.id);
+        } else {
+            debug!("Deleting orphaned link entry: {:?}", link.id);
+            try!(store.delete(link.id.clone())
+                .map_err(|e| LE::new(LEK::StoreWriteError, Some(Box::new(e)))));
+        }
+        deleted.push(link.id);
+    }
+    Ok(deleted)
+}
+
+/// Get the id of the entry which holds the external link `url`
+fn link_entry_id(url: &Url) -> StoreId {
+    let hash = {
+        let mut s = Sha1::new();
+        s.input_str(url.as_str());
+        s.result_str()
+    };
+    ModuleEntryPath::new(format!("external/{}", hash)).into_storeid()
+}
+
+/// Retrieve the entry for the external link `url`, which implicitely creates the entry if it does
+/// not exist, and write the URL into its header
+fn retrieve_link_entry<'a>(store: &'a Store, url: &Url) -> Result<FileLockEntry<'a>> {
+    let file_id = link_entry_id(url);
+    debug!("Link    = '{:?}'", url);
+    debug!("StoreId = '{:?}'", file_id);
+
+    let mut file = try!(store.retrieve(file_id)
+        .map_err(|e| {
+            debug!("Failed to create or retrieve an file for this link '{:?}'", url);
+            LE::new(LEK::StoreWriteError, Some(Box::new(e)))
+        }));
+
+    debug!("Generating header content!");
+    {
+        let mut hdr = file.deref_mut().get_header_mut();
+
+        let mut table = match hdr.read("imag.content") {
+            Ok(Some(Value::Table(table))) => table,
+            Ok(Some(_)) => {
+                warn!("There is a value at 'imag.content' which is not a table.");
+                warn!("Going to override this value");
+                BTreeMap::new()
+            },
+            Ok(None) => BTreeMap::new(),
+            Err(e)   => return Err(LE::new(LEK::StoreWriteError, Some(Box::new(e)))),
+        };
+
+        let v = Value::String(String::from(url.as_str()));
+
+        debug!("setting URL = '{:?}", v);
+        table.insert(String::from("url"), v);
+
+        try!(hdr.set("imag.content", Value::Table(table))
+            .map_err(|e| LE::new(LEK::StoreWriteError, Some(Box::new(e)))));
+        debug!("Setting URL worked");
+    }
+
+    Ok(file)
 }
 
 /// Implement ExternalLinker for Entry, hiding the fact that there is no such thing as an external
@@ -122,92 +251,70 @@ impl ExternalLinker for Entry {
 
     /// Get the external links from the implementor object
     fn get_external_links(&self, store: &Store) -> Result<Vec<Url>> {
-        // Iterate through all internal links and filter for FileLockEntries which live in
-        // /link/external/<SHA> -> load these files and get the external link from their headers,
+        // Iterate through all internal links and filter for the entries which live in
+        // links/external/<SHA> -> load these files and get the external link from their headers,
         // put them into the return vector.
-        self.get_internal_links()
-            .map(|vect| {
-                debug!("Getting external links");
-                vect.into_iter()
-                    .filter(is_external_link_storeid)
-                    .map(|id| {
-                        debug!("Retrieving entry for id: '{:?}'", id);
-                        match store.retrieve(id.clone()) {
-                            Ok(f) => get_external_link_from_file(&f),
-                            Err(e) => {
-                                debug!("Retrieving entry for id: '{:?}' failed", id);
-                                Err(LE::new(LEK::StoreReadError, Some(Box::new(e))))
-                            }
-                        }
-                    })
-                    .filter_map(|x| x.ok()) // TODO: Do not ignore error here
-                    .collect()
-            })
-            .map_err(|e| LE::new(LEK::StoreReadError, Some(Box::new(e))))
+        debug!("Getting external links");
+        let mut urls = vec![];
+        for id in try!(self.get_internal_links()).into_iter().filter(is_external_link_storeid) {
+            debug!("Retrieving entry for id: '{:?}'", id);
+            let entry = try!(store.retrieve_copy(id.clone())
+                .map_err(|e| {
+                    debug!("Retrieving entry for id: '{:?}' failed", id);
+                    LE::new(LEK::StoreReadError, Some(Box::new(e)))
+                }));
+
+            match try!(get_link_url(&entry)) {
+                Some(url) => urls.push(url),
+                None      => warn!("Link entry without URL: {:?}", id),
+            }
+        }
+        Ok(urls)
     }
 
     /// Set the external links for the implementor object
+    ///
+    /// The internal links to the entries of URLs which are not in `links` anymore are removed.
+    /// These entries are kept, even if no other entry links to them.
     fn set_external_links(&mut self, store: &Store, links: Vec<Url>) -> Result<()> {
-        // Take all the links, generate a SHA sum out of each one, filter out the already existing
-        // store entries and store the other URIs in the header of one FileLockEntry each, in
-        // the path /link/external/<SHA of the URL>
+        // Take all the links, generate a SHA sum out of each one and store the URIs in the header
+        // of one FileLockEntry each, in the path links/external/<SHA of the URL>. Then remove the
+        // links to the entries of the URLs which are not in the list anymore.
+        let old_ids : Vec<StoreId> = try!(self.get_internal_links())
+            .into_iter()
+            .filter(is_external_link_storeid)
+            .collect();
 
         debug!("Iterating {} links = {:?}", links.len(), links);
-        for link in links { // for all links
-            let hash = {
-                let mut s = Sha1::new();
-                s.input_str(&link.as_str()[..]);
-                s.result_str()
-            };
-            let file_id = ModuleEntryPath::new(format!("external/{}", hash)).into_storeid();
-
-            debug!("Link    = '{:?}'", link);
-            debug!("Hash    = '{:?}'", hash);
-            debug!("StoreId = '{:?}'", file_id);
-
-            // retrieve the file from the store, which implicitely creates the entry if it does not
-            // exist
-            let file = store.retrieve(file_id.clone());
-            if file.is_err() {
-                debug!("Failed to create or retrieve an file for this link '{:?}'", link);
-                return Err(LE::new(LEK::StoreWriteError, Some(Box::new(file.unwrap_err()))));
-            }
-            let mut file = file.unwrap();
-
-            debug!("Generating header content!");
-            {
-                let mut hdr = file.deref_mut().get_header_mut();
-
-                let mut table = match hdr.read("imag.content") {
-                    Ok(Some(Value::Table(table))) => table,
-                    Ok(Some(_)) => {
-                        warn!("There is a value at 'imag.content' which is not a table.");
-                        warn!("Going to override this value");
-                        BTreeMap::new()
-                    },
-                    Ok(None) => BTreeMap::new(),
-                    Err(e)   => return Err(LE::new(LEK::StoreWriteError, Some(Box::new(e)))),
-                };
-
-                let v = Value::String(link.into_string());
-
-                debug!("setting URL = '{:?}", v);
-                table.insert(String::from("url"), v);
-
-                if let Err(e) = hdr.set("imag.content", Value::Table(table)) {
+        let mut new_ids = vec![];
+        for link in links {
+            let mut file = try!(retrieve_link_entry(store, &link));
+            let id = file.get_location().clone();
+
+            // then add an internal link to the new file or return an error if this fails. Existing
+            // links are kept as they are.
+            if !old_ids.contains(&id) {
+                if let Err(e) = self.add_internal_link(file.deref_mut()) {
+                    debug!("Error adding internal link");
                     return Err(LE::new(LEK::StoreWriteError, Some(Box::new(e))));
-                } else {
-                    debug!("Setting URL worked");
                 }
             }
 
-            // then add an internal link to the new file or return an error if this fails
-            if let Err(e) = self.add_internal_link(file.deref_mut()) {
-                debug!("Error adding internal link");
+            new_ids.push(id);
+        }
+        debug!("Ready iterating");
+
+        for id in old_ids.into_iter().filter(|id| !new_ids.contains(id)) {
+            debug!("Removing link to {:?}", id);
+            let mut file = try!(store.retrieve(id)
+                .map_err(|e| LE::new(LEK::StoreReadError, Some(Box::new(e)))));
+
+            if let Err(e) = self.remove_internal_link(file.deref_mut()) {
+                debug!("Error removing internal link");
                 return Err(LE::new(LEK::StoreWriteError, Some(Box::new(e))));
             }
         }
-        debug!("Ready iterating");
+
         Ok(())
     }
 
@@ -218,7 +325,9 @@ impl ExternalLinker for Entry {
         self.get_external_links(store)
             .and_then(|mut links| {
                 debug!("Adding link = '{:?}' to links = {:?}", link, links);
-                links.push(link);
+                if !links.contains(&link) {
+                    links.push(link);
+                }
                 debug!("Setting {} links = {:?}", links.len(), links);
                 self.set_external_links(store, links)
             })
@@ -239,3 +348,133 @@ impl ExternalLinker for Entry {
 
 }
 
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs::remove_dir_all;
+
+    use toml::Value;
+
+    use libimagstore::store::Store;
+    use libimagstore::storeid::StoreId;
+
+    use url::Url;
+
+    use internal::InternalLinker;
+    use super::{ExternalLinker, all_external_links, gc_external_links, is_external_link_storeid,
+                link_entry_id};
+
+    fn store(name: &str) -> Store {
+        let mut path = env::temp_dir();
+        path.push(format!("imag-external-link-test-{}", name));
+        let _ = remove_dir_all(&path);
+        Store::new(path, None).unwrap()
+    }
+
+    fn id(store: &Store, name: &str) -> StoreId {
+        StoreId::from(store.path().join(name))
+    }
+
+    #[test]
+    fn test_list_and_gc() {
+        let store = store("gc");
+        let a_id      = id(&store, "a~0.1.0");
+        let used_id   = id(&store, "links/external/used~0.1.0");
+        let orphan_id = id(&store, "links/external/orphan~0.1.0");
+
+        {
+            let mut a = store.create(a_id.clone()).unwrap();
+            for (link_id, url) in vec![(&used_id, "http://used.example.com/"),
+                                       (&orphan_id, "http://orphan.example.com/")] {
+                let mut link = store.create(link_id.clone()).unwrap();
+                let mut content = BTreeMap::new();
+                content.insert(String::from("url"), Value::String(String::from(url)));
+                link.get_header_mut().set("imag.content", Value::Table(content)).unwrap();
+                if *link_id == used_id {
+                    a.add_internal_link(&mut link).unwrap();
+                }
+            }
+        }
+
+        let links = all_external_links(&store).unwrap();
+        assert_eq!(2, links.len());
+        let used = links.iter().find(|l| l.id == used_id).unwrap();
+        assert_eq!(vec![a_id.clone()], used.referenced_by);
+        assert_eq!("http://used.example.com/", used.url.as_ref().unwrap().as_str());
+        assert!(links.iter().find(|l| l.id == orphan_id).unwrap().referenced_by.is_empty());
+
+        assert_eq!(vec![orphan_id.clone()], gc_external_links(&store, true).unwrap());
+        assert!(orphan_id.is_file());
+
+        assert_eq!(vec![orphan_id.clone()], gc_external_links(&store, false).unwrap());
+        assert!(!orphan_id.is_file());
+        assert!(used_id.is_file());
+        assert!(gc_external_links(&store, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_is_external_link_storeid() {
+        let store = store("is-external");
+        assert!(is_external_link_storeid(&id(&store, "links/external/abc~0.1.0")));
+        assert!(is_external_link_storeid(&StoreId::from(String::from("links/external/abc~0.1.0"))));
+        assert!(!is_external_link_storeid(&id(&store, "links/abc~0.1.0")));
+        assert!(!is_external_link_storeid(&id(&store, "notes/external/abc~0.1.0")));
+    }
+
+    #[test]
+    fn test_set_external_links_replaces() {
+        let store = store("set");
+        let a_id  = id(&store, "a~0.1.0");
+        let one   = Url::parse("http://one.example.com/").unwrap();
+        let two   = Url::parse("http://two.example.com/").unwrap();
+        let one_id = StoreId::from(store.path().join(&*link_entry_id(&one)));
+        let two_id = StoreId::from(store.path().join(&*link_entry_id(&two)));
+
+        let mut a = store.create(a_id.clone()).unwrap();
+        a.set_external_links(&store, vec![one.clone(), two.clone()]).unwrap();
+        assert_eq!(vec![one.clone(), two.clone()], {
+            let mut links = a.get_external_links(&store).unwrap();
+            links.sort_by(|x, y| x.as_str().cmp(y.as_str()));
+            links
+        });
+
+        a.set_external_links(&store, vec![two.clone()]).unwrap();
+        assert_eq!(vec![two.clone()], a.get_external_links(&store).unwrap());
+        assert!(store.retrieve_copy(one_id.clone())
+                .unwrap()
+                .get_internal_links()
+                .unwrap()
+                .is_empty());
+        assert_eq!(vec![a_id.clone()],
+                   store.retrieve_copy(two_id.clone()).unwrap().get_internal_links().unwrap());
+
+        a.add_external_link(&store, one.clone()).unwrap();
+        a.remove_external_link(&store, two.clone()).unwrap();
+        assert_eq!(vec![one.clone()], a.get_external_links(&store).unwrap());
+        assert!(store.retrieve_copy(two_id).unwrap().get_internal_links().unwrap().is_empty());
+        assert_eq!(vec![a_id], store.retrieve_copy(one_id).unwrap().get_internal_links().unwrap());
+    }
+
+    #[test]
+    fn test_add_external_link_keeps_existing_links() {
+        let store = store("keep");
+        let one   = Url::parse("http://one.example.com/").unwrap();
+        let two   = Url::parse("http://two.example.com/").unwrap();
+
+        let mut a = store.create(id(&store, "a~0.1.0")).unwrap();
+        a.add_external_link(&store, one.clone()).unwrap();
+        a.get_header_mut()
+            .set("imag.links.0.created-at", Value::String(String::from("2016-05-01T12:00:00")))
+            .unwrap();
+
+        a.add_external_link(&store, two.clone()).unwrap();
+        let link = a.get_typed_links()
+            .unwrap()
+            .into_iter()
+            .find(|l| l.target == StoreId::from(store.path().join(&*link_entry_id(&one))))
+            .unwrap();
+        assert_eq!(Some(String::from("2016-05-01T12:00:00")), link.created_at);
+    }
+
+}
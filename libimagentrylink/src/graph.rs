@@ -0,0 +1,366 @@
+//! A graph over the internal links of the entries in the store
+//!
+//! The nodes of the graph are the entries, the edges are the internal links. As internal links
+//! are bidirectional, the graph is traversed as undirected graph. The edges keep the kind of the
+//! link as it is stored in the entry the link is stored in.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::PathBuf;
+
+use rustc_serialize::json::Json;
+
+use libimagstore::store::{Entry, Store, StoreObject};
+use libimagstore::storeid::StoreId;
+
+use error::LinkError as LE;
+use error::LinkErrorKind as LEK;
+use internal::InternalLinker;
+use kind::LinkKind;
+use result::Result;
+
+/// A link from one entry to another, as stored in the entry `from`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub from: StoreId,
+    pub to: StoreId,
+    pub kind: LinkKind,
+}
+
+pub struct LinkGraph {
+    store_path: PathBuf,
+    nodes: BTreeSet<StoreId>,
+    edges: Vec<Edge>,
+    adjacent: BTreeMap<StoreId, BTreeSet<StoreId>>,
+}
+
+impl LinkGraph {
+
+    /// Create an empty graph for the store at `store_path`
+    pub fn new(store_path: PathBuf) -> LinkGraph {
+        LinkGraph {
+            store_path: store_path,
+            nodes: BTreeSet::new(),
+            edges: vec![],
+            adjacent: BTreeMap::new(),
+        }
+    }
+
+    /// Build the graph of all entries in the store
+    pub fn from_store(store: &Store) -> Result<LinkGraph> {
+        let mut graph = LinkGraph::new(store.path().clone());
+
+        for obj in store.walk("") {
+            if let StoreObject::Id(id) = obj {
+                debug!("Adding entry to link graph: {:?}", id);
+                let entry = try!(store.retrieve_copy(id)
+                    .map_err(|e| LE::new(LEK::StoreReadError, Some(Box::new(e)))));
+                try!(graph.add_entry(&entry));
+            }
+        }
+
+        Ok(graph)
+    }
+
+    /// Add an entry and its links to the graph
+    pub fn add_entry(&mut self, e: &Entry) -> Result<()> {
+        let from = e.get_location().clone();
+        self.nodes.insert(from.clone());
+        self.adjacent.entry(from.clone()).or_insert(BTreeSet::new());
+
+        for link in try!(e.get_typed_links()) {
+            self.nodes.insert(link.target.clone());
+            self.adjacent.entry(from.clone()).or_insert(BTreeSet::new()).insert(link.target.clone());
+            self.adjacent.entry(link.target.clone()).or_insert(BTreeSet::new()).insert(from.clone());
+            self.edges.push(Edge {
+                from: from.clone(),
+                to: link.target,
+                kind: link.kind,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn nodes(&self) -> Vec<&StoreId> {
+        self.nodes.iter().collect()
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges[..]
+    }
+
+    /// Get all entries which are reachable from `id` with at most `depth` links, without `id`
+    /// itself. The entries are ordered by their distance to `id`.
+    pub fn neighbors(&self, id: &StoreId, depth: usize) -> Vec<StoreId> {
+        let mut seen = BTreeSet::new();
+        let mut result = vec![];
+        let mut queue = VecDeque::new();
+
+        seen.insert(id.clone());
+        queue.push_back((id.clone(), 0));
+
+        while let Some((current, dist)) = queue.pop_front() {
+            if dist >= depth {
+                continue;
+            }
+
+            for next in self.adjacent_of(&current) {
+                if seen.insert(next.clone()) {
+                    result.push(next.clone());
+                    queue.push_back((next.clone(), dist + 1));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Get the shortest path from `from` to `to`, including both, if there is one
+    pub fn shortest_path(&self, from: &StoreId, to: &StoreId) -> Option<Vec<StoreId>> {
+        let mut predecessor : BTreeMap<StoreId, StoreId> = BTreeMap::new();
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::new();
+
+        seen.insert(from.clone());
+        queue.push_back(from.clone());
+
+        while let Some(current) = queue.pop_front() {
+            if current == *to {
+                let mut path = vec![current.clone()];
+                let mut node = current;
+                while let Some(pred) = predecessor.get(&node) {
+                    path.push(pred.clone());
+                    node = pred.clone();
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for next in self.adjacent_of(&current) {
+                if seen.insert(next.clone()) {
+                    predecessor.insert(next.clone(), current.clone());
+                    queue.push_back(next.clone());
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Get the connected components of the graph, each component sorted
+    pub fn connected_components(&self) -> Vec<Vec<StoreId>> {
+        let mut seen : BTreeSet<StoreId> = BTreeSet::new();
+        let mut components = vec![];
+
+        for node in self.nodes.iter() {
+            if seen.contains(node) {
+                continue;
+            }
+
+            let mut component = vec![node.clone()];
+            component.extend(self.neighbors(node, usize::max_value()));
+            for id in component.iter() {
+                seen.insert(id.clone());
+            }
+            component.sort();
+            components.push(component);
+        }
+
+        components
+    }
+
+    /// Get all entries without any links
+    pub fn unlinked(&self) -> Vec<StoreId> {
+        self.nodes
+            .iter()
+            .filter(|n| self.adjacent_of(n).is_empty())
+            .cloned()
+            .collect()
+    }
+
+    /// Export the graph in the Graphviz DOT format
+    ///
+    /// As links are bidirectional, a link and its backlink are one undirected edge. Each kind of
+    /// link between two entries gets its own edge, labeled with the kind of the link as seen from
+    /// the first entry of the edge, unless it is a plain link.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("graph imag {\n");
+        for node in self.nodes.iter() {
+            out.push_str(&format!("    \"{}\";\n", escape_dot(&self.name(node)[..]))[..]);
+        }
+
+        let mut done : BTreeSet<(StoreId, StoreId, LinkKind)> = BTreeSet::new();
+        for edge in self.edges.iter() {
+            let (from, to, kind) = if edge.from <= edge.to {
+                (edge.from.clone(), edge.to.clone(), edge.kind.clone())
+            } else {
+                (edge.to.clone(), edge.from.clone(), edge.kind.inverse())
+            };
+
+            let label = match kind {
+                LinkKind::Link => String::new(),
+                ref kind => format!(" [label=\"{}\"]", escape_dot(kind.name())),
+            };
+            let line = format!("    \"{}\" -- \"{}\"{};\n",
+                               escape_dot(&self.name(&from)[..]),
+                               escape_dot(&self.name(&to)[..]),
+                               label);
+
+            if done.insert((from, to, kind)) {
+                out.push_str(&line[..]);
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Export the graph as JSON object with a list of nodes and a list of edges:
+    ///
+    /// ```ignore
+    /// { "nodes": [ { "id": "notes/a~0.1.0" } ],
+    ///   "edges": [ { "from": "notes/a~0.1.0", "to": "notes/b~0.1.0", "kind": "link" } ] }
+    /// ```
+    pub fn to_json(&self) -> Json {
+        let nodes = self.nodes
+            .iter()
+            .map(|n| {
+                let mut obj = BTreeMap::new();
+                obj.insert(String::from("id"), Json::String(self.name(n)));
+                Json::Object(obj)
+            })
+            .collect();
+
+        let edges = self.edges
+            .iter()
+            .map(|e| {
+                let mut obj = BTreeMap::new();
+                obj.insert(String::from("from"), Json::String(self.name(&e.from)));
+                obj.insert(String::from("to"), Json::String(self.name(&e.to)));
+                obj.insert(String::from("kind"), Json::String(String::from(e.kind.name())));
+                Json::Object(obj)
+            })
+            .collect();
+
+        let mut obj = BTreeMap::new();
+        obj.insert(String::from("nodes"), Json::Array(nodes));
+        obj.insert(String::from("edges"), Json::Array(edges));
+        Json::Object(obj)
+    }
+
+    /// Get the name of the entry, which is its id relative to the store
+    pub fn name(&self, id: &StoreId) -> String {
+        format!("{}", id.strip_prefix(&self.store_path).unwrap_or(id).display())
+    }
+
+    fn adjacent_of(&self, id: &StoreId) -> Vec<&StoreId> {
+        self.adjacent
+            .get(id)
+            .map(|a| a.iter().collect())
+            .unwrap_or(vec![])
+    }
+
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace("\\", "\\\\").replace("\"", "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+
+    use internal::InternalLinker;
+    use kind::LinkKind;
+    use super::LinkGraph;
+
+    fn id(name: &str) -> StoreId {
+        StoreId::from(PathBuf::from(format!("/store/{}~0.1.0", name)))
+    }
+
+    /// a -- b -- c, d -- e, f
+    fn graph() -> LinkGraph {
+        let mut entries : Vec<Entry> = vec!["a", "b", "c", "d", "e", "f"]
+            .into_iter()
+            .map(|n| Entry::new(id(n)))
+            .collect();
+
+        {
+            let (left, right) = entries.split_at_mut(1);
+            left[0].add_internal_link(&mut right[0]).unwrap();
+        }
+        {
+            let (left, right) = entries.split_at_mut(2);
+            left[1].add_typed_link(&mut right[0], LinkKind::References, None).unwrap();
+        }
+        {
+            let (left, right) = entries.split_at_mut(4);
+            left[3].add_internal_link(&mut right[0]).unwrap();
+        }
+
+        let mut graph = LinkGraph::new(PathBuf::from("/store"));
+        for e in entries.iter() {
+            graph.add_entry(e).unwrap();
+        }
+        graph
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let g = graph();
+        assert_eq!(vec![id("b")], g.neighbors(&id("a"), 1));
+        assert_eq!(vec![id("b"), id("c")], g.neighbors(&id("a"), 2));
+        assert!(g.neighbors(&id("f"), 3).is_empty());
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let g = graph();
+        assert_eq!(Some(vec![id("a"), id("b"), id("c")]), g.shortest_path(&id("a"), &id("c")));
+        assert_eq!(Some(vec![id("a")]), g.shortest_path(&id("a"), &id("a")));
+        assert_eq!(None, g.shortest_path(&id("a"), &id("e")));
+    }
+
+    #[test]
+    fn test_components_and_unlinked() {
+        let g = graph();
+        assert_eq!(vec![vec![id("a"), id("b"), id("c")], vec![id("d"), id("e")], vec![id("f")]],
+                   g.connected_components());
+        assert_eq!(vec![id("f")], g.unlinked());
+    }
+
+    #[test]
+    fn test_export() {
+        let g = graph();
+        let dot = g.to_dot();
+        assert!(dot.contains("\"a~0.1.0\" -- \"b~0.1.0\";"));
+        assert!(dot.contains("\"b~0.1.0\" -- \"c~0.1.0\" [label=\"references\"];"));
+        assert_eq!(1, dot.matches("\"d~0.1.0\" -- \"e~0.1.0\"").count());
+        assert_eq!(1, dot.matches("\"b~0.1.0\" -- \"c~0.1.0\"").count());
+
+        let json = g.to_json();
+        assert_eq!(6, json.find("nodes").and_then(|n| n.as_array()).map(|a| a.len()).unwrap());
+        assert_eq!(6, json.find("edges").and_then(|n| n.as_array()).map(|a| a.len()).unwrap());
+    }
+
+    #[test]
+    fn test_dot_keeps_each_link_kind() {
+        let mut a = Entry::new(id("a"));
+        let mut b = Entry::new(id("b"));
+        a.add_internal_link(&mut b).unwrap();
+        b.add_typed_link(&mut a, LinkKind::DependsOn, None).unwrap();
+
+        let mut g = LinkGraph::new(PathBuf::from("/store"));
+        g.add_entry(&a).unwrap();
+        g.add_entry(&b).unwrap();
+
+        let dot = g.to_dot();
+        assert_eq!(2, dot.matches("\"a~0.1.0\" -- \"b~0.1.0\"").count());
+        assert!(dot.contains("\"a~0.1.0\" -- \"b~0.1.0\";"));
+        assert!(dot.contains("\"a~0.1.0\" -- \"b~0.1.0\" [label=\"dependency-of\"];"));
+    }
+
+}
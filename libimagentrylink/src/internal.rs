@@ -1,42 +1,174 @@
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 
 use libimagstore::storeid::StoreId;
 use libimagstore::store::Entry;
 use libimagstore::store::EntryHeader;
-use libimagstore::store::Result as StoreResult;
+use libimagstore::store::Store;
+use libimagstore::store::StoreObject;
 
 use error::{LinkError, LinkErrorKind};
+use kind::LinkKind;
 use result::Result;
 
+use chrono::Local;
 use toml::Value;
-use itertools::Itertools;
 
 pub type Link = StoreId;
 
+/// An internal link with its relation type and attributes
+///
+/// Typed links are stored in the header as array of tables:
+///
+/// ```toml
+/// [[imag.links]]
+/// target = "/path/to/entry~0.1.0"
+/// kind = "references"
+/// created-at = "2016-05-01T12:00:00"
+/// label = "see also"
+/// ```
+///
+/// The old format, an array of store ids, can still be read. Each id is a link of the kind
+/// `LinkKind::Link` without attributes. Links are always written in the new format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedLink {
+    pub target: Link,
+    pub kind: LinkKind,
+    pub created_at: Option<String>,
+    pub label: Option<String>,
+}
+
+impl TypedLink {
+
+    pub fn new(target: Link, kind: LinkKind) -> TypedLink {
+        TypedLink {
+            target: target,
+            kind: kind,
+            created_at: None,
+            label: None,
+        }
+    }
+
+    /// Get the backlink for this link, which points to `from`, with the inverse kind and the same
+    /// attributes
+    pub fn inverse(&self, from: Link) -> TypedLink {
+        TypedLink {
+            target: from,
+            kind: self.kind.inverse(),
+            created_at: self.created_at.clone(),
+            label: self.label.clone(),
+        }
+    }
+
+    fn from_value(v: Value) -> Result<TypedLink> {
+        match v {
+            Value::String(s) => Ok(TypedLink::new(StoreId::from(s), LinkKind::Link)),
+            Value::Table(t) => {
+                let target = match t.get("target") {
+                    Some(&Value::String(ref s)) => StoreId::from(s.clone()),
+                    _ => {
+                        debug!("Link without target: {:?}", t);
+                        return Err(LinkError::new(LinkErrorKind::ExistingLinkTypeWrong, None));
+                    },
+                };
+
+                let kind = match t.get("kind") {
+                    Some(&Value::String(ref s)) => LinkKind::from_name(&s[..]),
+                    None => LinkKind::Link,
+                    Some(_) => {
+                        debug!("Link kind is not a String: {:?}", t);
+                        return Err(LinkError::new(LinkErrorKind::ExistingLinkTypeWrong, None));
+                    },
+                };
+
+                Ok(TypedLink {
+                    target: target,
+                    kind: kind,
+                    created_at: get_string(&t, "created-at"),
+                    label: get_string(&t, "label"),
+                })
+            },
+            _ => {
+                debug!("We expected a String or a Table for a link, but got: {:?}", v);
+                Err(LinkError::new(LinkErrorKind::ExistingLinkTypeWrong, None))
+            },
+        }
+    }
+
+    fn into_value(self) -> Result<Value> {
+        let target = try!(self.target
+            .to_str()
+            .map(String::from)
+            .ok_or(LinkError::new(LinkErrorKind::InternalConversionError, None)));
+
+        let mut t = BTreeMap::new();
+        t.insert(String::from("target"), Value::String(target));
+        t.insert(String::from("kind"), Value::String(String::from(self.kind.name())));
+        if let Some(created_at) = self.created_at {
+            t.insert(String::from("created-at"), Value::String(created_at));
+        }
+        if let Some(label) = self.label {
+            t.insert(String::from("label"), Value::String(label));
+        }
+        Ok(Value::Table(t))
+    }
+
+}
+
 pub trait InternalLinker {
 
     /// Get the internal links from the implementor object
     fn get_internal_links(&self) -> Result<Vec<Link>>;
 
     /// Set the internal links for the implementor object
-    fn set_internal_links(&mut self, links: Vec<&mut Entry>) -> Result<Vec<Link>>;
+    ///
+    /// The entries in `links` get a backlink, the backlinks in the entries which are not linked
+    /// anymore are removed. These entries are retrieved from the `store`, so they must not be
+    /// borrowed by the caller.
+    fn set_internal_links(&mut self, store: &Store, links: Vec<&mut Entry>) -> Result<Vec<Link>>;
 
-    /// Add an internal link to the implementor object
+    /// Add an internal link to the implementor object, and a backlink to `link`
     fn add_internal_link(&mut self, link: &mut Entry) -> Result<()>;
 
-    /// Remove an internal link from the implementor object
+    /// Remove all internal links between the implementor object and `link`, in both directions
     fn remove_internal_link(&mut self, link: &mut Entry) -> Result<()>;
 
+    /// Remove all internal links to `link` from the implementor object only
+    ///
+    /// This is meant for links to entries which do not exist anymore, so there is no backlink to
+    /// remove.
+    fn remove_dangling_link(&mut self, link: &Link) -> Result<()>;
+
+    /// Get the internal links with their kinds and attributes
+    fn get_typed_links(&self) -> Result<Vec<TypedLink>>;
+
+    /// Get the internal links of one kind
+    fn get_links_of_kind(&self, kind: &LinkKind) -> Result<Vec<Link>>;
+
+    /// Add a link of `kind` to `link`, which gets a backlink of the inverse kind
+    ///
+    /// If there is a link of the same kind to `link` already, it keeps its creation time and its
+    /// label, unless a new label is given.
+    fn add_typed_link(&mut self, link: &mut Entry, kind: LinkKind, label: Option<String>)
+        -> Result<()>;
+
+    /// Remove the link of `kind` to `link` and its backlink
+    fn remove_typed_link(&mut self, link: &mut Entry, kind: &LinkKind) -> Result<()>;
+
+    /// Rewrite the links from the old format (an array of store ids) into the format for typed
+    /// links. Returns whether the links were rewritten.
+    fn migrate_links(&mut self) -> Result<bool>;
+
 }
 
 impl InternalLinker for Entry {
 
     fn get_internal_links(&self) -> Result<Vec<Link>> {
-        process_rw_result(self.get_header().read("imag.links"))
+        read_typed_links(self.get_header()).map(|links| targets(&links[..]))
     }
 
     /// Set the links in a header and return the old links, if any.
-    fn set_internal_links(&mut self, links: Vec<&mut Entry>) -> Result<Vec<Link>> {
+    fn set_internal_links(&mut self, store: &Store, links: Vec<&mut Entry>) -> Result<Vec<Link>> {
         let self_location = self.get_location().clone();
         let mut new_links = vec![];
 
@@ -48,134 +180,561 @@ impl InternalLinker for Entry {
             new_links.push(link);
         }
 
-        let new_links = links_into_values(new_links);
-        if new_links.iter().any(|o| o.is_none()) {
-            return Err(LinkError::new(LinkErrorKind::InternalConversionError, None));
+        let old_links = try!(self.get_internal_links());
+        for old in old_links.iter().filter(|l| !new_links.contains(*l) && **l != self_location) {
+            if !entry_exists(store, old) {
+                debug!("Not removing backlink from non-existing entry: {:?}", old);
+                continue;
+            }
+
+            debug!("Removing backlink from {:?}", old);
+            let mut entry = try!(store.retrieve(old.clone())
+                .map_err(|e| LinkError::new(LinkErrorKind::StoreReadError, Some(Box::new(e)))));
+            try!(remove_links_to(entry.get_header_mut(), &self_location));
         }
-        let new_links = new_links.into_iter().map(|o| o.unwrap()).collect();
-        process_rw_result(self.get_header_mut().set("imag.links", Value::Array(new_links)))
+
+        rewrite_links(self.get_header_mut(), new_links).map(|_| old_links)
     }
 
     fn add_internal_link(&mut self, link: &mut Entry) -> Result<()> {
-        let new_link = link.get_location().clone();
-
-        add_foreign_link(link, self.get_location().clone())
-            .and_then(|_| {
-                self.get_internal_links()
-                    .and_then(|mut links| {
-                        links.push(new_link);
-                        rewrite_links(self.get_header_mut(), links)
-                    })
-            })
+        self.add_typed_link(link, LinkKind::Link, None)
     }
 
     fn remove_internal_link(&mut self, link: &mut Entry) -> Result<()> {
-        let own_loc   = link.get_location().clone();
+        let own_loc   = self.get_location().clone();
         let other_loc = link.get_location().clone();
 
-        link.get_internal_links()
-            .and_then(|links| {
-                let links = links.into_iter().filter(|l| l.clone() != own_loc).collect();
-                rewrite_links(self.get_header_mut(), links)
-            })
-            .and_then(|_| {
-                self.get_internal_links()
-                    .and_then(|links| {
-                        let links = links.into_iter().filter(|l| l.clone() != other_loc).collect();
-                        rewrite_links(link.get_header_mut(), links)
-                    })
+        remove_links_to(self.get_header_mut(), &other_loc)
+            .and_then(|_| remove_links_to(link.get_header_mut(), &own_loc))
+    }
+
+    fn remove_dangling_link(&mut self, link: &Link) -> Result<()> {
+        remove_links_to(self.get_header_mut(), link)
+    }
+
+    fn get_typed_links(&self) -> Result<Vec<TypedLink>> {
+        read_typed_links(self.get_header())
+    }
+
+    fn get_links_of_kind(&self, kind: &LinkKind) -> Result<Vec<Link>> {
+        self.get_typed_links()
+            .map(|links| {
+                links.into_iter()
+                    .filter(|l| l.kind == *kind)
+                    .map(|l| l.target)
+                    .collect()
             })
     }
 
+    fn add_typed_link(&mut self, link: &mut Entry, kind: LinkKind, label: Option<String>)
+        -> Result<()>
+    {
+        let target   = link.get_location().clone();
+        let existing = try!(self.get_typed_links())
+            .into_iter()
+            .find(|l| l.target == target && l.kind == kind);
+
+        let mut new_link = match existing {
+            Some(l) => l,
+            None    => {
+                let mut l = TypedLink::new(target, kind);
+                l.created_at = Some(now());
+                l
+            },
+        };
+        if label.is_some() {
+            new_link.label = label;
+        }
+
+        let backlink = new_link.inverse(self.get_location().clone());
+        insert_typed_link(link.get_header_mut(), backlink)
+            .and_then(|_| insert_typed_link(self.get_header_mut(), new_link))
+    }
+
+    fn remove_typed_link(&mut self, link: &mut Entry, kind: &LinkKind) -> Result<()> {
+        let own_loc   = self.get_location().clone();
+        let other_loc = link.get_location().clone();
+
+        remove_typed_links(self.get_header_mut(), &other_loc, kind)
+            .and_then(|_| remove_typed_links(link.get_header_mut(), &own_loc, &kind.inverse()))
+    }
+
+    fn migrate_links(&mut self) -> Result<bool> {
+        let is_old_format = match self.get_header().read("imag.links") {
+            Ok(Some(Value::Array(ref a))) => a.iter().any(|v| is_string(v)),
+            Ok(_)  => false,
+            Err(e) => return Err(LinkError::new(LinkErrorKind::EntryHeaderReadError,
+                                                Some(Box::new(e)))),
+        };
+
+        if is_old_format {
+            debug!("Migrating links of {:?}", self.get_location());
+            let links = try!(self.get_typed_links());
+            write_typed_links(self.get_header_mut(), links).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
 }
 
-fn links_into_values(links: Vec<StoreId>) -> Vec<Option<Value>> {
-    links
-        .into_iter()
-        .map(|s| s.to_str().map(|s| String::from(s)))
-        .unique()
-        .map(|elem| elem.map(|s| Value::String(s)))
-        .sorted_by(|a, b| {
-            match (a, b) {
-                (&Some(Value::String(ref a)), &Some(Value::String(ref b))) => Ord::cmp(a, b),
-                (&None, _) | (_, &None) => Ordering::Equal,
-                _                                              => unreachable!()
-            }
+/// Get the targets of the links, each target only once
+fn targets(links: &[TypedLink]) -> Vec<Link> {
+    let mut targets : Vec<Link> = vec![];
+    for link in links {
+        if !targets.contains(&link.target) {
+            targets.push(link.target.clone());
+        }
+    }
+    targets
+}
+
+fn read_typed_links(header: &EntryHeader) -> Result<Vec<TypedLink>> {
+    match header.read("imag.links") {
+        Ok(Some(Value::Array(links))) => links.into_iter().map(TypedLink::from_value).collect(),
+        Ok(Some(_)) => {
+            debug!("We expected an Array for the links, but there was a non-Array!");
+            Err(LinkError::new(LinkErrorKind::ExistingLinkTypeWrong, None))
+        },
+        Ok(None) => {
+            debug!("We got no value from the header!");
+            Ok(vec![])
+        },
+        Err(e) => {
+            debug!("RW action on store failed. Generating LinkError");
+            Err(LinkError::new(LinkErrorKind::EntryHeaderReadError, Some(Box::new(e))))
+        },
+    }
+}
+
+/// Write the links sorted by target and kind, without duplicates. Of two links with the same
+/// target and kind, the latter one is kept.
+fn write_typed_links(header: &mut EntryHeader, links: Vec<TypedLink>) -> Result<()> {
+    let mut unique : Vec<TypedLink> = vec![];
+    for link in links.into_iter().rev() {
+        if !unique.iter().any(|l| l.target == link.target && l.kind == link.kind) {
+            unique.push(link);
+        }
+    }
+    unique.sort_by(|a, b| {
+        match Ord::cmp(&a.target, &b.target) {
+            Ordering::Equal => Ord::cmp(&a.kind, &b.kind),
+            other => other,
+        }
+    });
+
+    let mut values = vec![];
+    for link in unique {
+        values.push(try!(link.into_value()));
+    }
+
+    header.set("imag.links", Value::Array(values))
+        .map(|_| ())
+        .map_err(|e| LinkError::new(LinkErrorKind::EntryHeaderWriteError, Some(Box::new(e))))
+}
+
+fn insert_typed_link(header: &mut EntryHeader, link: TypedLink) -> Result<()> {
+    read_typed_links(header)
+        .and_then(|mut links| {
+            links.push(link);
+            write_typed_links(header, links)
+        })
+}
+
+fn remove_typed_links(header: &mut EntryHeader, target: &Link, kind: &LinkKind) -> Result<()> {
+    read_typed_links(header)
+        .and_then(|links| {
+            let links = links.into_iter()
+                .filter(|l| !(l.target == *target && l.kind == *kind))
+                .collect();
+            write_typed_links(header, links)
+        })
+}
+
+fn remove_links_to(header: &mut EntryHeader, target: &Link) -> Result<()> {
+    read_typed_links(header)
+        .and_then(|links| {
+            let links = links.into_iter().filter(|l| l.target != *target).collect();
+            write_typed_links(header, links)
         })
 }
 
+/// Set the links in the header to `links`. Existing links to these targets are kept with their
+/// kind and attributes, new targets get an untyped link.
 fn rewrite_links(header: &mut EntryHeader, links: Vec<StoreId>) -> Result<()> {
-    let links = links_into_values(links);
+    read_typed_links(header)
+        .and_then(|existing| {
+            let mut new_links : Vec<TypedLink> = existing.into_iter()
+                .filter(|l| links.contains(&l.target))
+                .collect();
 
-    if links.iter().any(|o| o.is_none()) {
-        // if any type convert failed we fail as well
-        Err(LinkError::new(LinkErrorKind::InternalConversionError, None))
-    } else {
-        // I know it is ugly
-        let links = links.into_iter().map(|opt| opt.unwrap()).collect();
-        let process = header.set("imag.links", Value::Array(links));
-        process_rw_result(process).map(|_| ())
-    }
+            for link in links {
+                if !new_links.iter().any(|l| l.target == link) {
+                    new_links.push(TypedLink::new(link, LinkKind::Link));
+                }
+            }
+
+            write_typed_links(header, new_links)
+        })
 }
 
 /// When Linking A -> B, the specification wants us to link back B -> A.
-/// This is a helper function which does this.
+/// This is a helper function which does this, if there is no link B -> A already.
 fn add_foreign_link(target: &mut Entry, from: StoreId) -> Result<()> {
-    target.get_internal_links()
-        .and_then(|mut links| {
-            links.push(from);
-            let links = links_into_values(links);
-            if links.iter().any(|o| o.is_none()) {
-                Err(LinkError::new(LinkErrorKind::InternalConversionError, None))
-            } else {
-                let links = links.into_iter().map(|opt| opt.unwrap()).collect();
-                process_rw_result(target.get_header_mut().set("imag.links", Value::Array(links)))
-                    .map(|_| ())
+    if try!(target.get_internal_links()).contains(&from) {
+        return Ok(());
+    }
+
+    let mut link = TypedLink::new(from, LinkKind::Link);
+    link.created_at = Some(now());
+    insert_typed_link(target.get_header_mut(), link)
+}
+
+/// A change which was made by `repair_links()`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Repair {
+    /// The backlink was added to the entry
+    AddedBacklink(Link, TypedLink),
+
+    /// The link to an entry which does not exist was removed from the entry
+    RemovedDanglingLink(Link, TypedLink),
+}
+
+/// Scan the store for one-sided links and repair them
+///
+/// For each link A -> B, B gets the backlink B -> A of the inverse kind, if it does not have it
+/// already. Links to entries which do not exist are removed.
+pub fn repair_links(store: &Store) -> Result<Vec<Repair>> {
+    let ids : Vec<StoreId> = store.walk("")
+        .filter_map(|obj| match obj {
+            StoreObject::Id(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+
+    let mut repairs = vec![];
+    for id in ids {
+        let links = try!(store.retrieve_copy(id.clone())
+            .map_err(|e| LinkError::new(LinkErrorKind::StoreReadError, Some(Box::new(e))))
+            .and_then(|entry| entry.get_typed_links()));
+
+        for link in links {
+            if !entry_exists(store, &link.target) {
+                debug!("Removing dangling link {:?} -> {:?}", id, link.target);
+                try!(modify_entry(store, &id, |hdr| {
+                    remove_typed_links(hdr, &link.target, &link.kind)
+                }));
+                repairs.push(Repair::RemovedDanglingLink(id.clone(), link));
+                continue;
             }
+
+            let backlink = link.inverse(id.clone());
+            let has_backlink = try!(store.retrieve_copy(link.target.clone())
+                .map_err(|e| LinkError::new(LinkErrorKind::StoreReadError, Some(Box::new(e))))
+                .and_then(|entry| entry.get_typed_links()))
+                .iter()
+                .any(|l| l.target == backlink.target && l.kind == backlink.kind);
+
+            if !has_backlink {
+                debug!("Adding backlink {:?} -> {:?}", link.target, id);
+                try!(modify_entry(store, &link.target, |hdr| {
+                    insert_typed_link(hdr, backlink.clone())
+                }));
+                repairs.push(Repair::AddedBacklink(link.target.clone(), backlink));
+            }
+        }
+    }
+
+    Ok(repairs)
+}
+
+/// Convert the links of all entries in the store which use the old format (an array of store
+/// ids) to typed links. Returns the ids of the converted entries.
+pub fn migrate_all_links(store: &Store) -> Result<Vec<StoreId>> {
+    let ids : Vec<StoreId> = store.walk("")
+        .filter_map(|obj| match obj {
+            StoreObject::Id(id) => Some(id),
+            _ => None,
         })
+        .collect();
+
+    let mut migrated = vec![];
+    for id in ids {
+        let mut entry = try!(store.retrieve(id.clone())
+            .map_err(|e| LinkError::new(LinkErrorKind::StoreReadError, Some(Box::new(e)))));
+
+        // The entry is written when it is dropped
+        if try!(entry.migrate_links()) {
+            migrated.push(id);
+        }
+    }
+
+    Ok(migrated)
+}
+
+/// Change the header of the entry `id`, the entry is written when it is dropped
+fn modify_entry<F>(store: &Store, id: &StoreId, f: F) -> Result<()>
+    where F: FnOnce(&mut EntryHeader) -> Result<()>
+{
+    store.retrieve(id.clone())
+        .map_err(|e| LinkError::new(LinkErrorKind::StoreReadError, Some(Box::new(e))))
+        .and_then(|mut entry| f(entry.get_header_mut()))
+}
+
+/// Check whether the linked entry exists, the same way `LinkedEntriesExistHook` does
+fn entry_exists(store: &Store, link: &Link) -> bool {
+    let mut path = store.path().clone();
+    path.push(link);
+    path.is_file()
 }
 
-fn process_rw_result(links: StoreResult<Option<Value>>) -> Result<Vec<Link>> {
-    if links.is_err() {
-        debug!("RW action on store failed. Generating LinkError");
-        let lerr  = LinkError::new(LinkErrorKind::EntryHeaderReadError,
-                                   Some(Box::new(links.unwrap_err())));
-        return Err(lerr);
+fn get_string(t: &BTreeMap<String, Value>, key: &str) -> Option<String> {
+    match t.get(key) {
+        Some(&Value::String(ref s)) => Some(s.clone()),
+        _ => None,
     }
-    let links = links.unwrap();
+}
 
-    if links.is_none() {
-        debug!("We got no value from the header!");
-        return Ok(vec![])
+fn is_string(v: &Value) -> bool {
+    match v {
+        &Value::String(_) => true,
+        _ => false,
     }
-    let links = links.unwrap();
+}
 
-    let links = {
-        match links {
-            Value::Array(a) => a,
-            _ => {
-                debug!("We expected an Array for the links, but there was a non-Array!");
-                return Err(LinkError::new(LinkErrorKind::ExistingLinkTypeWrong, None));
-            },
+fn now() -> String {
+    Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::remove_dir_all;
+    use std::path::PathBuf;
+
+    use toml::Value;
+
+    use libimagstore::store::Entry;
+    use libimagstore::store::Store;
+    use libimagstore::storeid::StoreId;
+
+    use kind::LinkKind;
+    use super::{InternalLinker, Repair, migrate_all_links, repair_links};
+
+    fn entry(name: &str) -> Entry {
+        Entry::new(StoreId::from(PathBuf::from(name)))
+    }
+
+    fn store(name: &str) -> Store {
+        let mut path = env::temp_dir();
+        path.push(format!("imag-link-test-{}", name));
+        let _ = remove_dir_all(&path);
+        Store::new(path, None).unwrap()
+    }
+
+    fn id(store: &Store, name: &str) -> StoreId {
+        let mut path = store.path().clone();
+        path.push(name);
+        StoreId::from(path)
+    }
+
+    #[test]
+    fn test_add_internal_link_is_symmetric() {
+        let mut a = entry("/a~0.1.0");
+        let mut b = entry("/b~0.1.0");
+
+        a.add_internal_link(&mut b).unwrap();
+
+        assert_eq!(vec![StoreId::from(PathBuf::from("/b~0.1.0"))], a.get_internal_links().unwrap());
+        assert_eq!(vec![StoreId::from(PathBuf::from("/a~0.1.0"))], b.get_internal_links().unwrap());
+    }
+
+    #[test]
+    fn test_remove_internal_link_is_symmetric() {
+        let mut a = entry("/a~0.1.0");
+        let mut b = entry("/b~0.1.0");
+        let mut c = entry("/c~0.1.0");
+
+        a.add_internal_link(&mut b).unwrap();
+        a.add_typed_link(&mut b, LinkKind::References, None).unwrap();
+        a.add_internal_link(&mut c).unwrap();
+        a.remove_internal_link(&mut b).unwrap();
+
+        assert_eq!(vec![StoreId::from(PathBuf::from("/c~0.1.0"))], a.get_internal_links().unwrap());
+        assert!(b.get_internal_links().unwrap().is_empty());
+        assert_eq!(vec![StoreId::from(PathBuf::from("/a~0.1.0"))], c.get_internal_links().unwrap());
+    }
+
+    #[test]
+    fn test_set_internal_links_removes_backlinks() {
+        let store = store("set");
+        let a_id = id(&store, "a~0.1.0");
+        let b_id = id(&store, "b~0.1.0");
+        let c_id = id(&store, "c~0.1.0");
+
+        {
+            let mut a = store.create(a_id.clone()).unwrap();
+            let mut c = store.create(c_id.clone()).unwrap();
+            {
+                // b must not be borrowed when its backlink is removed
+                let mut b = store.create(b_id.clone()).unwrap();
+                a.add_internal_link(&mut b).unwrap();
+            }
+
+            let old = a.set_internal_links(&store, vec![&mut *c]).unwrap();
+            assert_eq!(vec![b_id.clone()], old);
         }
-    };
 
-    if !links.iter().all(|l| match l { &Value::String(_) => true, _ => false }) {
-        debug!("At least one of the Values which were expected in the Array of links is a non-String!");
-        debug!("Generating LinkError");
-        return Err(LinkError::new(LinkErrorKind::ExistingLinkTypeWrong, None));
+        let a = store.retrieve_copy(a_id.clone()).unwrap();
+        let b = store.retrieve_copy(b_id.clone()).unwrap();
+        let c = store.retrieve_copy(c_id.clone()).unwrap();
+        assert_eq!(vec![c_id], a.get_internal_links().unwrap());
+        assert!(b.get_internal_links().unwrap().is_empty());
+        assert_eq!(vec![a_id], c.get_internal_links().unwrap());
     }
 
-    let links : Vec<Link> = links.into_iter()
-        .map(|link| {
-            match link {
-                Value::String(s) => StoreId::from(s),
-                _ => unreachable!(),
+    #[test]
+    fn test_repair_links() {
+        let store = store("repair");
+        let a_id = id(&store, "a~0.1.0");
+        let b_id = id(&store, "b~0.1.0");
+        let d_id = id(&store, "d~0.1.0");
+
+        {
+            let mut a = store.create(a_id.clone()).unwrap();
+            let mut b = store.create(b_id.clone()).unwrap();
+            let mut d = entry("/d~0.1.0");
+            a.add_typed_link(&mut b, LinkKind::References, None).unwrap();
+            a.add_internal_link(&mut d).unwrap();
+
+            // Make the link from a to b one-sided
+            b.get_header_mut().set("imag.links", Value::Array(vec![])).unwrap();
+
+            // Make the link to d point into the store, where d does not exist
+            let mut links = a.get_typed_links().unwrap();
+            for l in links.iter_mut().filter(|l| l.kind == LinkKind::Link) {
+                l.target = d_id.clone();
             }
-        })
-        .collect();
+            super::write_typed_links(a.get_header_mut(), links).unwrap();
+        }
 
-    debug!("Ok, the RW action was successful, returning link vector now!");
-    Ok(links)
-}
+        let repairs = repair_links(&store).unwrap();
+        assert_eq!(2, repairs.len());
+        assert!(repairs.iter().any(|r| match r {
+            &Repair::AddedBacklink(ref id, ref l) => {
+                *id == b_id && l.kind == LinkKind::ReferencedBy
+            },
+            _ => false,
+        }));
+        assert!(repairs.iter().any(|r| match r {
+            &Repair::RemovedDanglingLink(ref id, ref l) => *id == a_id && l.target == d_id,
+            _ => false,
+        }));
+
+        let a = store.retrieve_copy(a_id.clone()).unwrap();
+        let b = store.retrieve_copy(b_id.clone()).unwrap();
+        assert_eq!(vec![b_id], a.get_internal_links().unwrap());
+        assert_eq!(vec![a_id], b.get_links_of_kind(&LinkKind::ReferencedBy).unwrap());
+        assert!(repair_links(&store).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_typed_link_has_inverse_backlink() {
+        let mut a = entry("/a~0.1.0");
+        let mut b = entry("/b~0.1.0");
+
+        a.add_typed_link(&mut b, LinkKind::SubtaskOf, Some(String::from("label"))).unwrap();
+
+        let a_links = a.get_typed_links().unwrap();
+        let b_links = b.get_typed_links().unwrap();
+        assert_eq!(1, a_links.len());
+        assert_eq!(1, b_links.len());
+        assert_eq!(LinkKind::SubtaskOf, a_links[0].kind);
+        assert_eq!(LinkKind::HasSubtask, b_links[0].kind);
+        assert_eq!(Some(String::from("label")), b_links[0].label);
+        assert!(a_links[0].created_at.is_some());
+        assert_eq!(vec![StoreId::from(PathBuf::from("/a~0.1.0"))],
+                   b.get_links_of_kind(&LinkKind::HasSubtask).unwrap());
+    }
 
+    #[test]
+    fn test_add_existing_typed_link_keeps_attributes() {
+        let mut a = entry("/a~0.1.0");
+        let mut b = entry("/b~0.1.0");
+
+        a.add_typed_link(&mut b, LinkKind::References, Some(String::from("label"))).unwrap();
+        let mut links = a.get_typed_links().unwrap();
+        links[0].created_at = Some(String::from("2016-05-01T12:00:00"));
+        super::write_typed_links(a.get_header_mut(), links).unwrap();
+
+        a.add_typed_link(&mut b, LinkKind::References, None).unwrap();
+        let links = a.get_typed_links().unwrap();
+        assert_eq!(1, links.len());
+        assert_eq!(Some(String::from("2016-05-01T12:00:00")), links[0].created_at);
+        assert_eq!(Some(String::from("label")), links[0].label);
+        assert_eq!(Some(String::from("2016-05-01T12:00:00")),
+                   b.get_typed_links().unwrap()[0].created_at);
+
+        a.add_typed_link(&mut b, LinkKind::References, Some(String::from("other"))).unwrap();
+        let links = a.get_typed_links().unwrap();
+        assert_eq!(Some(String::from("2016-05-01T12:00:00")), links[0].created_at);
+        assert_eq!(Some(String::from("other")), links[0].label);
+    }
+
+    #[test]
+    fn test_remove_typed_link() {
+        let mut a = entry("/a~0.1.0");
+        let mut b = entry("/b~0.1.0");
+
+        a.add_typed_link(&mut b, LinkKind::References, None).unwrap();
+        a.add_internal_link(&mut b).unwrap();
+        a.remove_typed_link(&mut b, &LinkKind::References).unwrap();
+
+        assert_eq!(vec![LinkKind::Link],
+                   a.get_typed_links().unwrap().into_iter().map(|l| l.kind).collect::<Vec<_>>());
+        assert_eq!(vec![LinkKind::Link],
+                   b.get_typed_links().unwrap().into_iter().map(|l| l.kind).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_migrate_links() {
+        let mut a = entry("/a~0.1.0");
+        a.get_header_mut()
+            .set("imag.links", Value::Array(vec![Value::String(String::from("/b~0.1.0"))]))
+            .unwrap();
+
+        assert_eq!(vec![StoreId::from(PathBuf::from("/b~0.1.0"))], a.get_internal_links().unwrap());
+        assert!(a.migrate_links().unwrap());
+        assert!(!a.migrate_links().unwrap());
+
+        match a.get_header().read("imag.links.0.kind") {
+            Ok(Some(Value::String(ref s))) => assert_eq!("link", s),
+            other => panic!("Unexpected value: {:?}", other),
+        }
+        assert_eq!(vec![StoreId::from(PathBuf::from("/b~0.1.0"))], a.get_internal_links().unwrap());
+    }
+
+    #[test]
+    fn test_migrate_all_links() {
+        let store = store("migrate");
+        let a_id = id(&store, "a~0.1.0");
+        let b_id = id(&store, "b~0.1.0");
+
+        {
+            let mut a = store.create(a_id.clone()).unwrap();
+            let old = Value::String(String::from("/b~0.1.0"));
+            a.get_header_mut().set("imag.links", Value::Array(vec![old])).unwrap();
+            let _ = store.create(b_id.clone()).unwrap();
+        }
+
+        assert_eq!(vec![a_id.clone()], migrate_all_links(&store).unwrap());
+        assert!(migrate_all_links(&store).unwrap().is_empty());
+
+        let a = store.retrieve_copy(a_id).unwrap();
+        match a.get_header().read("imag.links.0.kind") {
+            Ok(Some(Value::String(ref s))) => assert_eq!("link", s),
+            other => panic!("Unexpected value: {:?}", other),
+        }
+    }
+
+}
@@ -0,0 +1,75 @@
+//! Relation types of internal links
+//!
+//! Each internal link has a kind, which describes the relation between the linking and the linked
+//! entry. As links are always bidirectional, the backlink gets the inverse kind: If A `references`
+//! B, B is `referenced-by` A. Kinds which are not known to this library are their own inverse.
+
+use std::fmt::{Display, Formatter};
+use std::fmt::Error as FmtError;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LinkKind {
+    /// A plain link without a special meaning, this is the kind of all untyped links
+    Link,
+    References,
+    ReferencedBy,
+    SubtaskOf,
+    HasSubtask,
+    DependsOn,
+    DependencyOf,
+    Custom(String),
+}
+
+impl LinkKind {
+
+    pub fn from_name(s: &str) -> LinkKind {
+        match s {
+            "link"          => LinkKind::Link,
+            "references"    => LinkKind::References,
+            "referenced-by" => LinkKind::ReferencedBy,
+            "subtask-of"    => LinkKind::SubtaskOf,
+            "has-subtask"   => LinkKind::HasSubtask,
+            "depends-on"    => LinkKind::DependsOn,
+            "dependency-of" => LinkKind::DependencyOf,
+            other           => LinkKind::Custom(String::from(other)),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            &LinkKind::Link          => "link",
+            &LinkKind::References    => "references",
+            &LinkKind::ReferencedBy  => "referenced-by",
+            &LinkKind::SubtaskOf     => "subtask-of",
+            &LinkKind::HasSubtask    => "has-subtask",
+            &LinkKind::DependsOn     => "depends-on",
+            &LinkKind::DependencyOf  => "dependency-of",
+            &LinkKind::Custom(ref s) => &s[..],
+        }
+    }
+
+    /// Get the kind of the backlink for a link of this kind
+    pub fn inverse(&self) -> LinkKind {
+        match self {
+            &LinkKind::Link          => LinkKind::Link,
+            &LinkKind::References    => LinkKind::ReferencedBy,
+            &LinkKind::ReferencedBy  => LinkKind::References,
+            &LinkKind::SubtaskOf     => LinkKind::HasSubtask,
+            &LinkKind::HasSubtask    => LinkKind::SubtaskOf,
+            &LinkKind::DependsOn     => LinkKind::DependencyOf,
+            &LinkKind::DependencyOf  => LinkKind::DependsOn,
+            &LinkKind::Custom(ref s) => LinkKind::Custom(s.clone()),
+        }
+    }
+
+}
+
+impl Display for LinkKind {
+
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FmtError> {
+        try!(write!(fmt, "{}", self.name()));
+        Ok(())
+    }
+
+}
+
@@ -12,8 +12,10 @@
     while_true,
 )]
 
-extern crate itertools;
+extern crate chrono;
+extern crate hyper;
 #[macro_use] extern crate log;
+extern crate rustc_serialize;
 extern crate toml;
 extern crate semver;
 extern crate url;
@@ -23,8 +25,11 @@ extern crate crypto;
 
 module_entry_path_mod!("links", "0.1.0");
 
+pub mod check;
 pub mod error;
 pub mod external;
+pub mod graph;
 pub mod internal;
+pub mod kind;
 pub mod result;
 
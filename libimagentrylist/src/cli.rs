@@ -1,11 +1,10 @@
 use clap::{Arg, ArgMatches, App, SubCommand};
+use toml::Value;
 
 use libimagstore::store::FileLockEntry;
 
 use result::Result;
-use listers::line::LineLister;
-use listers::path::PathLister;
-use lister::Lister;
+use registry::{ListerArgs, ListerRegistry};
 use error::{ListError, ListErrorKind};
 
 pub fn build_list_cli_component<'a, 'b>() -> App<'a, 'b> {
@@ -14,6 +13,14 @@ pub fn build_list_cli_component<'a, 'b>() -> App<'a, 'b> {
         .version("0.1")
         .about("List entries")
 
+        .arg(Arg::with_name(list_lister())
+             .long("lister")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("NAME")
+             .help("Use the lister with this name"))
+
         .arg(Arg::with_name(list_backend_line())
              .short("l")
              .long("line")
@@ -38,12 +45,41 @@ pub fn build_list_cli_component<'a, 'b>() -> App<'a, 'b> {
              .multiple(false)
              .help("Use backend: Path (absolute)"))
 
+        .arg(Arg::with_name(list_backend_json())
+             .short("j")
+             .long("json")
+             .takes_value(false)
+             .required(false)
+             .multiple(false)
+             .help("Use backend: JSON"))
+
+        .arg(Arg::with_name(list_backend_csv())
+             .long("csv")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("FIELDS")
+             .help("Use backend: CSV, with these header fields as columns (seperate by comma)"))
+
+        .arg(Arg::with_name(list_backend_template())
+             .short("t")
+             .long("template")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("TEMPLATE")
+             .help("Use backend: Template, for example '{{note.name}} [{{imag.tags|join(\",\")}}]'"))
+
 }
 
 pub fn list_subcommand_name() -> &'static str {
     "list"
 }
 
+pub fn list_lister() -> &'static str {
+    "lister"
+}
+
 pub fn list_backend_line() -> &'static str {
     "line"
 }
@@ -56,27 +92,105 @@ pub fn list_backend_path_absolute() -> &'static str {
     "path-absolute"
 }
 
-// TODO: Add Registry for listers where a HashMap name->lister is in and where we can fetch the
-// lister from.
+pub fn list_backend_json() -> &'static str {
+    "json"
+}
+
+pub fn list_backend_csv() -> &'static str {
+    "csv"
+}
+
+pub fn list_backend_template() -> &'static str {
+    "template"
+}
+
+/// Names of the flags for the builtin listers, which equal the names of the listers in the
+/// default `ListerRegistry`
+pub fn list_backend_names() -> Vec<&'static str> {
+    vec![
+        list_backend_line(),
+        list_backend_path(),
+        list_backend_path_absolute(),
+        list_backend_json(),
+        list_backend_csv(),
+        list_backend_template(),
+    ]
+}
+
+/// List the entries with the builtin listers, selected from the commandline
 pub fn list_entries_with_lister<'a, I>(m: &ArgMatches, entries: I) -> Result<()>
     where I: Iterator<Item = FileLockEntry<'a>>
 {
-    if let Some(matches) = m.subcommand_matches(list_subcommand_name()) {
-        if matches.is_present(list_backend_line()) {
-            return LineLister::new("<unknown>").list(entries)
-        };
-
-        if matches.is_present(list_backend_path()) {
-            return PathLister::new(false).list(entries)
-        }
+    list_entries_with_registry(m, &ListerRegistry::default(), None, "", entries)
+}
 
+/// Get the name of the lister selected for listing entries of `module`
+///
+/// The lister is selected by (in this order):
+///
+/// * the `--lister <name>` argument
+/// * one of the shortcut flags for the builtin listers (`--line`, `--json`, ...)
+/// * the "lister" key in the section of `module` in the configuration
+pub fn selected_lister(m: &ArgMatches, config: Option<&Value>, module: &str) -> Option<String> {
+    m.subcommand_matches(list_subcommand_name())
+        .and_then(|matches| {
+            matches.value_of(list_lister())
+                .map(String::from)
+                .or_else(|| {
+                    list_backend_names()
+                        .into_iter()
+                        .filter(|n| matches.is_present(n))
+                        .next()
+                        .map(String::from)
+                })
+        })
+        .or_else(|| {
+            match module_config(config, module) {
+                Some(&Value::Table(ref t)) => match t.get("lister") {
+                    Some(&Value::String(ref s)) => Some(s.clone()),
+                    _ => None,
+                },
+                _ => None,
+            }
+        })
+}
 
-        if matches.is_present(list_backend_path_absolute()) {
-            return PathLister::new(true).list(entries)
+/// List the entries with a lister from the `registry`
+///
+/// The lister is selected with `selected_lister()`. If no lister is selected, nothing is listed.
+pub fn list_entries_with_registry<'a, I>(m: &ArgMatches,
+                                         registry: &ListerRegistry,
+                                         config: Option<&Value>,
+                                         module: &str,
+                                         entries: I)
+    -> Result<()>
+    where I: Iterator<Item = FileLockEntry<'a>>
+{
+    if let Some(matches) = m.subcommand_matches(list_subcommand_name()) {
+        match selected_lister(m, config, module) {
+            Some(name) => {
+                debug!("Listing with lister '{}'", name);
+                let args = ListerArgs {
+                    matches: Some(matches),
+                    config: module_config(config, module),
+                };
+                registry.list(&name[..], &args, entries)
+            },
+            None => {
+                debug!("No lister selected");
+                Ok(())
+            },
         }
-
-        Ok(())
     } else {
         Err(ListError::new(ListErrorKind::CLIError, None))
     }
 }
+
+fn module_config<'a>(config: Option<&'a Value>, module: &str) -> Option<&'a Value> {
+    config.and_then(|c| {
+        match c {
+            &Value::Table(ref t) => t.get(module),
+            _ => None,
+        }
+    })
+}
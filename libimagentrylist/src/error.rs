@@ -12,6 +12,8 @@ pub enum ListErrorKind {
     EntryError,
     IterationError,
     CLIError,
+    NoSuchLister,
+    ListerArgumentMissing,
 }
 
 fn counter_error_type_as_str(err: &ListErrorKind) -> &'static str{
@@ -20,6 +22,8 @@ fn counter_error_type_as_str(err: &ListErrorKind) -> &'static str{
         &ListErrorKind::EntryError     => "EntryError",
         &ListErrorKind::IterationError => "IterationError",
         &ListErrorKind::CLIError       => "No CLI subcommand for listing entries",
+        &ListErrorKind::NoSuchLister   => "No lister with this name",
+        &ListErrorKind::ListerArgumentMissing => "Lister argument missing",
     }
 }
 
@@ -16,13 +16,16 @@
 
 extern crate clap;
 #[macro_use] extern crate log;
+extern crate rustc_serialize;
 extern crate toml;
 
 extern crate libimagstore;
+extern crate libimagentryview;
 
 pub mod cli;
 pub mod error;
 pub mod lister;
 pub mod listers;
+pub mod registry;
 pub mod result;
 
@@ -0,0 +1,91 @@
+use std::io::stdout;
+use std::io::Write;
+
+use toml::Value;
+
+use lister::Lister;
+use result::Result;
+
+use libimagstore::store::FileLockEntry;
+
+/// Lists the entries as CSV, one line per entry.
+///
+/// The first column is always the location of the entry, the other columns are the values of the
+/// header fields passed on construction. A header line with the column names is printed first.
+pub struct CsvLister {
+    fields: Vec<String>,
+    separator: char,
+}
+
+impl CsvLister {
+
+    pub fn new(fields: Vec<String>, separator: char) -> CsvLister {
+        CsvLister {
+            fields: fields,
+            separator: separator,
+        }
+    }
+
+    fn line(&self, cells: Vec<String>) -> String {
+        let sep = self.separator.to_string();
+        cells.into_iter()
+            .map(|c| escape_cell(c, self.separator))
+            .collect::<Vec<String>>()
+            .join(&sep[..])
+    }
+
+}
+
+impl Lister for CsvLister {
+
+    fn list<'a, I: Iterator<Item = FileLockEntry<'a>>>(&self, entries: I) -> Result<()> {
+        use error::ListError as LE;
+        use error::ListErrorKind as LEK;
+
+        let header = {
+            let mut h = vec![String::from("location")];
+            h.extend(self.fields.iter().cloned());
+            self.line(h)
+        };
+
+        let init = write!(stdout(), "{}\n", header)
+            .map_err(|e| LE::new(LEK::FormatError, Some(Box::new(e))));
+
+        entries.fold(init, |accu, entry| {
+            accu.and_then(|_| {
+                let mut cells = vec![
+                    entry.get_location().to_str().map(String::from).unwrap_or(String::new())
+                ];
+
+                for field in self.fields.iter() {
+                    let cell = try!(entry.get_header()
+                        .read(&field[..])
+                        .map_err(|e| LE::new(LEK::EntryError, Some(Box::new(e)))));
+                    cells.push(cell.map(|v| value_to_cell(&v)).unwrap_or(String::new()));
+                }
+
+                write!(stdout(), "{}\n", self.line(cells))
+                    .map_err(|e| LE::new(LEK::FormatError, Some(Box::new(e))))
+            })
+        })
+    }
+
+}
+
+/// Strings are printed raw, arrays are joined by comma, everything else is printed as TOML.
+fn value_to_cell(v: &Value) -> String {
+    match v {
+        &Value::String(ref s) => s.clone(),
+        &Value::Array(ref a)  => a.iter().map(value_to_cell).collect::<Vec<String>>().join(","),
+        other                 => format!("{}", other),
+    }
+}
+
+/// Quote a cell if it contains the separator, a quote or a newline. Quotes are doubled.
+fn escape_cell(cell: String, sep: char) -> String {
+    if cell.contains(sep) || cell.contains('"') || cell.contains('\n') {
+        format!("\"{}\"", cell.replace("\"", "\"\""))
+    } else {
+        cell
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::stdout;
+use std::io::Write;
+
+use rustc_serialize::json::{Json, ToJson};
+
+use lister::Lister;
+use result::Result;
+
+use libimagstore::store::FileLockEntry;
+
+/// Lists the entries as one JSON array of objects, each object containing the location and the
+/// header of the entry, and optionally the content.
+pub struct JsonLister {
+    with_content: bool,
+    pretty: bool,
+}
+
+impl JsonLister {
+
+    pub fn new(with_content: bool, pretty: bool) -> JsonLister {
+        JsonLister {
+            with_content: with_content,
+            pretty: pretty,
+        }
+    }
+
+}
+
+impl Lister for JsonLister {
+
+    fn list<'a, I: Iterator<Item = FileLockEntry<'a>>>(&self, entries: I) -> Result<()> {
+        use error::ListError as LE;
+        use error::ListErrorKind as LEK;
+
+        let entries = entries
+            .map(|entry| {
+                let mut json = entry.to_json();
+                if !self.with_content {
+                    if let Json::Object(ref mut obj) = json {
+                        obj.remove("content");
+                    }
+                }
+                json
+            })
+            .collect();
+        let json = Json::Array(entries);
+
+        if self.pretty {
+            write!(stdout(), "{}\n", json.pretty())
+        } else {
+            write!(stdout(), "{}\n", json)
+        }
+        .map_err(|e| LE::new(LEK::FormatError, Some(Box::new(e))))
+    }
+
+}
@@ -1,3 +1,6 @@
 pub mod core;
+pub mod csv;
+pub mod json;
 pub mod line;
 pub mod path;
+pub mod template;
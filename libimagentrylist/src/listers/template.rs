@@ -0,0 +1,44 @@
+use std::io::stdout;
+use std::io::Write;
+
+use lister::Lister;
+use result::Result;
+
+use libimagstore::store::FileLockEntry;
+use libimagentryview::template::Template;
+
+/// Lists the entries formatted by a template, one line per entry.
+pub struct TemplateLister {
+    template: Template,
+}
+
+impl TemplateLister {
+
+    pub fn new(template: Template) -> TemplateLister {
+        TemplateLister {
+            template: template,
+        }
+    }
+
+}
+
+impl Lister for TemplateLister {
+
+    fn list<'a, I: Iterator<Item = FileLockEntry<'a>>>(&self, entries: I) -> Result<()> {
+        use error::ListError as LE;
+        use error::ListErrorKind as LEK;
+
+        entries.fold(Ok(()), |accu, entry| {
+            accu.and_then(|_| {
+                    self.template
+                        .render(&entry)
+                        .map_err(|e| LE::new(LEK::FormatError, Some(Box::new(e))))
+                })
+                .and_then(|line| {
+                    write!(stdout(), "{}\n", line)
+                        .map_err(|e| LE::new(LEK::FormatError, Some(Box::new(e))))
+                })
+            })
+    }
+
+}
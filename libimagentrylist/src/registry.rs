@@ -0,0 +1,264 @@
+//! A registry of listers, where listers can be fetched by name
+//!
+//! Each lister is registered with a function which instantiates the lister and lists the entries
+//! with it. The function gets the `ListerArgs`, which contain the commandline matches of the
+//! "list" subcommand and the configuration section of the module which lists, so the lister can
+//! be configured from there.
+//!
+//! Other crates can add their own listers:
+//!
+//! ```ignore
+//! fn list_with_my_lister(args: &ListerArgs, entries: &mut Iterator<Item = FileLockEntry>)
+//!     -> Result<()>
+//! {
+//!     MyLister::new().list(entries)
+//! }
+//!
+//! let mut registry = ListerRegistry::default();
+//! registry.register("mine", Box::new(list_with_my_lister));
+//! ```
+
+use std::collections::BTreeMap;
+
+use clap::ArgMatches;
+use toml::Value;
+
+use libimagstore::store::FileLockEntry;
+use libimagentryview::template::Template;
+
+use error::{ListError, ListErrorKind};
+use lister::Lister;
+use listers::csv::CsvLister;
+use listers::json::JsonLister;
+use listers::line::LineLister;
+use listers::path::PathLister;
+use listers::template::TemplateLister;
+use result::Result;
+
+/// Arguments for instantiating a lister
+pub struct ListerArgs<'a> {
+    /// The matches of the "list" subcommand, if any
+    pub matches: Option<&'a ArgMatches<'a>>,
+
+    /// The configuration section of the module which lists entries, if any
+    pub config: Option<&'a Value>,
+}
+
+impl<'a> ListerArgs<'a> {
+
+    /// Get a value for the lister, either from the commandline argument `arg` or, if that is not
+    /// present, from the key `key` in the module configuration.
+    pub fn get(&self, arg: &str, key: &str) -> Option<String> {
+        self.matches
+            .and_then(|m| m.value_of(arg))
+            .map(String::from)
+            .or_else(|| {
+                match self.config {
+                    Some(&Value::Table(ref t)) => match t.get(key) {
+                        Some(&Value::String(ref s)) => Some(s.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            })
+    }
+
+}
+
+/// Function which instantiates a lister from the `ListerArgs` and lists the entries with it
+pub type ListFn = Box<Fn(&ListerArgs, &mut Iterator<Item = FileLockEntry>) -> Result<()>>;
+
+pub struct ListerRegistry {
+    listers: BTreeMap<String, ListFn>,
+}
+
+impl ListerRegistry {
+
+    /// Create an empty registry. Use `ListerRegistry::default()` for a registry with the builtin
+    /// listers.
+    pub fn new() -> ListerRegistry {
+        ListerRegistry {
+            listers: BTreeMap::new(),
+        }
+    }
+
+    /// Register a lister. An already registered lister with the same name is replaced.
+    ///
+    /// Returns true if a lister was replaced.
+    pub fn register(&mut self, name: &str, f: ListFn) -> bool {
+        debug!("Registering lister: '{}'", name);
+        self.listers.insert(String::from(name), f).is_some()
+    }
+
+    pub fn has_lister(&self, name: &str) -> bool {
+        self.listers.contains_key(name)
+    }
+
+    /// Get the names of all registered listers, in alphabetical order
+    pub fn names(&self) -> Vec<&str> {
+        self.listers.keys().map(|s| &s[..]).collect()
+    }
+
+    /// List the `entries` with the lister registered for `name`
+    pub fn list<'a, I>(&self, name: &str, args: &ListerArgs, mut entries: I) -> Result<()>
+        where I: Iterator<Item = FileLockEntry<'a>>
+    {
+        match self.listers.get(name) {
+            Some(f) => f(args, &mut entries),
+            None    => {
+                debug!("No lister registered for '{}'", name);
+                Err(ListError::new(ListErrorKind::NoSuchLister, None))
+            },
+        }
+    }
+
+}
+
+impl Default for ListerRegistry {
+
+    /// A registry with all builtin listers of this library
+    fn default() -> ListerRegistry {
+        let mut reg = ListerRegistry::new();
+        reg.register("line", Box::new(list_line));
+        reg.register("path", Box::new(list_path));
+        reg.register("path-absolute", Box::new(list_path_absolute));
+        reg.register("json", Box::new(list_json));
+        reg.register("csv", Box::new(list_csv));
+        reg.register("template", Box::new(list_template));
+        reg
+    }
+
+}
+
+fn list_line(_: &ListerArgs, entries: &mut Iterator<Item = FileLockEntry>) -> Result<()> {
+    LineLister::new("<unknown>").list(entries)
+}
+
+fn list_path(_: &ListerArgs, entries: &mut Iterator<Item = FileLockEntry>) -> Result<()> {
+    PathLister::new(false).list(entries)
+}
+
+fn list_path_absolute(_: &ListerArgs, entries: &mut Iterator<Item = FileLockEntry>) -> Result<()> {
+    PathLister::new(true).list(entries)
+}
+
+fn list_json(_: &ListerArgs, entries: &mut Iterator<Item = FileLockEntry>) -> Result<()> {
+    JsonLister::new(false, true).list(entries)
+}
+
+/// The columns are taken from `--csv` or from the "list-csv-fields" configuration key
+fn list_csv(args: &ListerArgs, entries: &mut Iterator<Item = FileLockEntry>) -> Result<()> {
+    let fields = try!(args.get("csv", "list-csv-fields")
+        .ok_or(ListError::new(ListErrorKind::ListerArgumentMissing, None)));
+    let fields = fields.split(",").map(|s| String::from(s.trim())).collect();
+    CsvLister::new(fields, ',').list(entries)
+}
+
+/// The template is taken from `--template` or from the "list-template" configuration key
+fn list_template(args: &ListerArgs, entries: &mut Iterator<Item = FileLockEntry>) -> Result<()> {
+    let template = try!(args.get("template", "list-template")
+        .ok_or(ListError::new(ListErrorKind::ListerArgumentMissing, None)));
+    Template::parse(&template[..])
+        .map_err(|e| ListError::new(ListErrorKind::FormatError, Some(Box::new(e))))
+        .and_then(|t| TemplateLister::new(t).list(entries))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use clap::App;
+    use toml::Value;
+
+    use libimagstore::store::FileLockEntry;
+
+    use cli::build_list_cli_component;
+    use error::{ListError, ListErrorKind};
+    use result::Result;
+    use super::{ListerArgs, ListerRegistry};
+
+    fn no_entries<'a>() -> ::std::vec::IntoIter<FileLockEntry<'a>> {
+        vec![].into_iter()
+    }
+
+    fn config() -> Value {
+        let mut t = BTreeMap::new();
+        t.insert(String::from("list-csv-fields"), Value::String(String::from("from,config")));
+        Value::Table(t)
+    }
+
+    #[test]
+    fn test_default_listers() {
+        let reg = ListerRegistry::default();
+        assert_eq!(vec!["csv", "json", "line", "path", "path-absolute", "template"], reg.names());
+        assert!(reg.has_lister("json"));
+        assert!(!reg.has_lister("nonexistent"));
+    }
+
+    #[test]
+    fn test_no_such_lister() {
+        let reg  = ListerRegistry::default();
+        let args = ListerArgs { matches: None, config: None };
+        let res  = reg.list("nonexistent", &args, no_entries());
+        assert_eq!(ListErrorKind::NoSuchLister, res.unwrap_err().err_type());
+    }
+
+    #[test]
+    fn test_lister_argument_missing() {
+        let reg  = ListerRegistry::default();
+        let args = ListerArgs { matches: None, config: None };
+
+        let res = reg.list("csv", &args, no_entries());
+        assert_eq!(ListErrorKind::ListerArgumentMissing, res.unwrap_err().err_type());
+
+        let res = reg.list("template", &args, no_entries());
+        assert_eq!(ListErrorKind::ListerArgumentMissing, res.unwrap_err().err_type());
+    }
+
+    fn list_ok(_: &ListerArgs, _: &mut Iterator<Item = FileLockEntry>) -> Result<()> {
+        Ok(())
+    }
+
+    fn list_err(_: &ListerArgs, _: &mut Iterator<Item = FileLockEntry>) -> Result<()> {
+        Err(ListError::new(ListErrorKind::FormatError, None))
+    }
+
+    #[test]
+    fn test_register_and_replace() {
+        let mut reg = ListerRegistry::new();
+        assert!(!reg.register("mine", Box::new(list_ok)));
+        assert!(reg.register("mine", Box::new(list_err)));
+        assert_eq!(vec!["mine"], reg.names());
+
+        let args = ListerArgs { matches: None, config: None };
+        let res  = reg.list("mine", &args, no_entries());
+        assert_eq!(ListErrorKind::FormatError, res.unwrap_err().err_type());
+    }
+
+    #[test]
+    fn test_get_prefers_cli_over_config() {
+        let app     = App::new("test").subcommand(build_list_cli_component());
+        let matches = app.get_matches_from(vec!["test", "list", "--csv", "from,cli"]);
+        let matches = matches.subcommand_matches("list").unwrap();
+        let config  = config();
+
+        let args = ListerArgs { matches: Some(matches), config: Some(&config) };
+        assert_eq!(Some(String::from("from,cli")), args.get("csv", "list-csv-fields"));
+    }
+
+    #[test]
+    fn test_get_falls_back_to_config() {
+        let app     = App::new("test").subcommand(build_list_cli_component());
+        let matches = app.get_matches_from(vec!["test", "list"]);
+        let matches = matches.subcommand_matches("list").unwrap();
+        let config  = config();
+
+        let args = ListerArgs { matches: Some(matches), config: Some(&config) };
+        assert_eq!(Some(String::from("from,config")), args.get("csv", "list-csv-fields"));
+        assert_eq!(None, args.get("template", "list-template"));
+
+        let args = ListerArgs { matches: None, config: None };
+        assert_eq!(None, args.get("csv", "list-csv-fields"));
+    }
+
+}
@@ -0,0 +1,146 @@
+//! Tag aliases
+//!
+//! Aliases are configured in the `[tag.aliases]` section of the configuration:
+//!
+//! ```ignore
+//! [tag.aliases]
+//! todo = "task"
+//! ```
+//!
+//! Programs which add tags read the aliases with `aliases_from_config()` and pass them to
+//! `Tagable::add_tag_with_aliases()`, so adding the tag "todo" to an entry adds the tag "task".
+
+use std::collections::BTreeMap;
+
+use toml::Value;
+
+use error::{TagError, TagErrorKind};
+use result::Result;
+use tag::Tag;
+use util::is_tag;
+
+/// Read the aliases from the `[tag.aliases]` section of the configuration
+pub fn aliases_from_config(config: Option<&Value>) -> Result<BTreeMap<Tag, Tag>> {
+    let section = match config {
+        Some(&Value::Table(ref t)) => {
+            match t.get("tag") {
+                Some(&Value::Table(ref tag)) => tag.get("aliases"),
+                _ => None,
+            }
+        },
+        _ => None,
+    };
+
+    let mut aliases = BTreeMap::new();
+    match section {
+        Some(&Value::Table(ref t)) => {
+            for (alias, tag) in t.iter() {
+                match tag {
+                    &Value::String(ref tag) if is_tag(alias) && is_tag(tag) => {
+                        aliases.insert(alias.clone(), tag.clone());
+                    },
+                    _ => {
+                        debug!("Invalid tag alias: '{}' -> {:?}", alias, tag);
+                        return Err(TagError::new(TagErrorKind::AliasConfigError, None));
+                    },
+                }
+            }
+        },
+        Some(_) => return Err(TagError::new(TagErrorKind::AliasConfigError, None)),
+        None    => debug!("No tag aliases configured"),
+    }
+
+    Ok(aliases)
+}
+
+/// Get the tag the alias stands for, or the tag itself if it is not an alias
+///
+/// Aliases of aliases are resolved, too. If the aliases form a cycle, the last tag before the
+/// cycle is closed is returned.
+pub fn resolve_alias(aliases: &BTreeMap<Tag, Tag>, t: Tag) -> Tag {
+    let mut seen = vec![];
+    let mut current = t;
+    while let Some(next) = aliases.get(&current) {
+        seen.push(current.clone());
+        if seen.contains(next) {
+            warn!("Tag aliases form a cycle: {:?}", seen);
+            break;
+        }
+        current = next.clone();
+    }
+
+    debug!("Resolved tag alias: {:?} -> '{}'", seen.first(), current);
+    current
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use toml::Value;
+
+    use super::{aliases_from_config, resolve_alias};
+
+    fn config(aliases: Vec<(&str, Value)>) -> Value {
+        let aliases = aliases.into_iter().map(|(k, v)| (String::from(k), v)).collect();
+        let mut tag = BTreeMap::new();
+        tag.insert(String::from("aliases"), Value::Table(aliases));
+        let mut config = BTreeMap::new();
+        config.insert(String::from("tag"), Value::Table(tag));
+        Value::Table(config)
+    }
+
+    fn s(s: &str) -> Value {
+        Value::String(String::from(s))
+    }
+
+    fn aliases(pairs: Vec<(&str, &str)>) -> BTreeMap<String, String> {
+        pairs.into_iter().map(|(a, t)| (String::from(a), String::from(t))).collect()
+    }
+
+    #[test]
+    fn test_aliases_from_config() {
+        let cfg = config(vec![("todo", s("task")), ("wp", s("work/projectx"))]);
+        assert_eq!(aliases(vec![("todo", "task"), ("wp", "work/projectx")]),
+                   aliases_from_config(Some(&cfg)).unwrap());
+    }
+
+    #[test]
+    fn test_aliases_from_config_missing() {
+        assert!(aliases_from_config(None).unwrap().is_empty());
+        assert!(aliases_from_config(Some(&Value::Table(BTreeMap::new()))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_aliases_from_config_invalid() {
+        assert!(aliases_from_config(Some(&config(vec![("todo", Value::Integer(1))]))).is_err());
+        assert!(aliases_from_config(Some(&config(vec![("todo", s("my task"))]))).is_err());
+        assert!(aliases_from_config(Some(&config(vec![("2do", s("task"))]))).is_err());
+
+        let mut tag = BTreeMap::new();
+        tag.insert(String::from("aliases"), s("todo"));
+        let mut cfg = BTreeMap::new();
+        cfg.insert(String::from("tag"), Value::Table(tag));
+        assert!(aliases_from_config(Some(&Value::Table(cfg))).is_err());
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        let a = aliases(vec![("todo", "task"), ("task", "work/task")]);
+        assert_eq!("work/task", resolve_alias(&a, String::from("todo")));
+        assert_eq!("work/task", resolve_alias(&a, String::from("task")));
+        assert_eq!("other", resolve_alias(&a, String::from("other")));
+        assert_eq!("todo", resolve_alias(&BTreeMap::new(), String::from("todo")));
+    }
+
+    #[test]
+    fn test_resolve_alias_cycle() {
+        let a = aliases(vec![("a", "b"), ("b", "c"), ("c", "a")]);
+        assert_eq!("c", resolve_alias(&a, String::from("a")));
+        assert_eq!("a", resolve_alias(&a, String::from("b")));
+
+        let a = aliases(vec![("a", "a")]);
+        assert_eq!("a", resolve_alias(&a, String::from("a")));
+    }
+
+}
@@ -9,6 +9,7 @@ pub enum TagErrorKind {
     HeaderReadError,
     HeaderWriteError,
     NotATag,
+    AliasConfigError,
 }
 
 fn tag_error_type_as_str(e: &TagErrorKind) -> &'static str {
@@ -17,6 +18,7 @@ fn tag_error_type_as_str(e: &TagErrorKind) -> &'static str {
         &TagErrorKind::HeaderReadError  => "Error while reading entry header",
         &TagErrorKind::HeaderWriteError => "Error while writing entry header",
         &TagErrorKind::NotATag          => "String is not a tag",
+        &TagErrorKind::AliasConfigError => "Tag aliases are not configured properly",
     }
 }
 
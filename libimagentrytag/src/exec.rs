@@ -1,15 +1,24 @@
+use std::collections::BTreeMap;
+
 use clap::ArgMatches;
 
 use libimagstore::store::FileLockEntry;
 
 use result::Result;
+use tag::Tag;
 use tagable::*;
 use ui::{get_add_tags, get_remove_tags};
 
-pub fn exec_cli_for_entry(matches: &ArgMatches, entry: &mut FileLockEntry) -> Result<()> {
+/// Add and remove the tags passed on the commandline, the added tags with their `aliases`
+/// resolved
+pub fn exec_cli_for_entry(matches: &ArgMatches,
+                          entry: &mut FileLockEntry,
+                          aliases: &BTreeMap<Tag, Tag>)
+    -> Result<()>
+{
     match get_add_tags(matches) {
         Some(ts) => for t in ts {
-            if let Err(e) = entry.add_tag(t) {
+            if let Err(e) = entry.add_tag_with_aliases(t, aliases) {
                 return Err(e);
             }
         },
@@ -20,9 +20,11 @@ extern crate toml;
 
 extern crate libimagstore;
 
+pub mod alias;
 pub mod error;
 pub mod exec;
 pub mod result;
+pub mod stats;
 pub mod tag;
 pub mod tagable;
 pub mod util;
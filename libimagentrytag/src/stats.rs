@@ -0,0 +1,302 @@
+//! Tag statistics
+//!
+//! Statistics about how often tags are used in the store and which tags are used together, which
+//! are used to suggest tags for an entry.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use libimagstore::store::Store;
+use libimagstore::store::StoreObject;
+
+use tag::Tag;
+use tagable::Tagable;
+
+/// How often tags are used and how often two tags are used on the same entry
+#[derive(Debug, Clone)]
+pub struct TagStatistics {
+    entries: usize,
+    counts: BTreeMap<Tag, usize>,
+    cooccurrences: BTreeMap<(Tag, Tag), usize>,
+}
+
+impl TagStatistics {
+
+    pub fn new() -> TagStatistics {
+        TagStatistics {
+            entries: 0,
+            counts: BTreeMap::new(),
+            cooccurrences: BTreeMap::new(),
+        }
+    }
+
+    /// Build the statistics from all entries in the store
+    ///
+    /// Entries which cannot be read and entries with invalid tags are skipped.
+    pub fn from_store(store: &Store) -> TagStatistics {
+        let mut stats = TagStatistics::new();
+
+        for obj in store.walk("") {
+            let id = match obj {
+                StoreObject::Id(id) => id,
+                StoreObject::Collection(_) => continue,
+            };
+
+            let entry = match store.retrieve_copy(id.clone()) {
+                Ok(entry) => entry,
+                Err(e)    => {
+                    warn!("Skipping {:?}, could not read it: {:?}", id, e);
+                    continue;
+                },
+            };
+
+            match entry.get_tags() {
+                Ok(tags) => stats.add_tags(&tags),
+                Err(e)   => debug!("Skipping {:?}, could not read tags: {:?}", id, e),
+            }
+        }
+
+        stats
+    }
+
+    /// Add the tags of one entry to the statistics
+    pub fn add_tags(&mut self, tags: &[Tag]) {
+        let tags : BTreeSet<&Tag> = tags.iter().collect();
+
+        self.entries += 1;
+        for tag in tags.iter() {
+            *self.counts.entry((*tag).clone()).or_insert(0) += 1;
+        }
+
+        for a in tags.iter() {
+            for b in tags.iter().filter(|b| a < *b) {
+                let key = ((*a).clone(), (*b).clone());
+                *self.cooccurrences.entry(key).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// The number of entries the statistics were built from
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    /// All tags which are used, in alphabetical order
+    pub fn tags(&self) -> Vec<&Tag> {
+        self.counts.keys().collect()
+    }
+
+    /// The number of entries which are tagged with `tag`
+    pub fn count(&self, tag: &Tag) -> usize {
+        self.counts.get(tag).cloned().unwrap_or(0)
+    }
+
+    /// The number of entries which are tagged with both `a` and `b`
+    pub fn cooccurrence(&self, a: &Tag, b: &Tag) -> usize {
+        let key = if a < b { (a.clone(), b.clone()) } else { (b.clone(), a.clone()) };
+        self.cooccurrences.get(&key).cloned().unwrap_or(0)
+    }
+
+    /// The tags which are used together with `tag` and how often, the most frequent first
+    pub fn cooccurring(&self, tag: &Tag) -> Vec<(Tag, usize)> {
+        let mut tags : Vec<(Tag, usize)> = self.cooccurrences
+            .iter()
+            .filter_map(|(&(ref a, ref b), n)| {
+                if a == tag {
+                    Some((b.clone(), *n))
+                } else if b == tag {
+                    Some((a.clone(), *n))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        tags.sort_by(|&(ref ta, na), &(ref tb, nb)| {
+            match nb.cmp(&na) {
+                Ordering::Equal => ta.cmp(tb),
+                other => other,
+            }
+        });
+        tags
+    }
+
+    /// Suggest at most `n` tags for an entry which is tagged with `tags` and has `content`, the
+    /// best suggestion first
+    ///
+    /// A tag is suggested if it is used together with the tags of the entry, or if a word in the
+    /// content is the tag (or, for hierarchical tags, the last level of the tag). Tags the entry
+    /// has already are not suggested.
+    pub fn suggest(&self, tags: &[Tag], content: &str, n: usize) -> Vec<Tag> {
+        let words : BTreeSet<String> = content
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_lowercase())
+            .collect();
+
+        let mut scored : Vec<(&Tag, f64)> = self.counts
+            .keys()
+            .filter(|candidate| !tags.contains(*candidate))
+            .map(|candidate| {
+                // How likely an entry with one of the tags has the candidate, summed up
+                let mut score = tags.iter()
+                    .filter(|t| self.count(t) > 0)
+                    .map(|t| self.cooccurrence(t, candidate) as f64 / self.count(t) as f64)
+                    .fold(0.0, |acc, x| acc + x);
+
+                let last_level = candidate.rsplit('/').next().unwrap_or(&candidate[..]);
+                if words.contains(&candidate.to_lowercase()) ||
+                    words.contains(&last_level.to_lowercase())
+                {
+                    score += 1.0;
+                }
+
+                (candidate, score)
+            })
+            .filter(|&(_, score)| score > 0.0)
+            .collect();
+
+        scored.sort_by(|&(ta, sa), &(tb, sb)| {
+            match sb.partial_cmp(&sa).unwrap_or(Ordering::Equal) {
+                Ordering::Equal => match self.count(tb).cmp(&self.count(ta)) {
+                    Ordering::Equal => ta.cmp(tb),
+                    other => other,
+                },
+                other => other,
+            }
+        });
+
+        scored.into_iter().take(n).map(|(t, _)| t.clone()).collect()
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::{File, create_dir_all, remove_dir_all};
+    use std::io::Write;
+
+    use toml::Value;
+
+    use libimagstore::store::Store;
+    use libimagstore::storeid::StoreId;
+
+    use super::TagStatistics;
+
+    fn t(s: &str) -> String {
+        String::from(s)
+    }
+
+    fn tags(tags: Vec<&str>) -> Vec<String> {
+        tags.into_iter().map(String::from).collect()
+    }
+
+    /// Five entries: "rust" is used with "programming" three times and with "imag" once
+    fn stats() -> TagStatistics {
+        let mut stats = TagStatistics::new();
+        stats.add_tags(&tags(vec!["rust", "programming"]));
+        stats.add_tags(&tags(vec!["rust", "programming", "work/projectx"]));
+        stats.add_tags(&tags(vec!["programming", "rust"]));
+        stats.add_tags(&tags(vec!["rust", "imag"]));
+        stats.add_tags(&tags(vec!["garden"]));
+        stats
+    }
+
+    #[test]
+    fn test_counts() {
+        let s = stats();
+        assert_eq!(5, s.entries());
+        assert_eq!(4, s.count(&t("rust")));
+        assert_eq!(0, s.count(&t("unused")));
+        assert_eq!(vec!["garden", "imag", "programming", "rust", "work/projectx"], s.tags());
+    }
+
+    #[test]
+    fn test_duplicate_tags_counted_once() {
+        let mut s = TagStatistics::new();
+        s.add_tags(&tags(vec!["rust", "rust", "imag"]));
+        assert_eq!(1, s.count(&t("rust")));
+        assert_eq!(1, s.cooccurrence(&t("rust"), &t("imag")));
+        assert_eq!(0, s.cooccurrence(&t("rust"), &t("rust")));
+    }
+
+    #[test]
+    fn test_cooccurrence_is_symmetric() {
+        let s = stats();
+        assert_eq!(3, s.cooccurrence(&t("rust"), &t("programming")));
+        assert_eq!(3, s.cooccurrence(&t("programming"), &t("rust")));
+        assert_eq!(1, s.cooccurrence(&t("work/projectx"), &t("rust")));
+        assert_eq!(1, s.cooccurrence(&t("rust"), &t("work/projectx")));
+        assert_eq!(0, s.cooccurrence(&t("garden"), &t("rust")));
+    }
+
+    #[test]
+    fn test_cooccurring_ordering() {
+        let s = stats();
+        // Most frequent first, equally frequent in alphabetical order
+        assert_eq!(vec![(t("programming"), 3), (t("imag"), 1), (t("work/projectx"), 1)],
+                   s.cooccurring(&t("rust")));
+        assert!(s.cooccurring(&t("garden")).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_from_cooccurrence() {
+        let s = stats();
+        assert_eq!(vec![t("rust"), t("work/projectx")],
+                   s.suggest(&tags(vec!["programming"]), "", 10));
+    }
+
+    #[test]
+    fn test_suggest_excludes_existing_tags() {
+        let s = stats();
+        let suggestions = s.suggest(&tags(vec!["rust", "programming"]), "rust and programming", 10);
+        assert!(!suggestions.contains(&t("rust")));
+        assert!(!suggestions.contains(&t("programming")));
+    }
+
+    #[test]
+    fn test_suggest_from_content() {
+        let s = stats();
+        assert_eq!(vec![t("garden")], s.suggest(&[], "Planting in the Garden today", 10));
+
+        // Matched against the last level of hierarchical tags
+        assert_eq!(vec![t("work/projectx")], s.suggest(&[], "Notes on projectx.", 10));
+        assert!(s.suggest(&[], "Notes on work.", 10).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_limit() {
+        let s = stats();
+        assert_eq!(vec![t("programming")], s.suggest(&tags(vec!["rust"]), "", 1));
+        assert_eq!(3, s.suggest(&tags(vec!["rust"]), "", 10).len());
+        assert!(s.suggest(&tags(vec!["rust"]), "", 0).is_empty());
+    }
+
+    #[test]
+    fn test_from_store_skips_unreadable_entries() {
+        let mut path = env::temp_dir();
+        path.push("imag-tag-stats-test");
+        let _ = remove_dir_all(&path);
+        create_dir_all(&path).unwrap();
+
+        let mut broken = path.clone();
+        broken.push("broken~0.1.0");
+        File::create(&broken).unwrap().write_all(b"no header").unwrap();
+
+        let store = Store::new(path, None).unwrap();
+        {
+            let mut entry = store.create(StoreId::from(store.path().join("tagged~0.1.0")))
+                .unwrap();
+            entry.get_header_mut()
+                .set("imag.tags", Value::Array(vec![Value::String(t("rust"))]))
+                .unwrap();
+        }
+
+        let s = TagStatistics::from_store(&store);
+        assert_eq!(1, s.entries());
+        assert_eq!(1, s.count(&t("rust")));
+    }
+
+}
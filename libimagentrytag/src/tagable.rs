@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::ops::Deref;
 use std::ops::DerefMut;
 
@@ -5,6 +6,7 @@ use itertools::Itertools;
 
 use libimagstore::store::{Entry, EntryHeader, FileLockEntry};
 
+use alias::resolve_alias;
 use error::{TagError, TagErrorKind};
 use result::Result;
 use tag::Tag;
@@ -20,6 +22,11 @@ pub trait Tagable {
     fn add_tag(&mut self, t: Tag) -> Result<()>;
     fn remove_tag(&mut self, t: Tag) -> Result<()>;
 
+    /// Add a tag, or the tag it is an alias for, see `alias::resolve_alias()`
+    fn add_tag_with_aliases(&mut self, t: Tag, aliases: &BTreeMap<Tag, Tag>) -> Result<()> {
+        self.add_tag(resolve_alias(aliases, t))
+    }
+
     fn has_tag(&self, t: &Tag) -> Result<bool>;
     fn has_tags(&self, ts: &Vec<Tag>) -> Result<bool>;
 
@@ -103,25 +110,7 @@ impl Tagable for EntryHeader {
     }
 
     fn has_tag(&self, t: &Tag) -> Result<bool> {
-        let tags = self.read("imag.tags");
-        if tags.is_err() {
-            let kind = TagErrorKind::HeaderReadError;
-            return Err(TagError::new(kind, Some(Box::new(tags.unwrap_err()))));
-        }
-        let tags = tags.unwrap();
-
-        if !tags.iter().all(|t| match t { &Value::String(_) => true, _ => false }) {
-            return Err(TagError::new(TagErrorKind::TagTypeError, None));
-        }
-
-        Ok(tags
-           .iter()
-           .any(|tag| {
-               match tag {
-                   &Value::String(ref s) => { s == t },
-                   _ => unreachable!()
-               }
-           }))
+        self.get_tags().map(|tags| tags.iter().any(|tag| tag == t))
     }
 
     fn has_tags(&self, tags: &Vec<Tag>) -> Result<bool> {
@@ -197,3 +186,30 @@ impl<'a> Tagable for FileLockEntry<'a> {
 
 }
 
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+
+    use super::Tagable;
+
+    #[test]
+    fn test_add_tag_with_aliases() {
+        let mut aliases = BTreeMap::new();
+        aliases.insert(String::from("todo"), String::from("task"));
+
+        let mut e = Entry::new(StoreId::from(PathBuf::from("/test~0.1.0")));
+        e.add_tag_with_aliases(String::from("todo"), &aliases).unwrap();
+        e.add_tag_with_aliases(String::from("work"), &aliases).unwrap();
+        e.add_tag(String::from("todo")).unwrap();
+
+        assert_eq!(vec![String::from("task"), String::from("work"), String::from("todo")],
+                   e.get_tags().unwrap());
+        assert!(e.add_tag_with_aliases(String::from("my tag"), &aliases).is_err());
+    }
+
+}
@@ -56,6 +56,21 @@ pub fn tag_argument_name() -> &'static str {
     "specify-tags"
 }
 
+/// Generates a clap::Arg which can be integrated into the commandline-ui builder for building a
+/// "--suggest-tags" argument, to ask the user which of the suggested tags should be added, see
+/// `stats::TagStatistics::suggest()`
+pub fn tag_suggest_argument<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(tag_suggest_argument_name())
+        .long("suggest-tags")
+        .takes_value(false)
+        .required(false)
+        .help("Suggest tags based on the tags used in the store and the content")
+}
+
+pub fn tag_suggest_argument_name() -> &'static str {
+    "suggest-tags"
+}
+
 /// Get the tags which should be added from the commandline
 ///
 /// Returns none if the argument was not specified
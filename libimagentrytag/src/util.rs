@@ -1,5 +1,105 @@
 use regex::Regex;
 
+use tag::Tag;
+
+/// Check whether a string is a tag
+///
+/// Tags can be hierarchical, the levels are seperated by a slash, as in "work/projectx". Each
+/// level starts with a letter, followed by letters, digits, '_' or '-'.
 pub fn is_tag(s: &String) -> bool {
-    Regex::new("^[a-zA-Z]([a-zA-Z0-9_-]*)$").unwrap().captures(&s[..]).is_some()
+    Regex::new("^[a-zA-Z][a-zA-Z0-9_-]*(/[a-zA-Z][a-zA-Z0-9_-]*)*$")
+        .unwrap()
+        .captures(&s[..])
+        .is_some()
+}
+
+/// Check whether `tag` is `ancestor` itself or below `ancestor` in the tag hierarchy
+///
+/// "work/projectx" is a descendant of "work", but "workshop" is not.
+pub fn is_descendant_of(tag: &Tag, ancestor: &Tag) -> bool {
+    tag == ancestor || (tag.starts_with(&ancestor[..]) && tag[ancestor.len()..].starts_with("/"))
+}
+
+/// Get the parent of the tag in the hierarchy, "work" for "work/projectx"
+pub fn parent(tag: &Tag) -> Option<Tag> {
+    tag.rfind('/').map(|i| String::from(&tag[..i]))
+}
+
+/// Get all ancestors of the tag, from the top level down: "work" and "work/projectx" for
+/// "work/projectx/meetings"
+pub fn ancestors(tag: &Tag) -> Vec<Tag> {
+    let mut ancestors = vec![];
+    let mut current = parent(tag);
+    while let Some(t) = current {
+        current = parent(&t);
+        ancestors.push(t);
+    }
+    ancestors.reverse();
+    ancestors
+}
+
+/// Rename `tag`, if it is `old` or below `old` in the hierarchy: renaming "work" to "job" renames
+/// "work/projectx" to "job/projectx". Returns None if the tag is not affected.
+pub fn rename_tag(tag: &Tag, old: &Tag, new: &Tag) -> Option<Tag> {
+    if is_descendant_of(tag, old) {
+        Some(format!("{}{}", new, &tag[old.len()..]))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ancestors, is_descendant_of, is_tag, parent, rename_tag};
+
+    fn t(s: &str) -> String {
+        String::from(s)
+    }
+
+    #[test]
+    fn test_is_tag() {
+        assert!(is_tag(&t("work")));
+        assert!(is_tag(&t("work/projectx")));
+        assert!(is_tag(&t("work/project-x/meeting_notes2")));
+
+        assert!(!is_tag(&t("")));
+        assert!(!is_tag(&t("2016")));
+        assert!(!is_tag(&t("C++")));
+        assert!(!is_tag(&t("my tag")));
+        assert!(!is_tag(&t("/work")));
+        assert!(!is_tag(&t("work/")));
+        assert!(!is_tag(&t("work//projectx")));
+        assert!(!is_tag(&t("work/2016")));
+    }
+
+    #[test]
+    fn test_is_descendant_of() {
+        assert!(is_descendant_of(&t("work"), &t("work")));
+        assert!(is_descendant_of(&t("work/projectx"), &t("work")));
+        assert!(is_descendant_of(&t("work/projectx/meetings"), &t("work")));
+
+        assert!(!is_descendant_of(&t("workshop"), &t("work")));
+        assert!(!is_descendant_of(&t("work"), &t("work/projectx")));
+        assert!(!is_descendant_of(&t("home/work"), &t("work")));
+    }
+
+    #[test]
+    fn test_ancestors() {
+        assert_eq!(None, parent(&t("work")));
+        assert_eq!(Some(t("work/projectx")), parent(&t("work/projectx/meetings")));
+
+        assert!(ancestors(&t("work")).is_empty());
+        assert_eq!(vec![t("work"), t("work/projectx")], ancestors(&t("work/projectx/meetings")));
+    }
+
+    #[test]
+    fn test_rename_tag() {
+        assert_eq!(Some(t("job")), rename_tag(&t("work"), &t("work"), &t("job")));
+        assert_eq!(Some(t("job/projectx")), rename_tag(&t("work/projectx"), &t("work"), &t("job")));
+        assert_eq!(Some(t("work/x/projectx")),
+                   rename_tag(&t("work/projectx"), &t("work"), &t("work/x")));
+        assert_eq!(None, rename_tag(&t("workshop"), &t("work"), &t("job")));
+        assert_eq!(None, rename_tag(&t("home"), &t("work"), &t("job")));
+    }
+
 }
@@ -0,0 +1,48 @@
+use rustc_serialize::json::{Json, ToJson};
+
+use libimagstore::store::Entry;
+
+use viewer::Viewer;
+use result::Result;
+
+/// Prints an entry as JSON object, with the keys "location", "header" and "content"
+pub struct JsonViewer {
+    show_header: bool,
+    show_content: bool,
+    pretty: bool,
+}
+
+impl JsonViewer {
+
+    pub fn new(show_header: bool, show_content: bool, pretty: bool) -> JsonViewer {
+        JsonViewer {
+            show_header: show_header,
+            show_content: show_content,
+            pretty: pretty,
+        }
+    }
+
+}
+
+impl Viewer for JsonViewer {
+
+    fn view_entry(&self, e: &Entry) -> Result<()> {
+        let mut json = e.to_json();
+        if let Json::Object(ref mut obj) = json {
+            if !self.show_header {
+                obj.remove("header");
+            }
+            if !self.show_content {
+                obj.remove("content");
+            }
+        }
+
+        if self.pretty {
+            println!("{}", json.pretty());
+        } else {
+            println!("{}", json);
+        }
+        Ok(())
+    }
+
+}
@@ -1 +1,3 @@
+pub mod json;
 pub mod plain;
+pub mod template;
@@ -0,0 +1,28 @@
+use libimagstore::store::Entry;
+
+use template::Template;
+use viewer::Viewer;
+use result::Result;
+
+/// Prints an entry formatted by a template
+pub struct TemplateViewer {
+    template: Template,
+}
+
+impl TemplateViewer {
+
+    pub fn new(template: Template) -> TemplateViewer {
+        TemplateViewer {
+            template: template,
+        }
+    }
+
+}
+
+impl Viewer for TemplateViewer {
+
+    fn view_entry(&self, e: &Entry) -> Result<()> {
+        self.template.render(e).map(|s| println!("{}", s))
+    }
+
+}
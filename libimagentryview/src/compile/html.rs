@@ -0,0 +1,171 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use pulldown_cmark::{Event, Parser, Tag};
+use pulldown_cmark::html::push_html;
+
+use libimagstore::store::Entry;
+use libimagstore::storeid::StoreId;
+
+use compile::{Compiler, front_matter, internal_links, page_link, page_path};
+use result::Result;
+
+/// Compiles Markdown content into a HTML page
+pub struct HtmlCompiler {
+    store_path: PathBuf,
+    show_front_matter: bool,
+}
+
+impl HtmlCompiler {
+
+    pub fn new(store_path: PathBuf, show_front_matter: bool) -> HtmlCompiler {
+        HtmlCompiler {
+            store_path: store_path,
+            show_front_matter: show_front_matter,
+        }
+    }
+
+    /// Get the name of the entry, which is its id relative to the store
+    pub fn entry_name(&self, id: &Path) -> String {
+        format!("{}", id.strip_prefix(&self.store_path).unwrap_or(id).display())
+    }
+
+    /// Render the header of the entry as definition list
+    pub fn front_matter_html(&self, e: &Entry) -> String {
+        let mut out = String::from("<dl class=\"front-matter\">\n");
+        for (key, value) in front_matter(e) {
+            out.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", escape_html(&key[..]),
+                                  escape_html(&value[..]))[..]);
+        }
+        out.push_str("</dl>\n");
+        out
+    }
+
+    /// Render the content of the entry
+    ///
+    /// Markdown links to entries which are linked in `imag.links` point to the page of the linked
+    /// entry.
+    pub fn content_html(&self, e: &Entry) -> Result<String> {
+        let links = try!(internal_links(e));
+        let from  = e.get_location();
+        let events = Parser::new(&e.get_content()[..])
+            .map(|ev| match ev {
+                Event::Start(Tag::Link(url, title)) => {
+                    Event::Start(Tag::Link(self.rewrite_link(from, &links[..], url), title))
+                },
+                Event::End(Tag::Link(url, title)) => {
+                    Event::End(Tag::Link(self.rewrite_link(from, &links[..], url), title))
+                },
+                other => other,
+            });
+
+        let mut out = String::from("<div class=\"content\">\n");
+        push_html(&mut out, events);
+        out.push_str("</div>\n");
+        Ok(out)
+    }
+
+    /// Render the internal links of the entry as list of links to their pages
+    pub fn links_html(&self, e: &Entry) -> Result<String> {
+        let links = try!(internal_links(e));
+        Ok(self.link_list_html("links", e.get_location(), &links[..]))
+    }
+
+    /// Render a list of links from the page of `from` to the pages of the entries `to`
+    pub fn link_list_html(&self, class: &str, from: &Path, to: &[StoreId]) -> String {
+        if to.is_empty() {
+            return String::new();
+        }
+
+        let mut out = format!("<ul class=\"{}\">\n", class);
+        for link in to {
+            out.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n",
+                                  escape_html(&page_link(&self.store_path, from, link)[..]),
+                                  escape_html(&self.entry_name(link)[..]))[..]);
+        }
+        out.push_str("</ul>\n");
+        out
+    }
+
+    fn rewrite_link<'a>(&self, from: &Path, links: &[StoreId], url: Cow<'a, str>) -> Cow<'a, str> {
+        let target = page_path(&self.store_path, Path::new(&url[..]));
+        match links.iter().find(|l| page_path(&self.store_path, l) == target) {
+            Some(link) => Cow::Owned(page_link(&self.store_path, from, link)),
+            None       => url,
+        }
+    }
+
+}
+
+impl Compiler for HtmlCompiler {
+
+    fn compile(&self, e: &Entry) -> Result<String> {
+        let mut body = String::new();
+        if self.show_front_matter {
+            body.push_str(&self.front_matter_html(e)[..]);
+        }
+        body.push_str(&try!(self.content_html(e))[..]);
+        body.push_str(&try!(self.links_html(e))[..]);
+
+        Ok(page(&self.entry_name(e.get_location())[..], &body[..]))
+    }
+
+}
+
+/// Wrap `body` into a HTML document with the title `title`
+pub fn page(title: &str, body: &str) -> String {
+    let title = escape_html(title);
+    format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<h1>{}</h1>\n{}</body>\n</html>\n",
+            title, title, body)
+}
+
+pub fn escape_html(s: &str) -> String {
+    s.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use toml::Value;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+
+    use compile::Compiler;
+    use super::HtmlCompiler;
+
+    fn entry() -> Entry {
+        let mut e = Entry::new(StoreId::from(PathBuf::from("/store/notes/foo~0.1.0")));
+        e.get_header_mut()
+            .set("imag.links", Value::Array(vec![
+                Value::String(String::from("/store/notes/bar~0.1.0")),
+            ]))
+            .unwrap();
+        *e.get_content_mut() = String::from("# Title\n\nSee [bar](/notes/bar~0.1.0) & <more>");
+        e
+    }
+
+    #[test]
+    fn test_compile_html() {
+        let html = HtmlCompiler::new(PathBuf::from("/store"), true).compile(&entry()).unwrap();
+
+        assert!(html.contains("<title>notes/foo~0.1.0</title>"));
+        assert!(html.contains("<dt>imag.version</dt>"));
+        assert!(!html.contains("<dt>imag.links</dt>"));
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<a href=\"../notes/bar~0.1.0.html\">bar</a>"));
+        assert!(html.contains("<li><a href=\"../notes/bar~0.1.0.html\">notes/bar~0.1.0</a></li>"));
+    }
+
+    #[test]
+    fn test_compile_html_without_front_matter() {
+        let html = HtmlCompiler::new(PathBuf::from("/store"), false).compile(&entry()).unwrap();
+        assert!(!html.contains("front-matter"));
+    }
+
+}
+
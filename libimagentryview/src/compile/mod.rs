@@ -0,0 +1,214 @@
+//! Compilation of entry content from a markup language into other formats
+//!
+//! Currently, Markdown can be compiled into HTML (`html::HtmlCompiler`) or into text with ANSI
+//! escape sequences for the terminal (`terminal::TerminalCompiler`).
+//!
+//! Both compilers put the header of the entry into a front-matter block before the content and
+//! render the internal links from `imag.links` as links between the rendered entries. The HTML
+//! page of an entry `/notes/foo~0.1.0` is `notes/foo~0.1.0.html`, relative to the root of the
+//! rendered pages.
+
+pub mod html;
+pub mod terminal;
+
+use std::path::{Component, Path, PathBuf};
+
+use toml::Value;
+
+use libimagstore::store::Entry;
+use libimagstore::storeid::StoreId;
+
+use error::ViewError as VE;
+use error::ViewErrorKind as VEK;
+use result::Result;
+
+/// Markup languages which can be compiled
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Markup {
+    Markdown,
+}
+
+impl Markup {
+
+    pub fn from_name(name: &str) -> Result<Markup> {
+        match &name.to_lowercase()[..] {
+            "markdown" | "md" => Ok(Markup::Markdown),
+            other => {
+                debug!("Unsupported markup: '{}'", other);
+                Err(VE::new(VEK::UnknownMarkup, None))
+            },
+        }
+    }
+
+}
+
+/// Formats entries can be compiled into
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Html,
+    Terminal,
+}
+
+impl Target {
+
+    pub fn from_name(name: &str) -> Result<Target> {
+        match &name.to_lowercase()[..] {
+            "html"               => Ok(Target::Html),
+            "terminal" | "ansi"  => Ok(Target::Terminal),
+            other => {
+                debug!("Unsupported compile target: '{}'", other);
+                Err(VE::new(VEK::UnknownTarget, None))
+            },
+        }
+    }
+
+}
+
+pub trait Compiler {
+
+    /// Compile the entry into a String
+    fn compile(&self, e: &Entry) -> Result<String>;
+
+}
+
+/// Get the compiler for `target`, for content in `markup`
+///
+/// `store_path` is the path of the store, which is needed to build links between the entries.
+pub fn compiler(markup: Markup, target: Target, store_path: PathBuf) -> Box<Compiler> {
+    match (markup, target) {
+        (Markup::Markdown, Target::Html)     => Box::new(html::HtmlCompiler::new(store_path, true)),
+        (Markup::Markdown, Target::Terminal) => Box::new(terminal::TerminalCompiler::new(true)),
+    }
+}
+
+/// Get the header of the entry as flat list of `(key, value)` pairs for a front-matter block
+///
+/// Nested keys are joined with a dot (`note.name`), arrays are joined with a comma. The links are
+/// not included, as they are rendered separately.
+pub fn front_matter(e: &Entry) -> Vec<(String, String)> {
+    let mut fields = vec![];
+    flatten(e.get_header().header(), String::new(), &mut fields);
+    fields.into_iter().filter(|&(ref k, _)| k != "imag.links").collect()
+}
+
+fn flatten(v: &Value, prefix: String, out: &mut Vec<(String, String)>) {
+    match v {
+        &Value::Table(ref t) => {
+            for (k, v) in t.iter() {
+                let key = if prefix.is_empty() { k.clone() } else { format!("{}.{}", prefix, k) };
+                flatten(v, key, out);
+            }
+        },
+        other => out.push((prefix, value_to_string(other))),
+    }
+}
+
+fn value_to_string(v: &Value) -> String {
+    match v {
+        &Value::String(ref s) => s.clone(),
+        &Value::Array(ref a)  => a.iter().map(value_to_string).collect::<Vec<String>>().join(", "),
+        other                 => format!("{}", other),
+    }
+}
+
+/// Get the internal links of the entry from `imag.links`
+///
+/// Links are either store ids or tables with the store id in "target". Other values are ignored.
+pub fn internal_links(e: &Entry) -> Result<Vec<StoreId>> {
+    match e.get_header().read("imag.links") {
+        Ok(Some(Value::Array(links))) => {
+            let mut ids : Vec<StoreId> = vec![];
+            for link in links {
+                let id = match link {
+                    Value::String(s) => StoreId::from(s),
+                    Value::Table(t)  => match t.get("target") {
+                        Some(&Value::String(ref s)) => StoreId::from(s.clone()),
+                        _ => continue,
+                    },
+                    _ => continue,
+                };
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            Ok(ids)
+        },
+        Ok(_)  => Ok(vec![]),
+        Err(e) => Err(VE::new(VEK::CompileError, Some(Box::new(e)))),
+    }
+}
+
+/// Get the path of the HTML page for the entry `id`, relative to the root of the rendered pages
+pub fn page_path(store_path: &Path, id: &Path) -> PathBuf {
+    let relative = id.strip_prefix(store_path).unwrap_or(id);
+    let mut path : PathBuf = relative.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s),
+            _ => None,
+        })
+        .collect();
+
+    let file_name = path.file_name()
+        .and_then(|s| s.to_str())
+        .map(|s| format!("{}.html", s))
+        .unwrap_or(String::from("index.html"));
+    path.set_file_name(file_name);
+    path
+}
+
+/// Get the link from the page of the entry `from` to the page of the entry `to`
+pub fn page_link(store_path: &Path, from: &Path, to: &Path) -> String {
+    let depth = page_path(store_path, from).components().count();
+    let mut link = String::new();
+    for _ in 1..depth {
+        link.push_str("../");
+    }
+
+    let to = page_path(store_path, to);
+    let parts : Vec<&str> = to.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => s.to_str(),
+            _ => None,
+        })
+        .collect();
+    link.push_str(&parts.join("/")[..]);
+    link
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{Markup, Target, page_link, page_path};
+
+    #[test]
+    fn test_names() {
+        assert_eq!(Markup::Markdown, Markup::from_name("Markdown").unwrap());
+        assert!(Markup::from_name("textile").is_err());
+        assert_eq!(Target::Html, Target::from_name("html").unwrap());
+        assert_eq!(Target::Terminal, Target::from_name("ansi").unwrap());
+        assert!(Target::from_name("pdf").is_err());
+    }
+
+    #[test]
+    fn test_page_path() {
+        let store = Path::new("/tmp/store");
+        assert_eq!(PathBuf::from("notes/foo~0.1.0.html"),
+                   page_path(store, Path::new("/tmp/store/notes/foo~0.1.0")));
+        assert_eq!(PathBuf::from("notes/foo~0.1.0.html"),
+                   page_path(store, Path::new("/notes/foo~0.1.0")));
+    }
+
+    #[test]
+    fn test_page_link() {
+        let store = Path::new("/tmp/store");
+        assert_eq!("../counter/a/b~0.1.0.html",
+                   page_link(store,
+                             Path::new("/tmp/store/notes/foo~0.1.0"),
+                             Path::new("/tmp/store/counter/a/b~0.1.0")));
+        assert_eq!("../notes/bar~0.1.0.html",
+                   page_link(store, Path::new("/notes/foo~0.1.0"), Path::new("/notes/bar~0.1.0")));
+    }
+
+}
+
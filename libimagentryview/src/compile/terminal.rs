@@ -0,0 +1,186 @@
+use pulldown_cmark::{Event, Parser, Tag};
+
+use libimagstore::store::Entry;
+
+use compile::{Compiler, front_matter, internal_links};
+use result::Result;
+
+const RESET: &'static str     = "\x1b[0m";
+const BOLD: &'static str      = "\x1b[1m";
+const DIM: &'static str       = "\x1b[2m";
+const ITALIC: &'static str    = "\x1b[3m";
+const UNDERLINE: &'static str = "\x1b[4m";
+const CYAN: &'static str      = "\x1b[36m";
+
+/// Compiles Markdown content into text with ANSI escape sequences for the terminal
+///
+/// Links are rendered as underlined text, followed by their target in angle brackets.
+pub struct TerminalCompiler {
+    show_front_matter: bool,
+}
+
+impl TerminalCompiler {
+
+    pub fn new(show_front_matter: bool) -> TerminalCompiler {
+        TerminalCompiler {
+            show_front_matter: show_front_matter,
+        }
+    }
+
+    fn render_content(&self, content: &str) -> String {
+        let mut out = String::new();
+
+        // Stack of list counters, `None` for unordered lists
+        let mut lists : Vec<Option<usize>> = vec![];
+
+        // Styles which are active, re-applied after a style ends
+        let mut styles : Vec<&'static str> = vec![];
+
+        for event in Parser::new(content) {
+            match event {
+                Event::Start(Tag::Header(level)) => {
+                    out.push_str(BOLD);
+                    out.push_str(UNDERLINE);
+                    for _ in 0..level {
+                        out.push('#');
+                    }
+                    out.push(' ');
+                },
+                Event::End(Tag::Header(_)) => {
+                    out.push_str(RESET);
+                    out.push_str("\n\n");
+                },
+                Event::End(Tag::Paragraph) => {
+                    out.push_str(if lists.is_empty() { "\n\n" } else { "\n" });
+                },
+                Event::Start(Tag::BlockQuote) => out.push_str("> "),
+                Event::End(Tag::BlockQuote) => out.push('\n'),
+                Event::Start(Tag::CodeBlock(_)) => out.push_str(CYAN),
+                Event::End(Tag::CodeBlock(_)) => {
+                    out.push_str(RESET);
+                    out.push('\n');
+                },
+                Event::Start(Tag::List(start)) => lists.push(start),
+                Event::End(Tag::List(_)) => {
+                    lists.pop();
+                    if lists.is_empty() {
+                        out.push('\n');
+                    }
+                },
+                Event::Start(Tag::Item) => {
+                    let depth = if lists.is_empty() { 0 } else { lists.len() - 1 };
+                    for _ in 0..depth {
+                        out.push_str("  ");
+                    }
+                    match lists.last_mut() {
+                        Some(&mut Some(ref mut n)) => {
+                            out.push_str(&format!("{}. ", n)[..]);
+                            *n += 1;
+                        },
+                        _ => out.push_str("* "),
+                    }
+                },
+                Event::End(Tag::Item) => {
+                    if !out.ends_with("\n") {
+                        out.push('\n');
+                    }
+                },
+                Event::Start(Tag::Emphasis) => push_style(&mut out, &mut styles, ITALIC),
+                Event::Start(Tag::Strong)   => push_style(&mut out, &mut styles, BOLD),
+                Event::Start(Tag::Code)     => push_style(&mut out, &mut styles, CYAN),
+                Event::Start(Tag::Link(_, _)) => push_style(&mut out, &mut styles, UNDERLINE),
+                Event::End(Tag::Link(url, _)) => {
+                    pop_style(&mut out, &mut styles);
+                    out.push_str(&format!(" <{}>", url)[..]);
+                },
+                Event::End(Tag::Emphasis) |
+                Event::End(Tag::Strong)   |
+                Event::End(Tag::Code)     => pop_style(&mut out, &mut styles),
+                Event::Text(text)       => out.push_str(&text[..]),
+                Event::Html(html)       |
+                Event::InlineHtml(html) => out.push_str(&html[..]),
+                Event::SoftBreak        => out.push(' '),
+                Event::HardBreak        => out.push('\n'),
+                _ => { },
+            }
+        }
+
+        out
+    }
+
+}
+
+impl Compiler for TerminalCompiler {
+
+    fn compile(&self, e: &Entry) -> Result<String> {
+        let mut out = String::new();
+
+        if self.show_front_matter {
+            out.push_str(DIM);
+            out.push_str("---\n");
+            for (key, value) in front_matter(e) {
+                out.push_str(&format!("{}: {}\n", key, value)[..]);
+            }
+            out.push_str("---");
+            out.push_str(RESET);
+            out.push_str("\n\n");
+        }
+
+        out.push_str(&self.render_content(&e.get_content()[..])[..]);
+
+        let links = try!(internal_links(e));
+        if !links.is_empty() {
+            out.push_str(BOLD);
+            out.push_str("Links:");
+            out.push_str(RESET);
+            out.push('\n');
+            for link in links {
+                out.push_str(&format!("  -> {}{}{}\n", UNDERLINE, link.display(), RESET)[..]);
+            }
+        }
+
+        Ok(out)
+    }
+
+}
+
+fn push_style(out: &mut String, styles: &mut Vec<&'static str>, style: &'static str) {
+    styles.push(style);
+    out.push_str(style);
+}
+
+/// Reset the style and re-apply the styles which are still active
+fn pop_style(out: &mut String, styles: &mut Vec<&'static str>) {
+    styles.pop();
+    out.push_str(RESET);
+    for style in styles.iter() {
+        out.push_str(style);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+
+    use compile::Compiler;
+    use super::TerminalCompiler;
+
+    #[test]
+    fn test_compile_terminal() {
+        let mut e = Entry::new(StoreId::from(PathBuf::from("/store/notes/foo~0.1.0")));
+        *e.get_content_mut() = String::from("# Title\n\nSome **bold** text\n\n* a\n* b");
+
+        let out = TerminalCompiler::new(true).compile(&e).unwrap();
+
+        assert!(out.starts_with("\x1b[2m---\n"));
+        assert!(out.contains("imag.version: "));
+        assert!(out.contains("\x1b[1m\x1b[4m# Title\x1b[0m"));
+        assert!(out.contains("Some \x1b[1mbold\x1b[0m text"));
+        assert!(out.contains("* a\n* b\n"));
+    }
+
+}
+
@@ -8,11 +8,20 @@ use std::fmt::{Display, Formatter};
  */
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ViewErrorKind {
+    TemplateParseError,
+    TemplateRenderError,
+    UnknownMarkup,
+    UnknownTarget,
+    CompileError,
 }
 
 fn counter_error_type_as_str(e: &ViewErrorKind) -> &'static str {
     match e {
-        _ => "",
+        &ViewErrorKind::TemplateParseError  => "Error while parsing template",
+        &ViewErrorKind::TemplateRenderError => "Error while rendering template",
+        &ViewErrorKind::UnknownMarkup       => "Unknown or unsupported markup language",
+        &ViewErrorKind::UnknownTarget       => "Unknown or unsupported compile target",
+        &ViewErrorKind::CompileError        => "Error while compiling entry",
     }
 }
 
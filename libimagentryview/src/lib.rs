@@ -14,10 +14,17 @@
     while_true,
 )]
 
+#[macro_use] extern crate log;
+extern crate pulldown_cmark;
+extern crate rustc_serialize;
+extern crate toml;
+
 extern crate libimagstore;
 
 pub mod error;
 pub mod builtin;
+pub mod compile;
 pub mod result;
+pub mod template;
 pub mod viewer;
 
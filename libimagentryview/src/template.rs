@@ -0,0 +1,344 @@
+//! A minimal template language for formatting entries
+//!
+//! A template is plain text with expressions in double curly braces. An expression is a path into
+//! the header of the entry (`note.name`, `imag.tags`) or one of the special names `content` and
+//! `location`, optionally followed by filters which are separated by a pipe:
+//!
+//! ```ignore
+//! {{note.name}} [{{imag.tags|join(",")}}] {{content|lines(1)}}
+//! ```
+//!
+//! Available filters:
+//!
+//! * `join(sep)`: join an array with `sep`
+//! * `lines(n)`: only the first `n` lines
+//! * `upper`, `lower`, `trim`
+//! * `default(text)`: use `text` if the value is missing or empty
+//!
+//! Values which do not exist in the header are rendered as empty string.
+
+use toml::Value;
+
+use libimagstore::store::Entry;
+
+use error::ViewError as VE;
+use error::ViewErrorKind as VEK;
+use result::Result;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Expr(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Expression {
+    path: String,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Join(String),
+    Lines(usize),
+    Upper,
+    Lower,
+    Trim,
+    Default(String),
+}
+
+/// A parsed template, which can be rendered for entries
+#[derive(Debug, Clone)]
+pub struct Template {
+    tokens: Vec<Token>,
+}
+
+impl Template {
+
+    /// Parse a template string
+    pub fn parse(s: &str) -> Result<Template> {
+        let mut tokens = vec![];
+        let mut rest   = s;
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                tokens.push(Token::Text(String::from(&rest[..start])));
+            }
+
+            let after = &rest[(start + 2)..];
+            let end   = try!(find_unquoted(after, "}}")
+                .ok_or(VE::new(VEK::TemplateParseError, None)));
+
+            tokens.push(Token::Expr(try!(parse_expression(&after[..end]))));
+            rest = &after[(end + 2)..];
+        }
+
+        if !rest.is_empty() {
+            tokens.push(Token::Text(String::from(rest)));
+        }
+
+        Ok(Template { tokens: tokens })
+    }
+
+    /// Render the template for an entry
+    pub fn render(&self, e: &Entry) -> Result<String> {
+        let mut out = String::new();
+        for token in self.tokens.iter() {
+            match token {
+                &Token::Text(ref s)    => out.push_str(&s[..]),
+                &Token::Expr(ref expr) => out.push_str(&try!(expr.evaluate(e))[..]),
+            }
+        }
+        Ok(out)
+    }
+
+}
+
+impl Expression {
+
+    fn evaluate(&self, e: &Entry) -> Result<String> {
+        let value = match &self.path[..] {
+            "content"  => Some(Value::String(e.get_content().clone())),
+            "location" => e.get_location().to_str().map(|s| Value::String(String::from(s))),
+            path       => try!(e.get_header()
+                                .read(path)
+                                .map_err(|e| VE::new(VEK::TemplateRenderError, Some(Box::new(e))))),
+        };
+
+        Ok(self.filters
+            .iter()
+            .fold(value, |v, f| f.apply(v))
+            .map(|v| render_value(&v))
+            .unwrap_or(String::new()))
+    }
+
+}
+
+impl Filter {
+
+    fn apply(&self, v: Option<Value>) -> Option<Value> {
+        match (self, v) {
+            (&Filter::Join(ref sep), Some(Value::Array(a))) => {
+                let joined = a.iter().map(render_value).collect::<Vec<String>>().join(&sep[..]);
+                Some(Value::String(joined))
+            },
+            (&Filter::Join(_), v) => v,
+
+            (&Filter::Default(ref d), None) => Some(Value::String(d.clone())),
+            (&Filter::Default(ref d), Some(v)) => {
+                if is_empty(&v) { Some(Value::String(d.clone())) } else { Some(v) }
+            },
+
+            (_, None) => None,
+
+            (&Filter::Lines(n), Some(v)) => {
+                let s = render_value(&v);
+                Some(Value::String(s.lines().take(n).collect::<Vec<&str>>().join("\n")))
+            },
+            (&Filter::Upper, Some(v)) => Some(Value::String(render_value(&v).to_uppercase())),
+            (&Filter::Lower, Some(v)) => Some(Value::String(render_value(&v).to_lowercase())),
+            (&Filter::Trim, Some(v))  => Some(Value::String(String::from(render_value(&v).trim()))),
+        }
+    }
+
+}
+
+/// Read a template for a module from the configuration
+///
+/// The configuration is expected to have a section per module, where the templates are stored
+/// under a key, for example:
+///
+/// ```toml
+/// [notes]
+/// list-template = "{{note.name}} [{{imag.tags|join(\",\")}}]"
+/// view-template = "{{note.name}}\n\n{{content}}"
+/// ```
+pub fn template_from_config(config: &Value, module: &str, key: &str) -> Option<String> {
+    match config {
+        &Value::Table(ref t) => {
+            match t.get(module) {
+                Some(&Value::Table(ref module_cfg)) => {
+                    match module_cfg.get(key) {
+                        Some(&Value::String(ref s)) => Some(s.clone()),
+                        _ => None,
+                    }
+                },
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+fn render_value(v: &Value) -> String {
+    match v {
+        &Value::String(ref s) => s.clone(),
+        &Value::Array(ref a)  => a.iter().map(render_value).collect::<Vec<String>>().join(", "),
+        other                 => format!("{}", other),
+    }
+}
+
+fn is_empty(v: &Value) -> bool {
+    match v {
+        &Value::String(ref s) => s.is_empty(),
+        &Value::Array(ref a)  => a.is_empty(),
+        &Value::Table(ref t)  => t.is_empty(),
+        _                     => false,
+    }
+}
+
+/// Find the byte index of `pat` in `s`, ignoring occurences inside of double quotes
+fn find_unquoted(s: &str, pat: &str) -> Option<usize> {
+    let mut in_quotes = false;
+    let mut escaped   = false;
+    for (idx, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"'               => in_quotes = !in_quotes,
+            _ if !in_quotes && s[idx..].starts_with(pat) => return Some(idx),
+            _ => { },
+        }
+    }
+    None
+}
+
+/// Split `s` at each `sep` which is not inside of double quotes
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut rest  = s;
+    while let Some(idx) = find_unquoted(rest, &sep.to_string()[..]) {
+        parts.push(&rest[..idx]);
+        rest = &rest[(idx + sep.len_utf8())..];
+    }
+    parts.push(rest);
+    parts
+}
+
+fn parse_expression(s: &str) -> Result<Expression> {
+    let mut parts = split_unquoted(s, '|').into_iter();
+    let path = parts.next().map(|p| p.trim()).unwrap_or("");
+    if path.is_empty() {
+        debug!("Empty path in template expression: '{}'", s);
+        return Err(VE::new(VEK::TemplateParseError, None));
+    }
+
+    let mut filters = vec![];
+    for part in parts {
+        filters.push(try!(parse_filter(part.trim())));
+    }
+
+    Ok(Expression {
+        path: String::from(path),
+        filters: filters,
+    })
+}
+
+fn parse_filter(s: &str) -> Result<Filter> {
+    use std::str::FromStr;
+
+    let (name, args) = match s.find('(') {
+        Some(idx) => {
+            if !s.ends_with(")") {
+                debug!("Unclosed filter argument list: '{}'", s);
+                return Err(VE::new(VEK::TemplateParseError, None));
+            }
+            let args = split_unquoted(&s[(idx + 1)..(s.len() - 1)], ',')
+                .into_iter()
+                .map(|a| unquote(a.trim()))
+                .collect::<Vec<String>>();
+            (s[..idx].trim(), args)
+        },
+        None => (s, vec![]),
+    };
+
+    match (name, args.len()) {
+        ("join", 1)    => Ok(Filter::Join(args[0].clone())),
+        ("lines", 1)   => {
+            FromStr::from_str(&args[0][..])
+                .map(Filter::Lines)
+                .map_err(|e| VE::new(VEK::TemplateParseError, Some(Box::new(e))))
+        },
+        ("upper", 0)   => Ok(Filter::Upper),
+        ("lower", 0)   => Ok(Filter::Lower),
+        ("trim", 0)    => Ok(Filter::Trim),
+        ("default", 1) => Ok(Filter::Default(args[0].clone())),
+        _ => {
+            debug!("Unknown filter or wrong number of arguments: '{}'", s);
+            Err(VE::new(VEK::TemplateParseError, None))
+        },
+    }
+}
+
+fn unquote(s: &str) -> String {
+    if s.len() >= 2 && s.starts_with("\"") && s.ends_with("\"") {
+        s[1..(s.len() - 1)].replace("\\\"", "\"").replace("\\\\", "\\")
+    } else {
+        String::from(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use toml::Value;
+
+    use libimagstore::store::Entry;
+    use libimagstore::storeid::StoreId;
+
+    use super::Template;
+
+    fn entry() -> Entry {
+        let mut e = Entry::new(StoreId::from(PathBuf::from("/notes/test~0.1.0")));
+        {
+            let hdr = e.get_header_mut();
+            hdr.set("imag.tags", Value::Array(vec![
+                Value::String(String::from("a")),
+                Value::String(String::from("b")),
+            ])).unwrap();
+            hdr.insert("note", Value::Table(BTreeMap::new())).unwrap();
+            hdr.set("note.name", Value::String(String::from("test"))).unwrap();
+        }
+        *e.get_content_mut() = String::from("first line\nsecond line");
+        e
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let t = Template::parse("no expressions").unwrap();
+        assert_eq!("no expressions", t.render(&entry()).unwrap());
+    }
+
+    #[test]
+    fn test_header_and_filters() {
+        let t = Template::parse("{{note.name}} [{{imag.tags|join(\",\")}}] {{content|lines(1)}}")
+            .unwrap();
+        assert_eq!("test [a,b] first line", t.render(&entry()).unwrap());
+    }
+
+    #[test]
+    fn test_quoted_separator() {
+        let t = Template::parse("{{imag.tags|join(\" | \")|upper}}").unwrap();
+        assert_eq!("A | B", t.render(&entry()).unwrap());
+    }
+
+    #[test]
+    fn test_missing_value_and_default() {
+        let t = Template::parse("<{{note.title}}><{{note.title|default(none)}}>").unwrap();
+        assert_eq!("<><none>", t.render(&entry()).unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Template::parse("{{note.name").is_err());
+        assert!(Template::parse("{{}}").is_err());
+        assert!(Template::parse("{{note.name|unknown}}").is_err());
+        assert!(Template::parse("{{content|lines(x)}}").is_err());
+    }
+
+}
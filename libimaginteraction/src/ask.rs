@@ -152,6 +152,32 @@ pub fn ask_select_from_list(list: &[&str]) -> Result<String> {
         .map_err(|e| InteractionError::new(InteractionErrorKind::Unknown, Some(Box::new(e))))
 }
 
+/// Let the user select several elements of the list, one after another, until `done` is selected
+/// or all elements are selected. `done` is offered as first element.
+pub fn ask_select_many_from_list(list: &[&str], done: &str) -> Result<Vec<String>> {
+    ask_select_many_from_list_(list, done, ask_select_from_list)
+}
+
+fn ask_select_many_from_list_<F>(list: &[&str], done: &str, mut select: F) -> Result<Vec<String>>
+    where F: FnMut(&[&str]) -> Result<String>
+{
+    let mut selected : Vec<String> = vec![];
+
+    loop {
+        let mut choices = vec![done];
+        choices.extend(list.iter().filter(|e| !selected.iter().any(|s| s == **e)));
+        if choices.len() == 1 {
+            return Ok(selected);
+        }
+
+        let choice = try!(select(&choices[..]));
+        if choice == done {
+            return Ok(selected);
+        }
+        selected.push(choice);
+    }
+}
+
 /// Helper function to print a imag question string. The `question` argument may not contain a
 /// trailing questionmark.
 ///
@@ -170,6 +196,7 @@ mod test {
 
     use super::ask_bool_;
     use super::ask_uint_;
+    use super::ask_select_many_from_list_;
 
     #[test]
     fn test_ask_bool_nodefault_yes() {
@@ -297,4 +324,37 @@ mod test {
         assert!(2 == ask_uint_(question, default, &mut BufReader::new(answers.as_bytes())));
     }
 
+    #[test]
+    fn test_ask_select_many_from_list_until_done() {
+        let mut offered = vec![];
+        let mut answers = vec!["b", "done"].into_iter();
+        let selected = ask_select_many_from_list_(&["a", "b", "c"], "done", |choices| {
+            offered.push(choices.iter().map(|c| String::from(*c)).collect::<Vec<_>>());
+            Ok(String::from(answers.next().unwrap()))
+        }).unwrap();
+
+        assert_eq!(vec![String::from("b")], selected);
+        assert_eq!(vec!["done", "a", "b", "c"], offered[0]);
+        assert_eq!(vec!["done", "a", "c"], offered[1]);
+    }
+
+    #[test]
+    fn test_ask_select_many_from_list_all() {
+        let mut answers = vec!["c", "a"].into_iter();
+        let selected = ask_select_many_from_list_(&["a", "c"], "done", |_| {
+            Ok(String::from(answers.next().unwrap()))
+        }).unwrap();
+
+        assert_eq!(vec![String::from("c"), String::from("a")], selected);
+    }
+
+    #[test]
+    fn test_ask_select_many_from_empty_list() {
+        let selected = ask_select_many_from_list_(&[], "done", |_| {
+            panic!("Nothing to select from")
+        }).unwrap();
+
+        assert!(selected.is_empty());
+    }
+
 }
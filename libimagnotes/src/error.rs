@@ -12,7 +12,8 @@ pub enum NoteErrorKind {
     StoreReadError,
     HeaderTypeError,
     NoteToEntryConversion,
-    // Nothing here yet
+    TemplateNotFound,
+    FrontMatterError,
 }
 
 fn note_error_type_as_str(e: &NoteErrorKind) -> &'static str {
@@ -21,6 +22,8 @@ fn note_error_type_as_str(e: &NoteErrorKind) -> &'static str {
         &NoteErrorKind::StoreReadError  => "Error reading store",
         &NoteErrorKind::HeaderTypeError => "Header type error",
         &NoteErrorKind::NoteToEntryConversion => "Error converting Note instance to Entry instance",
+        &NoteErrorKind::TemplateNotFound => "Template not found",
+        &NoteErrorKind::FrontMatterError => "Error in the front matter",
     }
 }
 
@@ -15,16 +15,22 @@
 )]
 
 #[macro_use] extern crate log;
+extern crate chrono;
 extern crate semver;
 extern crate toml;
+extern crate url;
+extern crate yaml_rust;
 
 extern crate libimagrt;
 #[macro_use] extern crate libimagstore;
+extern crate libimagentrylink;
 extern crate libimagentrytag;
 
 module_entry_path_mod!("notes", "0.1.0");
 
 pub mod error;
+pub mod markdown;
 pub mod note;
 pub mod result;
+pub mod template;
 
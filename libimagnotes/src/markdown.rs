@@ -0,0 +1,627 @@
+//! Conversion of notes from and to Markdown with YAML front matter
+//!
+//! ```markdown
+//! ---
+//! id: meeting-with-bob
+//! title: Meeting with Bob
+//! tags: [work, meetings]
+//! links:
+//!   - project-plan
+//!   - id: minutes
+//!     kind: references
+//! urls: ["http://example.com"]
+//! location: Room 2
+//! ---
+//! The content of the note
+//! ```
+//!
+//! The `title` is the title of the note, `tags` are its tags. `links` are the links to other
+//! notes, by id, with an optional kind and label. `urls` are the external links of the note. The
+//! other fields of the front matter are the fields of the "note" section of the header.
+//!
+//! The `id` is the id of the note when it was exported. When importing, it is only used to resolve
+//! the links between the imported notes, the note gets a new id which is generated from its title.
+
+use std::collections::BTreeMap;
+
+use toml::Value;
+use url::Url;
+use yaml_rust::{Yaml, YamlEmitter, YamlLoader};
+use yaml_rust::yaml::Hash;
+
+use libimagstore::storeid::IntoStoreId;
+use libimagstore::store::Store;
+use libimagentrylink::external::ExternalLinker;
+use libimagentrylink::internal::InternalLinker;
+use libimagentrylink::kind::LinkKind;
+use libimagentrytag::tag::Tag;
+use libimagentrytag::tagable::Tagable;
+use libimagentrytag::util::is_tag;
+
+use module_path::ModuleEntryPath;
+use note::Note;
+use result::Result;
+use error::NoteError as NE;
+use error::NoteErrorKind as NEK;
+
+/// A link to another note
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownLink {
+    pub id: String,
+    pub kind: LinkKind,
+    pub label: Option<String>,
+}
+
+/// The result of importing a note
+#[derive(Debug, Clone, PartialEq)]
+pub enum Imported {
+    /// The note was created with this id
+    Created(String),
+
+    /// There is a note with the same title and content already, which has this id
+    Duplicate(String),
+}
+
+impl Imported {
+
+    pub fn id(&self) -> &str {
+        match self {
+            &Imported::Created(ref id)   => &id[..],
+            &Imported::Duplicate(ref id) => &id[..],
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownNote {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub tags: Vec<Tag>,
+    pub links: Vec<MarkdownLink>,
+    pub urls: Vec<String>,
+    pub fields: BTreeMap<String, Value>,
+    pub content: String,
+}
+
+impl MarkdownNote {
+
+    /// Parse a Markdown file. A file without front matter is a note with content only.
+    pub fn parse(s: &str) -> Result<MarkdownNote> {
+        let (front, content) = split_front_matter(s);
+        let mut note = MarkdownNote {
+            id: None,
+            title: None,
+            tags: vec![],
+            links: vec![],
+            urls: vec![],
+            fields: BTreeMap::new(),
+            content: String::from(content),
+        };
+
+        let front = match front {
+            Some(front) => front,
+            None        => return Ok(note),
+        };
+
+        let docs = try!(YamlLoader::load_from_str(front)
+            .map_err(|e| NE::new(NEK::FrontMatterError, Some(Box::new(e)))));
+
+        let hash = match docs.into_iter().next() {
+            Some(Yaml::Hash(hash))  => hash,
+            Some(Yaml::Null) | None => return Ok(note),
+            Some(other) => {
+                debug!("Front matter is not a hash: {:?}", other);
+                return Err(NE::new(NEK::FrontMatterError, None));
+            },
+        };
+
+        for (key, value) in hash {
+            let key = match yaml_key(key) {
+                Some(key) => key,
+                None      => continue,
+            };
+
+            match &key[..] {
+                "id"    => note.id    = yaml_string(value),
+                "title" => note.title = yaml_string(value),
+                "tags"  => note.tags  = yaml_strings(value),
+                "urls"  => note.urls  = yaml_strings(value),
+                "links" => note.links = try!(yaml_links(value)),
+                _       => if let Some(value) = yaml_to_toml(value) {
+                    note.fields.insert(key, value);
+                },
+            }
+        }
+
+        Ok(note)
+    }
+
+    /// Get the Markdown representation of a note, with the links to other notes and the
+    /// external links. Links to entries which are not notes are not included.
+    pub fn from_note(store: &Store, note: &Note) -> Result<MarkdownNote> {
+        let mut fields = match note.get_header().read("note") {
+            Ok(Some(Value::Table(t))) => t,
+            Ok(_)  => BTreeMap::new(),
+            Err(e) => return Err(NE::new(NEK::StoreReadError, Some(Box::new(e)))),
+        };
+
+        let title = match fields.remove("name") {
+            Some(Value::String(s)) => Some(s),
+            _ => None,
+        };
+
+        let tags = try!(note.get_tags()
+            .map_err(|e| NE::new(NEK::StoreReadError, Some(Box::new(e)))));
+
+        let links = try!(note.get_typed_links()
+            .map_err(|e| NE::new(NEK::StoreReadError, Some(Box::new(e)))))
+            .into_iter()
+            .filter_map(|link| {
+                Note::id_of(store, &link.target).map(|id| {
+                    MarkdownLink {
+                        id: id,
+                        kind: link.kind,
+                        label: link.label,
+                    }
+                })
+            })
+            .collect();
+
+        let urls = try!(note.get_external_links(store)
+            .map_err(|e| NE::new(NEK::StoreReadError, Some(Box::new(e)))))
+            .into_iter()
+            .map(|url| String::from(url.as_str()))
+            .collect();
+
+        Ok(MarkdownNote {
+            id: Some(try!(note.id())),
+            title: title,
+            tags: tags,
+            links: links,
+            urls: urls,
+            fields: fields,
+            content: note.get_text().clone(),
+        })
+    }
+
+    /// Get the Markdown file for the note
+    pub fn to_markdown(&self) -> Result<String> {
+        let mut front = Hash::new();
+
+        if let Some(ref id) = self.id {
+            front.insert(Yaml::String(String::from("id")), Yaml::String(id.clone()));
+        }
+        if let Some(ref title) = self.title {
+            front.insert(Yaml::String(String::from("title")), Yaml::String(title.clone()));
+        }
+        if !self.tags.is_empty() {
+            let tags = self.tags.iter().map(|t| Yaml::String(t.clone())).collect();
+            front.insert(Yaml::String(String::from("tags")), Yaml::Array(tags));
+        }
+        if !self.links.is_empty() {
+            let links = self.links.iter().map(link_to_yaml).collect();
+            front.insert(Yaml::String(String::from("links")), Yaml::Array(links));
+        }
+        if !self.urls.is_empty() {
+            let urls = self.urls.iter().map(|u| Yaml::String(u.clone())).collect();
+            front.insert(Yaml::String(String::from("urls")), Yaml::Array(urls));
+        }
+        for (key, value) in self.fields.iter() {
+            front.insert(Yaml::String(key.clone()), toml_to_yaml(value.clone()));
+        }
+
+        let mut yaml = String::new();
+        try!(YamlEmitter::new(&mut yaml)
+            .dump(&Yaml::Hash(front))
+            .map_err(|e| {
+                debug!("Emitting front matter failed: {:?}", e);
+                NE::new(NEK::FrontMatterError, None)
+            }));
+
+        // The emitter starts the document with "---" or not, depending on its version
+        Ok(format!("---\n{}\n---\n{}", yaml.trim_left_matches("---").trim(), self.content))
+    }
+
+    /// Create a note from the Markdown note, unless there is a note with the same title and the
+    /// same content already. `default_title` is the title if the front matter has none.
+    ///
+    /// Links are not added here, see `add_links()`.
+    pub fn import(&self, store: &Store, default_title: &str) -> Result<Imported> {
+        let title = self.title.clone().unwrap_or(String::from(default_title));
+
+        for id in try!(Note::ids_by_title(store, &title)) {
+            let entry = try!(store.retrieve_copy(ModuleEntryPath::new(id.clone()).into_storeid())
+                .map_err(|e| NE::new(NEK::StoreReadError, Some(Box::new(e)))));
+
+            if *entry.get_content() == self.content {
+                debug!("Note '{}' is a duplicate", id);
+                return Ok(Imported::Duplicate(id));
+            }
+        }
+
+        // Check the tags first, so an invalid tag does not leave a half imported note behind
+        let tags = import_tags(&self.tags);
+
+        let mut note = try!(Note::new(store, title, self.content.clone()));
+
+        if !tags.is_empty() {
+            try!(note.set_tags(tags)
+                .map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e)))));
+        }
+
+        if !self.fields.is_empty() {
+            let mut table = match note.get_header().read("note") {
+                Ok(Some(Value::Table(t))) => t,
+                Ok(_)  => BTreeMap::new(),
+                Err(e) => return Err(NE::new(NEK::StoreReadError, Some(Box::new(e)))),
+            };
+
+            for (key, value) in self.fields.iter() {
+                // The title is the "name" of the note
+                if key != "name" {
+                    table.insert(key.clone(), value.clone());
+                }
+            }
+
+            try!(note.get_header_mut()
+                .set("note", Value::Table(table))
+                .map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e)))));
+        }
+
+        note.id().map(Imported::Created)
+    }
+
+    /// Add the links and the external links of the Markdown note to the note with the id `id`
+    ///
+    /// `ids` maps the ids in the links to the ids of the imported notes. Links to ids which are
+    /// not in `ids` are links to existing notes in the store. Links which already exist are not
+    /// added again.
+    pub fn add_links(&self, store: &Store, id: &str, ids: &BTreeMap<String, String>)
+        -> Result<()>
+    {
+        let mut note = try!(Note::retrieve(store, String::from(id)));
+
+        for link in self.links.iter() {
+            let target = match ids.get(&link.id) {
+                Some(target) => target.clone(),
+                None if Note::exists(store, &link.id) => link.id.clone(),
+                None => {
+                    warn!("Not linking '{}' to '{}', there is no such note", id, link.id);
+                    continue;
+                },
+            };
+
+            if target == id {
+                continue;
+            }
+
+            let mut other = try!(Note::retrieve(store, target));
+            let exists = try!(note.get_typed_links()
+                .map_err(|e| NE::new(NEK::StoreReadError, Some(Box::new(e)))))
+                .iter()
+                .any(|l| l.target == *other.get_location() && l.kind == link.kind);
+
+            if !exists {
+                try!(note.add_typed_link(&mut other, link.kind.clone(), link.label.clone())
+                    .map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e)))));
+            }
+        }
+
+        for url in self.urls.iter() {
+            match Url::parse(url) {
+                Ok(url) => {
+                    try!(note.add_external_link(store, url)
+                        .map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e)))));
+                },
+                Err(e) => warn!("Not adding the link '{}' to '{}': {}", url, id, e),
+            }
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Split the front matter, between a "---" line at the beginning and a "---" or "..." line, from
+/// the content
+fn split_front_matter(s: &str) -> (Option<&str>, &str) {
+    if !(s.starts_with("---\n") || s.starts_with("---\r\n")) {
+        return (None, s);
+    }
+
+    let start = s.find('\n').unwrap() + 1; // safe, we checked the beginning of s
+    let mut pos = start;
+    for line in s[start..].split('\n') {
+        let end = pos + line.len();
+        let trimmed = line.trim_right();
+        if trimmed == "---" || trimmed == "..." {
+            let content_start = if end < s.len() { end + 1 } else { end };
+            return (Some(&s[start..pos]), &s[content_start..]);
+        }
+        pos = end + 1;
+    }
+
+    // No end of the front matter, so it is no front matter
+    (None, s)
+}
+
+/// Get the tags which can be set on a note. Whitespace in a tag is replaced by "-", so "my tag"
+/// becomes "my-tag". Tags which are still invalid, like "2016" or "C++", are dropped.
+fn import_tags(tags: &[Tag]) -> Vec<Tag> {
+    tags.iter()
+        .filter_map(|tag| {
+            if is_tag(tag) {
+                return Some(tag.clone());
+            }
+
+            let converted = tag.split_whitespace().collect::<Vec<_>>().join("-");
+            if is_tag(&converted) {
+                warn!("Importing the tag '{}' as '{}'", tag, converted);
+                Some(converted)
+            } else {
+                warn!("Not importing the tag '{}', it is not a valid tag", tag);
+                None
+            }
+        })
+        .collect()
+}
+
+fn yaml_key(key: Yaml) -> Option<String> {
+    match key {
+        Yaml::String(s)  => Some(s),
+        Yaml::Integer(i) => Some(format!("{}", i)),
+        Yaml::Real(r)    => Some(r),
+        Yaml::Boolean(b) => Some(format!("{}", b)),
+        other => {
+            warn!("Ignoring front matter field with the key {:?}", other);
+            None
+        },
+    }
+}
+
+fn yaml_string(v: Yaml) -> Option<String> {
+    match v {
+        Yaml::String(s)  => Some(s),
+        Yaml::Integer(i) => Some(format!("{}", i)),
+        Yaml::Real(r)    => Some(r),
+        _ => None,
+    }
+}
+
+/// Get a list of strings from an array, or from a string with comma separated values
+fn yaml_strings(v: Yaml) -> Vec<String> {
+    match v {
+        Yaml::Array(a)  => a.into_iter().filter_map(yaml_string).collect(),
+        Yaml::String(s) => {
+            s.split(',').map(|s| String::from(s.trim())).filter(|s| !s.is_empty()).collect()
+        },
+        _ => vec![],
+    }
+}
+
+/// Get the links, which are either ids or hashes with "id", "kind" and "label"
+fn yaml_links(v: Yaml) -> Result<Vec<MarkdownLink>> {
+    let links = match v {
+        Yaml::Array(a) => a,
+        Yaml::Null     => return Ok(vec![]),
+        other          => vec![other],
+    };
+
+    let mut result = vec![];
+    for link in links {
+        let link = match link {
+            Yaml::Hash(h) => {
+                let get = |key: &str| h.get(&Yaml::String(String::from(key)))
+                    .and_then(|v| yaml_string(v.clone()));
+
+                match get("id") {
+                    Some(id) => MarkdownLink {
+                        id: id,
+                        kind: get("kind")
+                            .map(|k| LinkKind::from_name(&k))
+                            .unwrap_or(LinkKind::Link),
+                        label: get("label"),
+                    },
+                    None => return Err(NE::new(NEK::FrontMatterError, None)),
+                }
+            },
+            other => match yaml_string(other) {
+                Some(id) => MarkdownLink { id: id, kind: LinkKind::Link, label: None },
+                None     => return Err(NE::new(NEK::FrontMatterError, None)),
+            },
+        };
+        result.push(link);
+    }
+    Ok(result)
+}
+
+/// Links of the kind `LinkKind::Link` without label are written as id only
+fn link_to_yaml(link: &MarkdownLink) -> Yaml {
+    if link.kind == LinkKind::Link && link.label.is_none() {
+        return Yaml::String(link.id.clone());
+    }
+
+    let mut h = Hash::new();
+    h.insert(Yaml::String(String::from("id")), Yaml::String(link.id.clone()));
+    h.insert(Yaml::String(String::from("kind")), Yaml::String(String::from(link.kind.name())));
+    if let Some(ref label) = link.label {
+        h.insert(Yaml::String(String::from("label")), Yaml::String(label.clone()));
+    }
+    Yaml::Hash(h)
+}
+
+/// Convert a YAML value to TOML. Null values and values with invalid numbers are dropped.
+fn yaml_to_toml(v: Yaml) -> Option<Value> {
+    match v {
+        Yaml::String(s)  => Some(Value::String(s)),
+        Yaml::Integer(i) => Some(Value::Integer(i)),
+        Yaml::Real(r)    => r.parse().ok().map(Value::Float),
+        Yaml::Boolean(b) => Some(Value::Boolean(b)),
+        Yaml::Array(a)   => Some(Value::Array(a.into_iter().filter_map(yaml_to_toml).collect())),
+        Yaml::Hash(h)    => {
+            let table = h.into_iter()
+                .filter_map(|(k, v)| yaml_key(k).and_then(|k| yaml_to_toml(v).map(|v| (k, v))))
+                .collect();
+            Some(Value::Table(table))
+        },
+        _ => None,
+    }
+}
+
+fn toml_to_yaml(v: Value) -> Yaml {
+    match v {
+        Value::String(s)   => Yaml::String(s),
+        Value::Integer(i)  => Yaml::Integer(i),
+        Value::Float(f)    => Yaml::Real(format!("{:?}", f)),
+        Value::Boolean(b)  => Yaml::Boolean(b),
+        Value::Datetime(d) => Yaml::String(d),
+        Value::Array(a)    => Yaml::Array(a.into_iter().map(toml_to_yaml).collect()),
+        Value::Table(t)    => {
+            let mut h = Hash::new();
+            for (k, v) in t {
+                h.insert(Yaml::String(k), toml_to_yaml(v));
+            }
+            Yaml::Hash(h)
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs::remove_dir_all;
+
+    use toml::Value;
+    use yaml_rust::YamlLoader;
+
+    use libimagstore::store::Store;
+    use libimagentrylink::kind::LinkKind;
+    use libimagentrytag::tagable::Tagable;
+
+    use note::Note;
+    use super::{Imported, MarkdownLink, MarkdownNote, split_front_matter, yaml_links};
+
+    fn link(id: &str, kind: LinkKind, label: Option<&str>) -> MarkdownLink {
+        MarkdownLink { id: String::from(id), kind: kind, label: label.map(String::from) }
+    }
+
+    fn links(yaml: &str) -> Vec<MarkdownLink> {
+        yaml_links(YamlLoader::load_from_str(yaml).unwrap().remove(0)).unwrap()
+    }
+
+    #[test]
+    fn test_split_front_matter() {
+        assert_eq!((Some("a: 1\n"), "content"), split_front_matter("---\na: 1\n---\ncontent"));
+        assert_eq!((Some("a: 1\n"), "content"), split_front_matter("---\na: 1\n...\ncontent"));
+        assert_eq!((Some("a: 1\n"), ""), split_front_matter("---\na: 1\n---"));
+        assert_eq!((Some(""), "content"), split_front_matter("---\n---\ncontent"));
+    }
+
+    #[test]
+    fn test_split_front_matter_crlf() {
+        assert_eq!((Some("a: 1\r\n"), "content\r\n"),
+                   split_front_matter("---\r\na: 1\r\n---\r\ncontent\r\n"));
+    }
+
+    #[test]
+    fn test_split_no_front_matter() {
+        assert_eq!((None, "content"), split_front_matter("content"));
+        assert_eq!((None, ""), split_front_matter(""));
+        assert_eq!((None, "--- a\ncontent"), split_front_matter("--- a\ncontent"));
+
+        // Without a closing line, the whole file is content
+        assert_eq!((None, "---\na: 1\ncontent"), split_front_matter("---\na: 1\ncontent"));
+    }
+
+    #[test]
+    fn test_parse_without_front_matter() {
+        let note = MarkdownNote::parse("# Title\ncontent").unwrap();
+        assert_eq!(None, note.title);
+        assert!(note.tags.is_empty());
+        assert_eq!("# Title\ncontent", note.content);
+
+        let note = MarkdownNote::parse("---\n---\ncontent").unwrap();
+        assert_eq!(None, note.title);
+        assert_eq!("content", note.content);
+    }
+
+    #[test]
+    fn test_yaml_links() {
+        assert_eq!(vec![link("a", LinkKind::Link, None)], links("a"));
+        assert_eq!(vec![link("a", LinkKind::Link, None), link("b", LinkKind::Link, None)],
+                   links("[a, b]"));
+        assert_eq!(vec![link("a", LinkKind::Link, None),
+                        link("b", LinkKind::References, Some("see b")),
+                        link("c", LinkKind::Custom(String::from("inspired-by")), None),
+                        link("d", LinkKind::Link, None)],
+                   links("- a\n\
+                          - id: b\n  kind: references\n  label: see b\n\
+                          - id: c\n  kind: inspired-by\n\
+                          - id: d\n"));
+        assert!(links("~").is_empty());
+    }
+
+    #[test]
+    fn test_yaml_links_without_id() {
+        let yaml = YamlLoader::load_from_str("- kind: references").unwrap().remove(0);
+        assert!(yaml_links(yaml).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut nested = BTreeMap::new();
+        nested.insert(String::from("room"), Value::Integer(2));
+        nested.insert(String::from("building"), Value::String(String::from("B")));
+
+        let mut fields = BTreeMap::new();
+        fields.insert(String::from("location"), Value::Table(nested));
+        fields.insert(String::from("attendees"),
+                      Value::Array(vec![Value::String(String::from("bob")),
+                                        Value::String(String::from("carol"))]));
+        fields.insert(String::from("done"), Value::Boolean(true));
+        fields.insert(String::from("hours"), Value::Float(1.5));
+
+        let note = MarkdownNote {
+            id: Some(String::from("meeting-with-bob")),
+            title: Some(String::from("Meeting with Bob")),
+            tags: vec![String::from("work"), String::from("work/meetings")],
+            links: vec![link("plan", LinkKind::Link, None),
+                        link("minutes", LinkKind::References, Some("the minutes")),
+                        link("bob", LinkKind::Custom(String::from("with")), None)],
+            urls: vec![String::from("http://example.com/")],
+            fields: fields,
+            content: String::from("# Meeting\n\n---\n\nnotes\n"),
+        };
+
+        let markdown = note.to_markdown().unwrap();
+        assert_eq!(note, MarkdownNote::parse(&markdown).unwrap());
+    }
+
+    #[test]
+    fn test_import_invalid_tags() {
+        let mut path = env::temp_dir();
+        path.push("imag-notes-test-markdown-tags");
+        let _ = remove_dir_all(&path);
+        let store = Store::new(path, None).unwrap();
+
+        let note = MarkdownNote {
+            id: None,
+            title: Some(String::from("Tags")),
+            tags: vec![String::from("work"), String::from("my tag"), String::from("2016"),
+                       String::from("C++")],
+            links: vec![],
+            urls: vec![],
+            fields: BTreeMap::new(),
+            content: String::from("content"),
+        };
+
+        assert_eq!(Imported::Created(String::from("tags")), note.import(&store, "").unwrap());
+
+        let imported = Note::retrieve(&store, String::from("tags")).unwrap();
+        assert_eq!(vec![String::from("work"), String::from("my-tag")],
+                   imported.get_tags().unwrap());
+    }
+
+}
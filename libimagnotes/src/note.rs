@@ -26,12 +26,18 @@ pub struct Note<'a> {
 
 impl<'a> Note<'a> {
 
-    pub fn new(store: &Store, name: String, text: String) -> Result<Note> {
+    /// Create a new note with the title `title`
+    ///
+    /// The id of the note is generated from the title, see `slug()`. If there is a note with this
+    /// id already, a number is appended, so "Meeting" gets the id "meeting", or "meeting-2" if
+    /// "meeting" is taken.
+    pub fn new(store: &Store, title: String, text: String) -> Result<Note> {
         use std::ops::DerefMut;
 
-        debug!("Creating new Note: '{}'", name);
+        let id = unique_id(store, &slug(&title));
+        debug!("Creating new Note: '{}' with id '{}'", title, id);
         let fle = {
-            let lockentry = store.create(ModuleEntryPath::new(name.clone()).into_storeid());
+            let lockentry = store.create(ModuleEntryPath::new(id).into_storeid());
             if lockentry.is_err() {
                 return Err(NE::new(NEK::StoreWriteError, Some(Box::new(lockentry.unwrap_err()))));
             }
@@ -48,7 +54,7 @@ impl<'a> Note<'a> {
                         return Err(NE::new(kind, Some(Box::new(setres.unwrap_err()))));
                     }
 
-                    let setres = header.set("note.name", Value::String(name));
+                    let setres = header.set("note.name", Value::String(title));
                     if setres.is_err() {
                         let kind = NEK::StoreWriteError;
                         return Err(NE::new(kind, Some(Box::new(setres.unwrap_err()))));
@@ -64,6 +70,12 @@ impl<'a> Note<'a> {
         Ok(Note { entry: fle })
     }
 
+    /// Get the id of the note, which is used to retrieve or delete it
+    pub fn id(&self) -> Result<String> {
+        note_id(self.entry.get_location()).ok_or(NE::new(NEK::StoreReadError, None))
+    }
+
+    /// Set the title of the note. The id of the note does not change.
     pub fn set_name(&mut self, n: String) -> Result<()> {
         let mut header = self.entry.get_header_mut();
         header.set("note.name", Value::String(n))
@@ -91,19 +103,73 @@ impl<'a> Note<'a> {
         self.entry.get_content()
     }
 
-    pub fn delete(store: &Store, name: String) -> Result<()> {
-        store.delete(ModuleEntryPath::new(name).into_storeid())
+    /// Get the entry of the note, for example to pass it to a lister
+    pub fn into_entry(self) -> FileLockEntry<'a> {
+        self.entry
+    }
+
+    /// Delete the note with the id `id`
+    pub fn delete(store: &Store, id: String) -> Result<()> {
+        store.delete(ModuleEntryPath::new(id).into_storeid())
             .map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e))))
     }
 
-    pub fn retrieve(store: &Store, name: String) -> Result<Note> {
-        store.retrieve(ModuleEntryPath::new(name).into_storeid())
+    /// Retrieve the note with the id `id`
+    pub fn retrieve(store: &Store, id: String) -> Result<Note> {
+        store.retrieve(ModuleEntryPath::new(id).into_storeid())
             .map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e))))
             .map(|entry| Note { entry: entry })
     }
 
+    /// Check whether there is a note with the id `id`
+    pub fn exists(store: &Store, id: &str) -> bool {
+        store.path().join(ModuleEntryPath::new(id).into_storeid()).is_file()
+    }
+
+    /// Get the id of the note with the store id `id`, or None if `id` is not the id of a note
+    pub fn id_of(store: &Store, id: &StoreId) -> Option<String> {
+        let notes = store.path().join("notes");
+        if id.parent() == Some(notes.as_path()) {
+            note_id(id)
+        } else {
+            None
+        }
+    }
+
+    /// Get the ids of the notes with the title `title`
+    ///
+    /// Notes which cannot be read are skipped.
+    pub fn ids_by_title(store: &Store, title: &str) -> Result<Vec<String>> {
+        let ids = try!(store.retrieve_for_module("notes")
+            .map_err(|e| NE::new(NEK::StoreReadError, Some(Box::new(e)))));
+
+        let mut found = vec![];
+        for id in ids.filter(|id| id.is_file()) {
+            let entry = match store.retrieve_copy(id.clone()) {
+                Ok(entry) => entry,
+                Err(e)    => {
+                    warn!("Skipping {:?}, could not read it: {:?}", id, e);
+                    continue;
+                },
+            };
+
+            match entry.get_header().read("note.name") {
+                Ok(Some(Value::String(ref s))) if s == title => {
+                    if let Some(note_id) = note_id(&id) {
+                        found.push(note_id);
+                    }
+                },
+                Ok(_)  => { },
+                Err(e) => warn!("Skipping {:?}, could not read its title: {:?}", id, e),
+            }
+        }
+        Ok(found)
+    }
+
+    /// Get all notes. Directories below "notes" are skipped.
     pub fn all_notes(store: &Store) -> Result<NoteIterator> {
         store.retrieve_for_module("notes")
+            .map(|iter| StoreIdIterator::new(Box::new(iter.filter(|id| id.is_file()))))
             .map(|iter| NoteIterator::new(store, iter))
             .map_err(|e| NE::new(NEK::StoreReadError, Some(Box::new(e))))
     }
@@ -146,6 +212,48 @@ impl<'a> Tagable for Note<'a> {
 
 }
 
+/// Generate an id from a title: "Meeting with Bob, 2016" becomes "meeting-with-bob-2016"
+pub fn slug(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+
+    if slug.is_empty() {
+        String::from("note")
+    } else {
+        slug
+    }
+}
+
+/// Append a number to `slug` if there is a note with this id already
+fn unique_id(store: &Store, slug: &str) -> String {
+    if !Note::exists(store, slug) {
+        return String::from(slug);
+    }
+
+    (2..)
+        .map(|n| format!("{}-{}", slug, n))
+        .find(|id| !Note::exists(store, id))
+        .unwrap() // there are more numbers than notes
+}
+
+/// Get the id of the note from its store id, "meeting" for "notes/meeting~0.1.0"
+fn note_id(id: &StoreId) -> Option<String> {
+    id.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('~').next())
+        .map(String::from)
+}
+
 trait FromStoreId {
     fn from_storeid<'a>(&'a Store, StoreId) -> Result<Note<'a>>;
 }
@@ -172,6 +280,14 @@ impl<'a> Deref for Note<'a> {
 
 }
 
+impl<'a> DerefMut for Note<'a> {
+
+    fn deref_mut(&mut self) -> &mut FileLockEntry<'a> {
+        &mut self.entry
+    }
+
+}
+
 #[derive(Debug)]
 pub struct NoteIterator<'a> {
     store: &'a Store,
@@ -200,3 +316,76 @@ impl<'a> Iterator for NoteIterator<'a> {
 
 }
 
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::{File, create_dir_all, remove_dir_all};
+    use std::io::Write;
+
+    use libimagstore::store::Store;
+
+    use super::{Note, slug, unique_id};
+
+    fn store(name: &str) -> Store {
+        let mut path = env::temp_dir();
+        path.push(format!("imag-notes-test-{}", name));
+        let _ = remove_dir_all(&path);
+        Store::new(path, None).unwrap()
+    }
+
+    /// Create a note and write it to the store
+    fn create(store: &Store, title: &str) -> String {
+        let note = Note::new(store, String::from(title), String::new()).unwrap();
+        note.id().unwrap()
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!("meeting-with-bob-2016", slug("Meeting with Bob, 2016"));
+        assert_eq!("hello-world", slug("  --Hello!!  World--  "));
+        assert_eq!("c-notes", slug("C++ notes"));
+        assert_eq!("a-b", slug("a/b"));
+    }
+
+    #[test]
+    fn test_slug_unicode() {
+        assert_eq!("über-café", slug("Über Café"));
+        assert_eq!("日本語", slug("日本語"));
+    }
+
+    #[test]
+    fn test_slug_empty() {
+        assert_eq!("note", slug(""));
+        assert_eq!("note", slug("!!! ..."));
+    }
+
+    #[test]
+    fn test_unique_id() {
+        let store = store("unique-id");
+        assert_eq!("meeting", unique_id(&store, "meeting"));
+
+        assert_eq!("meeting", create(&store, "Meeting"));
+        assert_eq!("meeting-2", unique_id(&store, "meeting"));
+        assert_eq!("meeting-2", create(&store, "Meeting"));
+        assert_eq!("meeting-3", create(&store, "meeting!"));
+        assert_eq!("other", unique_id(&store, "other"));
+    }
+
+    #[test]
+    fn test_ids_by_title_skips_unreadable_notes() {
+        let store = store("ids-by-title");
+        assert_eq!("meeting", create(&store, "Meeting"));
+        assert_eq!("meeting-2", create(&store, "Meeting"));
+        assert_eq!("other", create(&store, "Other"));
+
+        let notes = store.path().join("notes");
+        create_dir_all(&notes).unwrap();
+        File::create(notes.join("broken~0.1.0")).unwrap().write_all(b"no header").unwrap();
+
+        let mut ids = Note::ids_by_title(&store, "Meeting").unwrap();
+        ids.sort();
+        assert_eq!(vec!["meeting", "meeting-2"], ids);
+        assert!(Note::ids_by_title(&store, "Nothing").unwrap().is_empty());
+    }
+
+}
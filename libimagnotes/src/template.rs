@@ -0,0 +1,273 @@
+//! Templates for new notes
+//!
+//! A template is an entry in the "notes/templates" namespace of the store, for example
+//! "notes/templates/meeting~0.1.0". A note created from a template gets the content of the
+//! template, the tags of the template and its other header fields, except the ones in the "imag"
+//! section.
+//!
+//! The placeholders `{{title}}`, `{{date}}` and `{{author}}` in the content and in the header
+//! fields are replaced with the title of the note, the current date and the author when the note
+//! is created.
+
+use std::collections::BTreeMap;
+
+use chrono::Local;
+use toml::Value;
+
+use libimagstore::storeid::IntoStoreId;
+use libimagstore::store::Entry;
+use libimagstore::store::Store;
+use libimagentrytag::tag::Tag;
+use libimagentrytag::tagable::Tagable;
+
+use module_path::ModuleEntryPath;
+use note::Note;
+use result::Result;
+use error::NoteError as NE;
+use error::NoteErrorKind as NEK;
+
+pub struct NoteTemplate {
+    entry: Entry,
+}
+
+impl NoteTemplate {
+
+    /// Load the template with the name `name`
+    pub fn load(store: &Store, name: &str) -> Result<NoteTemplate> {
+        let id = ModuleEntryPath::new(format!("templates/{}", name)).into_storeid();
+        if !store.path().join(&id).is_file() {
+            debug!("No template at {:?}", id);
+            return Err(NE::new(NEK::TemplateNotFound, None));
+        }
+
+        store.retrieve_copy(id)
+            .map(|entry| NoteTemplate { entry: entry })
+            .map_err(|e| NE::new(NEK::StoreReadError, Some(Box::new(e))))
+    }
+
+    /// Create a note with the title `title` from the template
+    pub fn create_note<'a>(&self, store: &'a Store, title: String, author: &str)
+        -> Result<Note<'a>>
+    {
+        let date = Local::today().naive_local().format("%Y-%m-%d").to_string();
+        let vars = [
+            ("title", title.clone()),
+            ("date", date),
+            ("author", String::from(author)),
+        ];
+
+        let content = substitute(self.entry.get_content(), &vars);
+        let mut note = try!(Note::new(store, title, content));
+
+        let header = substitute_value(self.entry.get_header().header().clone(), &vars);
+        if let Value::Table(sections) = header {
+            for (key, value) in sections {
+                match &key[..] {
+                    "imag" => try!(set_tags(&mut note, value)),
+                    "note" => try!(set_note_fields(&mut note, value)),
+                    _      => {
+                        try!(note.get_header_mut()
+                             .set(&key, value)
+                             .map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e)))));
+                    },
+                }
+            }
+        }
+
+        Ok(note)
+    }
+
+}
+
+/// Set the tags of the "imag" section of the template on the note
+fn set_tags(note: &mut Note, imag: Value) -> Result<()> {
+    let tags = match imag {
+        Value::Table(mut t) => t.remove("tags"),
+        _ => None,
+    };
+
+    match tags {
+        Some(Value::Array(tags)) => {
+            let tags : Vec<Tag> = tags.into_iter()
+                .filter_map(|t| match t {
+                    Value::String(s) => Some(s),
+                    _ => None,
+                })
+                .collect();
+
+            note.set_tags(tags).map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e))))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Copy the fields of the "note" section of the template to the note, except the title
+fn set_note_fields(note: &mut Note, fields: Value) -> Result<()> {
+    let mut table = match note.get_header().read("note") {
+        Ok(Some(Value::Table(t))) => t,
+        Ok(_)  => BTreeMap::new(),
+        Err(e) => return Err(NE::new(NEK::StoreReadError, Some(Box::new(e)))),
+    };
+
+    if let Value::Table(fields) = fields {
+        for (key, value) in fields {
+            if key != "name" {
+                table.insert(key, value);
+            }
+        }
+    }
+
+    note.get_header_mut()
+        .set("note", Value::Table(table))
+        .map_err(|e| NE::new(NEK::StoreWriteError, Some(Box::new(e))))
+        .map(|_| ())
+}
+
+/// Replace the placeholders `{{key}}` in `s` with their values
+///
+/// The string is scanned once, so placeholders in the values are not replaced. Unknown
+/// placeholders are kept.
+fn substitute(s: &str, vars: &[(&str, String)]) -> String {
+    let mut result = String::new();
+    let mut rest = s;
+
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find("}}")
+            .and_then(|end| {
+                let key = &rest[2..end];
+                vars.iter()
+                    .find(|&&(k, _)| k == key)
+                    .map(|&(_, ref value)| (value, end + 2))
+            });
+
+        match value {
+            Some((value, len)) => {
+                result.push_str(value);
+                rest = &rest[len..];
+            },
+            None => {
+                result.push_str("{{");
+                rest = &rest[2..];
+            },
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Replace the placeholders in all strings of `v`
+fn substitute_value(v: Value, vars: &[(&str, String)]) -> Value {
+    match v {
+        Value::String(s) => Value::String(substitute(&s, vars)),
+        Value::Array(a)  => {
+            Value::Array(a.into_iter().map(|v| substitute_value(v, vars)).collect())
+        },
+        Value::Table(t)  => {
+            Value::Table(t.into_iter().map(|(k, v)| (k, substitute_value(v, vars))).collect())
+        },
+        v => v,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::remove_dir_all;
+    use std::path::PathBuf;
+
+    use chrono::Local;
+    use toml::Value;
+
+    use libimagstore::store::Entry;
+    use libimagstore::store::Store;
+    use libimagstore::storeid::StoreId;
+    use libimagentrytag::tagable::Tagable;
+
+    use super::{NoteTemplate, substitute, substitute_value};
+
+    fn vars() -> Vec<(&'static str, String)> {
+        vec![
+            ("title", String::from("Weekly {{date}}")),
+            ("date", String::from("2016-07-01")),
+            ("author", String::from("alice")),
+        ]
+    }
+
+    fn s(s: &str) -> Value {
+        Value::String(String::from(s))
+    }
+
+    fn table(fields: Vec<(&str, Value)>) -> Value {
+        Value::Table(fields.into_iter().map(|(k, v)| (String::from(k), v)).collect())
+    }
+
+    #[test]
+    fn test_substitute() {
+        let vars = vars();
+        assert_eq!("by alice", substitute("by {{author}}", &vars));
+        assert_eq!("alice, alice", substitute("{{author}}, {{author}}", &vars));
+        assert_eq!("no placeholders", substitute("no placeholders", &vars));
+        assert_eq!("{{unknown}} alice", substitute("{{unknown}} {{author}}", &vars));
+        assert_eq!("{{author", substitute("{{author", &vars));
+        assert_eq!("{ {author} }}", substitute("{ {author} }}", &vars));
+    }
+
+    #[test]
+    fn test_substitute_single_pass() {
+        // The title contains a placeholder, which is not replaced
+        assert_eq!("# Weekly {{date}}", substitute("# {{title}}", &vars()));
+        assert_eq!("Weekly {{date}}, 2016-07-01", substitute("{{title}}, {{date}}", &vars()));
+    }
+
+    #[test]
+    fn test_substitute_value() {
+        let v = table(vec![("a", s("{{author}}")),
+                           ("b", Value::Array(vec![s("x {{author}}"), Value::Integer(1)])),
+                           ("c", table(vec![("d", s("{{author}}!"))]))]);
+        let expected = table(vec![("a", s("alice")),
+                                  ("b", Value::Array(vec![s("x alice"), Value::Integer(1)])),
+                                  ("c", table(vec![("d", s("alice!"))]))]);
+        assert_eq!(expected, substitute_value(v, &vars()));
+    }
+
+    #[test]
+    fn test_create_note() {
+        let mut path = env::temp_dir();
+        path.push("imag-notes-test-template");
+        let _ = remove_dir_all(&path);
+        let store = Store::new(path, None).unwrap();
+
+        let mut entry = Entry::new(StoreId::from(PathBuf::from("/notes/templates/t~0.1.0")));
+        *entry.get_content_mut() = String::from("# {{title}}\n{{date}} by {{author}}");
+        {
+            let header = entry.get_header_mut();
+            header.set("imag.tags", Value::Array(vec![s("meeting")])).unwrap();
+            header.set("imag.links", Value::Array(vec![s("/notes/other~0.1.0")])).unwrap();
+            header.set("imag.custom", s("{{author}}")).unwrap();
+            header.set("note", table(vec![("name", s("Template")),
+                                          ("attendees", s("{{author}}"))])).unwrap();
+            header.set("project", table(vec![("name", s("{{title}}"))])).unwrap();
+        }
+        let template = NoteTemplate { entry: entry };
+
+        let note = template.create_note(&store, String::from("Weekly {{date}}"), "alice").unwrap();
+        let date = Local::today().naive_local().format("%Y-%m-%d").to_string();
+
+        assert_eq!(format!("# Weekly {{{{date}}}}\n{} by alice", date), *note.get_text());
+        assert_eq!("Weekly {{date}}", note.get_name().unwrap());
+        assert_eq!(vec![String::from("meeting")], note.get_tags().unwrap());
+
+        let header = note.get_header();
+        assert_eq!(Some(s("alice")), header.read("note.attendees").unwrap());
+        assert_eq!(Some(s("Weekly {{date}}")), header.read("project.name").unwrap());
+
+        // Only the tags are copied from the "imag" section
+        assert_eq!(Some(Value::Array(vec![])), header.read("imag.links").unwrap());
+        assert_eq!(None, header.read("imag.custom").unwrap());
+    }
+
+}
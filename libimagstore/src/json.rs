@@ -0,0 +1,98 @@
+//! Conversion of the TOML structures of the store into JSON
+//!
+//! The header of an entry is a TOML tree, which is not what most tools on the commandline can
+//! work with. This module provides `ToJson` implementations for `EntryHeader` and `Entry`, so the
+//! store data can be piped into tools like `jq`.
+
+use std::collections::BTreeMap;
+
+use rustc_serialize::json::{Json, ToJson};
+use toml::Value;
+
+use store::{Entry, EntryHeader};
+
+/// Convert a TOML value into a JSON value
+///
+/// Datetime values are converted to JSON strings, as JSON has no notion of a date.
+pub fn toml_to_json(v: &Value) -> Json {
+    match v {
+        &Value::String(ref s)   => Json::String(s.clone()),
+        &Value::Integer(i)      => Json::I64(i),
+        &Value::Float(f)        => Json::F64(f),
+        &Value::Boolean(b)      => Json::Boolean(b),
+        &Value::Datetime(ref s) => Json::String(s.clone()),
+        &Value::Array(ref a)    => Json::Array(a.iter().map(toml_to_json).collect()),
+        &Value::Table(ref t)    => {
+            Json::Object(t.iter().map(|(k, v)| (k.clone(), toml_to_json(v))).collect())
+        },
+    }
+}
+
+impl ToJson for EntryHeader {
+
+    fn to_json(&self) -> Json {
+        toml_to_json(self.header())
+    }
+
+}
+
+/// An entry is converted to a JSON object with the keys "location", "header" and "content".
+impl ToJson for Entry {
+
+    fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        let location = self.get_location()
+            .to_str()
+            .map(|s| Json::String(String::from(s)))
+            .unwrap_or(Json::Null);
+
+        obj.insert(String::from("location"), location);
+        obj.insert(String::from("header"), self.get_header().to_json());
+        obj.insert(String::from("content"), Json::String(self.get_content().clone()));
+        Json::Object(obj)
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use rustc_serialize::json::{Json, ToJson};
+    use toml::Value;
+
+    use super::toml_to_json;
+    use store::EntryHeader;
+
+    #[test]
+    fn test_scalars_to_json() {
+        assert_eq!(Json::String(String::from("a")), toml_to_json(&Value::String(String::from("a"))));
+        assert_eq!(Json::I64(1), toml_to_json(&Value::Integer(1)));
+        assert_eq!(Json::F64(1.5), toml_to_json(&Value::Float(1.5)));
+        assert_eq!(Json::Boolean(true), toml_to_json(&Value::Boolean(true)));
+    }
+
+    #[test]
+    fn test_nested_to_json() {
+        let mut inner = BTreeMap::new();
+        inner.insert(String::from("tags"), Value::Array(vec![
+            Value::String(String::from("a")),
+            Value::String(String::from("b")),
+        ]));
+        let mut outer = BTreeMap::new();
+        outer.insert(String::from("imag"), Value::Table(inner));
+
+        let json = toml_to_json(&Value::Table(outer));
+        assert_eq!("{\"imag\":{\"tags\":[\"a\",\"b\"]}}", format!("{}", json));
+    }
+
+    #[test]
+    fn test_header_to_json() {
+        let json = EntryHeader::new().to_json();
+        let links = json.find_path(&["imag", "links"]);
+
+        assert_eq!(Some(&Json::Array(vec![])), links);
+        assert!(json.find_path(&["imag", "version"]).map(|v| v.is_string()).unwrap_or(false));
+    }
+
+}
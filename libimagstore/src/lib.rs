@@ -18,6 +18,7 @@ extern crate fs2;
 extern crate glob;
 #[macro_use] extern crate lazy_static;
 extern crate regex;
+extern crate rustc_serialize;
 extern crate toml;
 #[cfg(test)] extern crate tempdir;
 extern crate semver;
@@ -27,6 +28,7 @@ extern crate walkdir;
 pub mod storeid;
 pub mod error;
 pub mod hook;
+pub mod json;
 pub mod store;
 mod configuration;
 mod lazyfile;
@@ -1,3 +1,4 @@
+use std::fs::File;
 use std::path::PathBuf;
 
 use toml::Value;
@@ -5,25 +6,130 @@ use toml::Value;
 use libimagstore::hook::Hook;
 use libimagstore::hook::accessor::HookDataAccessor as HDA;
 use libimagstore::hook::accessor::HookDataAccessorProvider;
+use libimagstore::hook::accessor::MutableHookDataAccessor;
 use libimagstore::hook::accessor::NonMutableHookDataAccessor;
+use libimagstore::hook::error::{HookError, HookErrorKind};
+use libimagstore::hook::position::HookPosition;
 use libimagstore::hook::result::HookResult;
+use libimagstore::store::Entry;
 use libimagstore::store::FileLockEntry;
+use libimagstore::storeid::StoreId;
 use libimagentrylink::internal::InternalLinker;
 use libimagutil::trace::trace_error;
 
+/// What the hook does if an entry links to an entry which does not exist
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Mode {
+    /// Warn about the dangling link
+    Warn,
+
+    /// Fail the hook, so the store action fails
+    Deny,
+
+    /// Remove the dangling link from the entry
+    Prune,
+}
+
+impl Mode {
+
+    fn from_name(s: &str) -> Option<Mode> {
+        match s {
+            "warn"  => Some(Mode::Warn),
+            "deny"  => Some(Mode::Deny),
+            "prune" => Some(Mode::Prune),
+            _       => None,
+        }
+    }
+
+}
+
+/// Verifies that the entries an entry links to exist
+///
+/// The mode is configured with the `mode` key in the hook configuration, which is one of "warn"
+/// (the default), "deny" or "prune". Links to existing entries which do not link back are
+/// reported in all modes.
+///
+/// The "prune" mode modifies the entry, so it is only possible at the positions where the store
+/// passes the entry mutably to its hooks: post-create, post-retrieve, pre-update and post-update.
+/// At the other positions, "prune" is rejected and the hook warns instead.
 #[derive(Debug, Clone)]
 pub struct LinkedEntriesExistHook {
     store_location: PathBuf,
+    position: HookPosition,
+    mode: Mode,
 }
 
 impl LinkedEntriesExistHook {
 
-    pub fn new(store_location: PathBuf) -> LinkedEntriesExistHook {
+    pub fn new(store_location: PathBuf, position: HookPosition) -> LinkedEntriesExistHook {
         LinkedEntriesExistHook {
             store_location: store_location,
+            position: position,
+            mode: Mode::Warn,
         }
     }
 
+    /// Whether the store passes the entry mutably to hooks at the position of this hook
+    fn is_mutable_position(&self) -> bool {
+        match self.position {
+            HookPosition::PostCreate   |
+            HookPosition::PostRetrieve |
+            HookPosition::PreUpdate    |
+            HookPosition::PostUpdate   => true,
+            _                          => false,
+        }
+    }
+
+    /// Check the links of the entry and get the links to entries which do not exist
+    fn dangling_links(&self, fle: &FileLockEntry) -> HookResult<Vec<StoreId>> {
+        let links = try!(fle.get_internal_links()
+            .map_err(|e| {
+                warn!("Couldn't execute Link-Verify hook");
+                trace_error(&e);
+                HookError::new(HookErrorKind::HookExecutionError, Some(Box::new(e)))
+            }));
+
+        let mut dangling = vec![];
+        for link in links {
+            let mut path = self.store_location.clone();
+            path.push(&link);
+            if !path.exists() {
+                warn!("File link does not exist: {:?} -> {:?}", fle.get_location(), path);
+                dangling.push(link);
+            } else if !path.is_file() {
+                warn!("File link is not a file: {:?} -> {:?}", fle.get_location(), path);
+                dangling.push(link);
+            } else if !self.links_back(&path, &link, fle.get_location()) {
+                warn!("Link is one-sided, there is no backlink: {:?} -> {:?}",
+                      fle.get_location(), path);
+            }
+        }
+
+        Ok(dangling)
+    }
+
+    /// Check whether the entry at `path` links back to `from`. If the entry cannot be read, it is
+    /// assumed that it links back.
+    fn links_back(&self, path: &PathBuf, link: &StoreId, from: &StoreId) -> bool {
+        File::open(path)
+            .map_err(|e| {
+                debug!("Could not open linked entry {:?}: {:?}", path, e);
+            })
+            .ok()
+            .and_then(|mut file| {
+                Entry::from_file(link.clone(), &mut file)
+                    .map_err(|e| trace_error(&e))
+                    .ok()
+            })
+            .and_then(|entry| {
+                entry.get_internal_links()
+                    .map_err(|e| trace_error(&e))
+                    .ok()
+            })
+            .map(|links| links.contains(from))
+            .unwrap_or(true)
+    }
+
 }
 
 impl Hook for LinkedEntriesExistHook {
@@ -32,8 +138,27 @@ impl Hook for LinkedEntriesExistHook {
         "stdhook_linked_entries_exist"
     }
 
-    fn set_config(&mut self, _: &Value) {
-        () // We are not configurable here.
+    fn set_config(&mut self, cfg: &Value) {
+        let mode = match cfg {
+            &Value::Table(ref t) => t.get("mode"),
+            _ => None,
+        };
+
+        match mode {
+            Some(&Value::String(ref s)) => {
+                match Mode::from_name(s) {
+                    Some(Mode::Prune) if !self.is_mutable_position() => {
+                        warn!("Mode 'prune' for {} is not possible at {:?}, using 'warn'",
+                              self.name(), self.position);
+                        self.mode = Mode::Warn;
+                    },
+                    Some(mode) => self.mode = mode,
+                    None => warn!("Unknown mode for {}: '{}', using 'warn'", self.name(), s),
+                }
+            },
+            Some(_) => warn!("Mode for {} is not a string, using 'warn'", self.name()),
+            None => debug!("No mode configured for {}, using 'warn'", self.name()),
+        }
     }
 
 }
@@ -41,7 +166,10 @@ impl Hook for LinkedEntriesExistHook {
 impl HookDataAccessorProvider for LinkedEntriesExistHook {
 
     fn accessor(&self) -> HDA {
-        HDA::NonMutableAccess(self)
+        match self.mode {
+            Mode::Prune => HDA::MutableAccess(self),
+            _           => HDA::NonMutableAccess(self),
+        }
     }
 
 }
@@ -50,26 +178,145 @@ impl NonMutableHookDataAccessor for LinkedEntriesExistHook {
 
     fn access(&self, fle: &FileLockEntry) -> HookResult<()> {
         debug!("[LINKVERIFY HOOK] {:?}", fle.get_location());
-        let _ = fle.get_internal_links()
-            .map(|links| {
-                for link in links {
-                    let mut path = self.store_location.clone();
-                    path.push(link);
-                    if !path.exists() {
-                        warn!("File link does not exist: {:?} -> {:?}", fle.get_location(), path);
-                    } else {
-                        if !path.is_file() {
-                            warn!("File link is not a file: {:?} -> {:?}", fle.get_location(), path);
-                        }
-                    }
-                }
-            })
-            .map_err(|e| {
-                warn!("Couldn't execute Link-Verify hook");
-                trace_error(&e);
-            });
+        let dangling = match self.dangling_links(fle) {
+            Ok(dangling) => dangling,
+            Err(e) => {
+                // Already reported, but only fail the action if we are asked to
+                return if self.mode == Mode::Deny { Err(e) } else { Ok(()) };
+            },
+        };
+
+        if self.mode == Mode::Deny && !dangling.is_empty() {
+            warn!("Denying action on {:?}, it links to entries which do not exist",
+                  fle.get_location());
+            return Err(HookError::new(HookErrorKind::HookExecutionError, None));
+        }
+
         Ok(())
     }
 
 }
 
+impl MutableHookDataAccessor for LinkedEntriesExistHook {
+
+    fn access_mut(&self, fle: &mut FileLockEntry) -> HookResult<()> {
+        debug!("[LINKVERIFY HOOK] {:?}", fle.get_location());
+        let dangling = try!(self.dangling_links(fle));
+
+        for link in dangling {
+            info!("Removing link to non-existing entry: {:?} -> {:?}", fle.get_location(), link);
+            try!(fle.remove_dangling_link(&link)
+                .map_err(|e| HookError::new(HookErrorKind::HookExecutionError, Some(Box::new(e)))));
+        }
+
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs::remove_dir_all;
+
+    use toml::Value;
+
+    use libimagstore::hook::Hook;
+    use libimagstore::hook::accessor::HookDataAccessor as HDA;
+    use libimagstore::hook::accessor::HookDataAccessorProvider;
+    use libimagstore::hook::accessor::MutableHookDataAccessor;
+    use libimagstore::hook::accessor::NonMutableHookDataAccessor;
+    use libimagstore::hook::position::HookPosition;
+    use libimagstore::store::Entry;
+    use libimagstore::store::FileLockEntry;
+    use libimagstore::store::Store;
+    use libimagstore::storeid::StoreId;
+    use libimagentrylink::internal::InternalLinker;
+
+    use super::{LinkedEntriesExistHook, Mode};
+
+    fn store(name: &str) -> Store {
+        let mut path = env::temp_dir();
+        path.push(format!("imag-linkverify-test-{}", name));
+        let _ = remove_dir_all(&path);
+        Store::new(path, None).unwrap()
+    }
+
+    fn id(store: &Store, name: &str) -> StoreId {
+        let mut path = store.path().clone();
+        path.push(name);
+        StoreId::from(path)
+    }
+
+    fn hook(store: &Store, mode: &str, position: HookPosition) -> LinkedEntriesExistHook {
+        let mut cfg = BTreeMap::new();
+        cfg.insert(String::from("mode"), Value::String(String::from(mode)));
+
+        let mut hook = LinkedEntriesExistHook::new(store.path().clone(), position);
+        hook.set_config(&Value::Table(cfg));
+        hook
+    }
+
+    /// Create the entry "a", which links to the existing entry "b" and to "d", which does not
+    /// exist
+    fn entry_with_dangling_link<'a>(store: &'a Store) -> FileLockEntry<'a> {
+        let mut a = store.create(id(store, "a~0.1.0")).unwrap();
+        {
+            let mut b = store.create(id(store, "b~0.1.0")).unwrap();
+            a.add_internal_link(&mut b).unwrap();
+        }
+
+        let mut d = Entry::new(id(store, "d~0.1.0"));
+        a.add_internal_link(&mut d).unwrap();
+        a
+    }
+
+    #[test]
+    fn test_warn() {
+        let store = store("warn");
+        let a = entry_with_dangling_link(&store);
+        let hook = hook(&store, "warn", HookPosition::PreUpdate);
+
+        assert_eq!(Mode::Warn, hook.mode);
+        assert!(hook.access(&a).is_ok());
+        assert_eq!(2, a.get_internal_links().unwrap().len());
+    }
+
+    #[test]
+    fn test_deny() {
+        let store = store("deny");
+        let a = entry_with_dangling_link(&store);
+        let hook = hook(&store, "deny", HookPosition::PreUpdate);
+
+        assert_eq!(Mode::Deny, hook.mode);
+        assert!(hook.access(&a).is_err());
+    }
+
+    #[test]
+    fn test_prune() {
+        let store = store("prune");
+        let mut a = entry_with_dangling_link(&store);
+        let hook = hook(&store, "prune", HookPosition::PreUpdate);
+
+        assert_eq!(Mode::Prune, hook.mode);
+        assert!(hook.access_mut(&mut a).is_ok());
+        assert_eq!(vec![id(&store, "b~0.1.0")], a.get_internal_links().unwrap());
+    }
+
+    #[test]
+    fn test_prune_rejected_at_non_mutable_positions() {
+        let store = store("prune-rejected");
+        for pos in vec![HookPosition::PreCreate, HookPosition::PreRetrieve,
+                        HookPosition::PreDelete, HookPosition::PostDelete]
+        {
+            let hook = hook(&store, "prune", pos);
+            assert_eq!(Mode::Warn, hook.mode);
+            match hook.accessor() {
+                HDA::NonMutableAccess(_) => {},
+                _ => panic!("Unexpected mutable accessor at {:?}", hook.position),
+            }
+        }
+    }
+
+}